@@ -0,0 +1,388 @@
+//! Contains the generation of the endpoints described by the operations of an
+//! OpenAPI document.
+
+use std::collections::HashSet;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use serde_json::Value;
+
+use crate::{
+    models::{docs, unique_in, Context},
+    names, Error,
+};
+
+/// The methods of the operations of a path item, in the order they're
+/// generated.
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Header parameters which OpenAPI requires to be ignored, since they're set
+/// by the codecs and clients.
+const IGNORED_HEADERS: [&str; 3] = ["accept", "authorization", "content-type"];
+
+/// Types which implement `ToString` and can be used in paths and headers.
+const SCALARS: [&str; 8] = ["bool", "i32", "i64", "u32", "u64", "f32", "f64", "String"];
+
+/// The kind of request body sent by an endpoint.
+enum Body {
+    Json,
+    Form,
+    Multipart,
+    Raw,
+}
+
+/// Generates an endpoint for each operation of the document.
+pub(crate) fn endpoints(cx: &mut Context<'_>) -> Result<(), Error> {
+    let paths = match cx.spec().get("paths") {
+        Some(Value::Object(paths)) => paths,
+        _ => return Ok(()),
+    };
+    for (path, item) in paths {
+        let item = cx.resolve(item)?;
+        for method in METHODS {
+            if let Some(operation) = item.get(method) {
+                endpoint(cx, path, method, item, operation)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Generates the endpoint of a single operation.
+///
+/// The endpoint is named after the `operationId` of the operation, or after
+/// its method and path when it has none. Its fields are the parameters of the
+/// operation followed by the properties of its request body.
+fn endpoint<'a>(
+    cx: &mut Context<'a>,
+    path: &str,
+    method: &str,
+    item: &'a Value,
+    operation: &'a Value,
+) -> Result<(), Error> {
+    let name = match operation.get("operationId").and_then(Value::as_str) {
+        Some(id) => names::pascal(id),
+        None => names::pascal(&format!("{} {}", method, path)),
+    };
+    let name = cx.unique(&name);
+    let id = names::ident(&name);
+
+    let mut used = HashSet::new();
+    let mut fields = Vec::new();
+    let mut template = path.trim_start_matches('/').to_string();
+    for param in parameters(cx, item, operation)? {
+        let key = str_field(param, "name")?;
+        let location = str_field(param, "in")?;
+        if location == "cookie"
+            || (location == "header" && IGNORED_HEADERS.contains(&key.to_lowercase().as_str()))
+        {
+            continue;
+        }
+
+        let field = unique_in(&mut used, names::snake(key));
+        let field_id = names::ident(&field);
+        let required = location == "path" || param.get("required") == Some(&Value::Bool(true));
+        let hint = format!("{}{}", name, names::pascal(key));
+        let mut ty = match param.get("schema") {
+            Some(schema) => cx.ty(schema, &hint)?,
+            None => quote! { String },
+        };
+        let attr = match location {
+            "path" => {
+                template = template.replace(&format!("{{{}}}", key), &format!("{{{}}}", field_id));
+                ty = scalar(ty);
+                quote! { #[endpoint(path)] }
+            }
+            "header" => {
+                ty = scalar(ty);
+                quote! { #[endpoint(header = #key)] }
+            }
+            _ => {
+                let style = match (
+                    param.get("style").and_then(Value::as_str),
+                    param.get("explode"),
+                ) {
+                    (Some("deepObject"), _) => Some("deepObject"),
+                    (None, Some(Value::Bool(false))) | (Some("form"), Some(Value::Bool(false))) => {
+                        Some("comma")
+                    }
+                    _ => None,
+                };
+                let rename = (field != key).then(|| quote! { #[serde(rename = #key)] });
+                match style {
+                    Some(s) => quote! { #[endpoint(query(style = #s))] #rename },
+                    None => quote! { #[endpoint(query)] #rename },
+                }
+            }
+        };
+        let ty = match required {
+            true => ty,
+            false => quote! { Option<#ty> },
+        };
+        let docs = docs(param.get("description"));
+        fields.push(quote! {
+            #docs
+            #attr
+            pub #field_id: #ty,
+        });
+    }
+
+    // Add the request body
+    let mut request_type = None;
+    if let Some(body) = operation.get("requestBody") {
+        let body = cx.resolve(body)?;
+        let (kind, schema) = request_body(body);
+        let raw = || {
+            quote! {
+                /// The serialized request body.
+                #[endpoint(raw)]
+                pub body: Vec<u8>,
+            }
+        };
+        match kind {
+            Body::Json | Body::Form => {
+                if let Body::Form = kind {
+                    request_type = Some("FORM");
+                }
+                let properties = match schema {
+                    Some(s) => cx.properties(s)?,
+                    None => Vec::new(),
+                };
+                let model = schema.filter(|s| s.get("$ref").is_some());
+                if properties.is_empty() {
+                    fields.push(raw());
+                } else if let Some(model) = model {
+                    // Models are sent as the body by flattening them
+                    let ty = cx.ty(model, &name)?;
+                    let field = unique_in(&mut used, names::snake(&ty.to_string()));
+                    let field_id = names::ident(&field);
+                    fields.push(quote! {
+                        #[endpoint(body)]
+                        #[serde(flatten)]
+                        pub #field_id: #ty,
+                    });
+                } else {
+                    for (key, property, required) in properties {
+                        let field = unique_in(&mut used, names::snake(key));
+                        let field_id = names::ident(&field);
+                        let hint = format!("{}{}", name, names::pascal(key));
+                        let ty = cx.field_ty(property, &hint, required)?;
+                        let docs = docs(property.get("description"));
+                        let rename = (field != key).then(|| quote! { #[serde(rename = #key)] });
+                        fields.push(quote! {
+                            #docs
+                            #[endpoint(body)]
+                            #rename
+                            pub #field_id: #ty,
+                        });
+                    }
+                }
+            }
+            Body::Multipart => {
+                let properties = match schema {
+                    Some(s) => cx.properties(s)?,
+                    None => Vec::new(),
+                };
+                for (key, property, required) in properties {
+                    let field = unique_in(&mut used, names::snake(key));
+                    let field_id = names::ident(&field);
+                    let property = cx.resolve(property)?;
+                    let binary = property.get("format").and_then(Value::as_str) == Some("binary")
+                        || property.get("contentMediaType").is_some();
+                    let (attr, ty) = match binary {
+                        true => (quote! { file }, quote! { rustified::multipart::File }),
+                        false => {
+                            let hint = format!("{}{}", name, names::pascal(key));
+                            (quote! { part }, scalar(cx.ty(property, &hint)?))
+                        }
+                    };
+                    let ty = match required {
+                        true => ty,
+                        false => quote! { Option<#ty> },
+                    };
+                    let docs = docs(property.get("description"));
+                    let rename = (field != key).then(|| quote! { #[serde(rename = #key)] });
+                    fields.push(quote! {
+                        #docs
+                        #[endpoint(#attr)]
+                        #rename
+                        pub #field_id: #ty,
+                    });
+                }
+            }
+            Body::Raw => fields.push(raw()),
+        }
+    }
+
+    // Build the parameters of the endpoint attribute
+    let mut params = vec![quote! { path = #template }];
+    if method != "get" {
+        let method = method.to_uppercase();
+        params.push(quote! { method = #method });
+    }
+    if let Some(t) = request_type {
+        params.push(quote! { request_type = #t });
+    }
+    let responses = operation.get("responses").and_then(Value::as_object);
+    let success = responses
+        .into_iter()
+        .flatten()
+        .find(|(status, _)| status.starts_with('2'));
+    if let Some((_, response)) = success {
+        let response = cx.resolve(response)?;
+        if let Some((ty, response_type)) = response_ty(cx, response, &format!("{}Response", name))?
+        {
+            let ty = type_string(&ty);
+            params.push(quote! { response = #ty });
+            if let Some(t) = response_type {
+                params.push(quote! { response_type = #t });
+            }
+        }
+    }
+    // Errors are parsed from the default response, or from the first client or
+    // server error response with a JSON body when there's no default
+    let errors = responses
+        .into_iter()
+        .flatten()
+        .filter(|(status, _)| status.starts_with('4') || status.starts_with('5'));
+    for (_, response) in responses
+        .and_then(|r| r.get_key_value("default"))
+        .into_iter()
+        .chain(errors)
+    {
+        let response = cx.resolve(response)?;
+        if let Some((ty, None)) = response_ty(cx, response, &format!("{}Error", name))? {
+            let ty = type_string(&ty);
+            params.push(quote! { error = #ty });
+            break;
+        }
+    }
+
+    let summary = operation.get("summary").and_then(Value::as_str);
+    let description = operation.get("description").and_then(Value::as_str);
+    let description = match (summary, description) {
+        (Some(s), Some(d)) => Some(Value::String(format!("{}\n\n{}", s, d))),
+        (s, d) => s.or(d).map(|s| Value::String(s.to_string())),
+    };
+    let docs = docs(description.as_ref());
+    cx.items.push(quote! {
+        #docs
+        #[derive(Clone, Debug, rustified_derive::Endpoint)]
+        #[endpoint(#(#params),*)]
+        pub struct #id {
+            #(#fields)*
+        }
+    });
+    Ok(())
+}
+
+/// Returns the parameters of an operation, which are those of its path item
+/// unless the operation overrides them.
+fn parameters<'a>(
+    cx: &Context<'a>,
+    item: &'a Value,
+    operation: &'a Value,
+) -> Result<Vec<&'a Value>, Error> {
+    let mut result: Vec<&'a Value> = Vec::new();
+    for source in [item, operation] {
+        for param in source
+            .get("parameters")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let param = cx.resolve(param)?;
+            let same =
+                |p: &&Value| p.get("name") == param.get("name") && p.get("in") == param.get("in");
+            match result.iter().position(same) {
+                Some(i) => result[i] = param,
+                None => result.push(param),
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Returns the kind and schema of a request body, preferring JSON over forms
+/// and forms over all other media types.
+fn request_body(body: &Value) -> (Body, Option<&Value>) {
+    let content = match body.get("content").and_then(Value::as_object) {
+        Some(c) => c,
+        None => return (Body::Raw, None),
+    };
+    let find = |f: fn(&str) -> bool| content.iter().find(|(media, _)| f(media));
+    let (kind, media) = if let Some((_, m)) = find(is_json) {
+        (Body::Json, m)
+    } else if let Some((_, m)) = find(|m| m == "application/x-www-form-urlencoded") {
+        (Body::Form, m)
+    } else if let Some((_, m)) = find(|m| m == "multipart/form-data") {
+        (Body::Multipart, m)
+    } else {
+        return (Body::Raw, None);
+    };
+    (kind, media.get("schema"))
+}
+
+/// Returns the type of the body of a response along with the response type
+/// used for decoding it, or [None] if it has no body.
+///
+/// JSON bodies are parsed into the type described by their schema, while text
+/// bodies are returned as a [String] and all others as bytes.
+fn response_ty<'a>(
+    cx: &mut Context<'a>,
+    response: &'a Value,
+    hint: &str,
+) -> Result<Option<(TokenStream, Option<&'static str>)>, Error> {
+    let content = match response.get("content").and_then(Value::as_object) {
+        Some(c) if !c.is_empty() => c,
+        _ => return Ok(None),
+    };
+    if let Some((_, media)) = content.iter().find(|(m, _)| is_json(m)) {
+        let ty = match media.get("schema") {
+            Some(schema) => cx.ty(schema, hint)?,
+            None => quote! { serde_json::Value },
+        };
+        return Ok(Some((ty, None)));
+    }
+    if content.keys().any(|m| m.starts_with("text/")) {
+        return Ok(Some((quote! { String }, Some("TEXT"))));
+    }
+    Ok(Some((quote! { Vec<u8> }, Some("BYTES"))))
+}
+
+/// Returns the value of a string field of an object.
+fn str_field<'a>(value: &'a Value, field: &str) -> Result<&'a str, Error> {
+    value
+        .get(field)
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::Invalid {
+            message: format!("Expected a `{}` in {}", field, value),
+        })
+}
+
+/// Returns whether the given media type is JSON, as in `application/json` or
+/// `application/problem+json`.
+fn is_json(media: &str) -> bool {
+    let media = media.split(';').next().unwrap_or_default().trim();
+    media == "application/json" || media.ends_with("+json")
+}
+
+/// Returns the given type if it implements `ToString`, or [String] otherwise.
+fn scalar(ty: TokenStream) -> TokenStream {
+    match SCALARS.contains(&ty.to_string().as_str()) {
+        true => ty,
+        false => quote! { String },
+    }
+}
+
+/// Formats a type the way it's written by hand, as in `Vec<Pet>`.
+fn type_string(ty: &TokenStream) -> String {
+    ty.to_string()
+        .replace(" :: ", "::")
+        .replace(" < ", "<")
+        .replace(" <", "<")
+        .replace(" >", ">")
+        .replace(" ,", ",")
+}
@@ -0,0 +1,106 @@
+//! Generates [rustified][1] endpoints from an [OpenAPI 3][2] document.
+//!
+//! Each operation of the document becomes a struct deriving `Endpoint`, with a
+//! field for each of its parameters and the properties of its request body.
+//! The schemas under `components/schemas`, along with objects and enums which
+//! are declared inline, become serde models. Descriptions and summaries are
+//! kept as doc comments.
+//!
+//! The response of an endpoint is taken from the first successful response of
+//! its operation. Its error is taken from the `default` response, or from the
+//! first client or server error response with a JSON body when there's none.
+//!
+//! The generator can be called from a build script, which writes the endpoints
+//! into a file included by the crate:
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("api.rs");
+//!     rustified_codegen::generate_file("openapi.yaml", out).unwrap();
+//!     println!("cargo:rerun-if-changed=openapi.yaml");
+//! }
+//! ```
+//!
+//! ```ignore
+//! // src/api.rs
+//! include!(concat!(env!("OUT_DIR"), "/api.rs"));
+//! ```
+//!
+//! The same is done by the `rustified-codegen` binary, which writes the
+//! endpoints to standard output or to the file given with `-o`. The generated
+//! code depends on the `rustified`, `rustified_derive`, `serde` (with the
+//! `derive` feature) and `serde_json` crates.
+//!
+//! Documents are read from local files only, in either JSON or YAML, and only
+//! references within the document are resolved. Schemas combining several
+//! others with `oneOf` or `anyOf` are represented by a `serde_json::Value`.
+//!
+//! [1]: https://docs.rs/rustified
+//! [2]: https://spec.openapis.org/oas/v3.1.0
+
+use std::{fs, path::Path};
+
+use serde_json::Value;
+use thiserror::Error;
+
+mod endpoints;
+mod models;
+mod names;
+
+/// The error type returned by the generator
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Invalid OpenAPI document: {message}")]
+    Invalid { message: String },
+    #[error("Error reading or writing {path}: {source}")]
+    Io {
+        source: std::io::Error,
+        path: String,
+    },
+    #[error("Error parsing OpenAPI document: {source}")]
+    Parse { source: serde_yaml::Error },
+    #[error("Unresolved reference in OpenAPI document: {reference}")]
+    Reference { reference: String },
+}
+
+/// Returns the source code of the endpoints and models described by the given
+/// OpenAPI document, which is either JSON or YAML.
+pub fn generate(spec: &str) -> Result<String, Error> {
+    // YAML is a superset of JSON, which allows parsing both the same way
+    let spec: Value = serde_yaml::from_str(spec).map_err(|e| Error::Parse { source: e })?;
+    match spec.get("openapi").and_then(Value::as_str) {
+        Some(v) if v.starts_with("3.") => {}
+        _ => {
+            return Err(Error::Invalid {
+                message: "Only OpenAPI 3 documents are supported".to_string(),
+            })
+        }
+    }
+
+    let mut cx = models::Context::new(&spec);
+    cx.components()?;
+    endpoints::endpoints(&mut cx)?;
+
+    let items = cx.items;
+    let file: syn::File =
+        syn::parse2(quote::quote! { #(#items)* }).map_err(|e| Error::Invalid {
+            message: e.to_string(),
+        })?;
+    Ok(format!(
+        "// This file is generated by rustified_codegen. Do not edit it by hand.\n\n{}",
+        prettyplease::unparse(&file)
+    ))
+}
+
+/// Reads the OpenAPI document at the given path and writes the endpoints and
+/// models it describes to the output path.
+pub fn generate_file(input: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<(), Error> {
+    let io = |path: &Path| {
+        let path = path.display().to_string();
+        move |e| Error::Io { source: e, path }
+    };
+    let (input, output) = (input.as_ref(), output.as_ref());
+    let spec = fs::read_to_string(input).map_err(io(input))?;
+    fs::write(output, generate(&spec)?).map_err(io(output))
+}
@@ -0,0 +1,46 @@
+//! Generates rustified endpoints from an OpenAPI document.
+//!
+//! ```text
+//! rustified-codegen <SPEC> [-o <OUTPUT>]
+//! ```
+
+use std::{env, fs, process};
+
+const USAGE: &str = "Usage: rustified-codegen <SPEC> [-o <OUTPUT>]";
+
+fn main() {
+    let mut input = None;
+    let mut output = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => output = args.next(),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if input.is_none() => input = Some(arg),
+            _ => exit(USAGE),
+        }
+    }
+    let input = input.unwrap_or_else(|| exit(USAGE));
+
+    let result = match output {
+        Some(output) => rustified_codegen::generate_file(&input, output),
+        None => fs::read_to_string(&input)
+            .map_err(|e| rustified_codegen::Error::Io {
+                source: e,
+                path: input.clone(),
+            })
+            .and_then(|spec| rustified_codegen::generate(&spec))
+            .map(|code| print!("{}", code)),
+    };
+    if let Err(e) = result {
+        exit(&e.to_string());
+    }
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
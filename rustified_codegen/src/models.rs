@@ -0,0 +1,385 @@
+//! Contains the generation of the serde models described by the schemas of an
+//! OpenAPI document.
+
+use std::collections::{HashMap, HashSet};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use serde_json::Value;
+
+use crate::{names, Error};
+
+/// The prefix of references to the schemas under `components/schemas`.
+const SCHEMAS: &str = "#/components/schemas/";
+
+/// The state shared by the generation of all items of a document.
+pub(crate) struct Context<'a> {
+    spec: &'a Value,
+    /// The names of all types generated so far.
+    names: HashSet<String>,
+    /// The names of the types generated for the schemas under
+    /// `components/schemas`, by their reference.
+    refs: HashMap<String, String>,
+    /// The generated items, in the order they are written.
+    pub items: Vec<TokenStream>,
+}
+
+impl<'a> Context<'a> {
+    /// Returns a new context for the given document.
+    pub fn new(spec: &'a Value) -> Self {
+        Context {
+            spec,
+            names: HashSet::new(),
+            refs: HashMap::new(),
+            items: Vec::new(),
+        }
+    }
+
+    /// Returns the document being generated.
+    pub fn spec(&self) -> &'a Value {
+        self.spec
+    }
+
+    /// Generates a model for each schema under `components/schemas`.
+    ///
+    /// The names of all models are reserved before generating any of them,
+    /// which allows schemas to refer to each other in any order.
+    pub fn components(&mut self) -> Result<(), Error> {
+        let schemas = match self.spec.pointer("/components/schemas") {
+            Some(Value::Object(schemas)) => schemas,
+            _ => return Ok(()),
+        };
+        for key in schemas.keys() {
+            let name = self.unique(&names::pascal(key));
+            self.refs.insert(format!("{}{}", SCHEMAS, key), name);
+        }
+        for (key, schema) in schemas {
+            let name = self.refs[&format!("{}{}", SCHEMAS, key)].clone();
+            self.model(&name, schema)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the given name, suffixed with a number if it's already taken,
+    /// and reserves it.
+    pub fn unique(&mut self, name: &str) -> String {
+        let mut result = name.to_string();
+        let mut i = 2;
+        while self.names.contains(&result) {
+            result = format!("{}{}", name, i);
+            i += 1;
+        }
+        self.names.insert(result.clone());
+        result
+    }
+
+    /// Follows the `$ref` of the given value, if it has one, until reaching a
+    /// value which isn't a reference.
+    ///
+    /// Only references to the document itself are supported.
+    pub fn resolve(&self, mut value: &'a Value) -> Result<&'a Value, Error> {
+        let mut seen = HashSet::new();
+        while let Some(r) = value.get("$ref").and_then(Value::as_str) {
+            if !seen.insert(r) {
+                return Err(Error::Reference {
+                    reference: r.to_string(),
+                });
+            }
+            value = r
+                .strip_prefix('#')
+                .and_then(|pointer| self.spec.pointer(pointer))
+                .ok_or_else(|| Error::Reference {
+                    reference: r.to_string(),
+                })?;
+        }
+        Ok(value)
+    }
+
+    /// Generates a named type for the given schema.
+    ///
+    /// Objects become structs and enums of strings become enums, while all
+    /// other schemas become an alias of the type they describe.
+    pub fn model(&mut self, name: &str, schema: &'a Value) -> Result<(), Error> {
+        let id = names::ident(name);
+        let docs = docs(self.resolve(schema)?.get("description"));
+
+        let item = if schema.get("$ref").is_some() {
+            let ty = self.ty(schema, name)?;
+            quote! {
+                #docs
+                pub type #id = #ty;
+            }
+        } else if let Some(values) = string_enum(schema) {
+            let mut variants = HashSet::new();
+            let variants = values.iter().map(|value| {
+                let name = unique_in(&mut variants, names::pascal(value));
+                let variant = names::ident(&name);
+                let rename = (&name != value).then(|| quote! { #[serde(rename = #value)] });
+                quote! {
+                    #rename
+                    #variant
+                }
+            });
+            quote! {
+                #docs
+                #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+                pub enum #id {
+                    #(#variants,)*
+                }
+            }
+        } else if is_object(schema) && !self.properties(schema)?.is_empty() {
+            let fields = self.fields(name, schema)?;
+            quote! {
+                #docs
+                #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+                pub struct #id {
+                    #(#fields)*
+                }
+            }
+        } else {
+            let ty = self.ty(schema, name)?;
+            quote! {
+                #docs
+                pub type #id = #ty;
+            }
+        };
+        self.items.push(item);
+        Ok(())
+    }
+
+    /// Returns the type of a value described by the given schema, which is
+    /// wrapped in an [Option] when it isn't required or is nullable.
+    ///
+    /// Objects and enums which are declared inline are generated as a new type
+    /// named after the given hint.
+    pub fn field_ty(
+        &mut self,
+        schema: &'a Value,
+        hint: &str,
+        required: bool,
+    ) -> Result<TokenStream, Error> {
+        let ty = self.ty(schema, hint)?;
+        Ok(match required && !nullable(self.resolve(schema)?) {
+            true => ty,
+            false => quote! { Option<#ty> },
+        })
+    }
+
+    /// Returns the type of a value described by the given schema, ignoring
+    /// whether it's nullable.
+    pub fn ty(&mut self, schema: &'a Value, hint: &str) -> Result<TokenStream, Error> {
+        if let Some(r) = schema.get("$ref").and_then(Value::as_str) {
+            if let Some(name) = self.refs.get(r) {
+                let id = names::ident(name);
+                return Ok(quote! { #id });
+            }
+            return self.ty(self.resolve(schema)?, hint);
+        }
+
+        // Compositions of a single schema, possibly along with `null`, are
+        // the same as that schema
+        for key in ["allOf", "anyOf", "oneOf"] {
+            if let Some(Value::Array(schemas)) = schema.get(key) {
+                let mut schemas = schemas.iter().filter(|s| !is_null(s));
+                return match (schemas.next(), schemas.next()) {
+                    (Some(s), None) => self.ty(s, hint),
+                    (Some(_), Some(_))
+                        if key == "allOf" && !self.properties(schema)?.is_empty() =>
+                    {
+                        self.inline(schema, hint)
+                    }
+                    _ => Ok(quote! { serde_json::Value }),
+                };
+            }
+        }
+
+        if string_enum(schema).is_some() {
+            return self.inline(schema, hint);
+        }
+        Ok(match primary_type(schema) {
+            Some("boolean") => quote! { bool },
+            Some("integer") => match schema.get("format").and_then(Value::as_str) {
+                Some("int32") => quote! { i32 },
+                Some("uint32") => quote! { u32 },
+                Some("uint64") => quote! { u64 },
+                _ => quote! { i64 },
+            },
+            Some("number") => match schema.get("format").and_then(Value::as_str) {
+                Some("float") => quote! { f32 },
+                _ => quote! { f64 },
+            },
+            Some("string") => quote! { String },
+            Some("array") => {
+                let items = match schema.get("items") {
+                    Some(items) => self.ty(items, &format!("{}Item", hint))?,
+                    None => quote! { serde_json::Value },
+                };
+                quote! { Vec<#items> }
+            }
+            Some("object") | None if is_object(schema) => {
+                if !self.properties(schema)?.is_empty() {
+                    return self.inline(schema, hint);
+                }
+                let values = match schema.get("additionalProperties") {
+                    Some(Value::Object(_)) => {
+                        let values = &schema["additionalProperties"];
+                        self.ty(values, &format!("{}Value", hint))?
+                    }
+                    _ => quote! { serde_json::Value },
+                };
+                quote! { std::collections::HashMap<String, #values> }
+            }
+            _ => quote! { serde_json::Value },
+        })
+    }
+
+    /// Generates a model for a schema declared inline and returns its type.
+    fn inline(&mut self, schema: &'a Value, hint: &str) -> Result<TokenStream, Error> {
+        let name = self.unique(&names::pascal(hint));
+        self.model(&name, schema)?;
+        let id = names::ident(&name);
+        Ok(quote! { #id })
+    }
+
+    /// Returns the properties of an object schema along with whether they are
+    /// required, including those of all schemas it's composed of with `allOf`.
+    pub fn properties(&self, schema: &'a Value) -> Result<Vec<(&'a str, &'a Value, bool)>, Error> {
+        let schema = self.resolve(schema)?;
+        let mut result = Vec::new();
+        for s in schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            result.extend(self.properties(s)?);
+        }
+        let required = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect::<HashSet<_>>();
+        if let Some(Value::Object(properties)) = schema.get("properties") {
+            for (name, property) in properties {
+                result.push((name.as_str(), property, required.contains(name.as_str())));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns the fields of the struct generated for an object schema.
+    fn fields(&mut self, name: &str, schema: &'a Value) -> Result<Vec<TokenStream>, Error> {
+        let mut used = HashSet::new();
+        let mut fields = Vec::new();
+        for (key, property, required) in self.properties(schema)? {
+            let field = unique_in(&mut used, names::snake(key));
+            let id = names::ident(&field);
+            let hint = format!("{}{}", name, names::pascal(key));
+            let mut ty = self.ty(property, &hint)?;
+
+            // Types which directly contain themselves need to be boxed
+            if names::ident(name) == ty.to_string() {
+                ty = quote! { Box<#ty> };
+            }
+            let optional = !required || nullable(self.resolve(property)?);
+            let ty = match optional {
+                true => quote! { Option<#ty> },
+                false => ty,
+            };
+
+            let docs = docs(property.get("description"));
+            let mut serde = Vec::new();
+            if field != key {
+                serde.push(quote! { rename = #key });
+            }
+            if optional {
+                serde.push(quote! { skip_serializing_if = "Option::is_none" });
+            }
+            let serde = (!serde.is_empty()).then(|| quote! { #[serde(#(#serde),*)] });
+            fields.push(quote! {
+                #docs
+                #serde
+                pub #id: #ty,
+            });
+        }
+        Ok(fields)
+    }
+}
+
+/// Returns the given name, suffixed with a number if it's already in the given
+/// set, and adds it to the set.
+pub(crate) fn unique_in(used: &mut HashSet<String>, name: String) -> String {
+    let mut result = name.clone();
+    let mut i = 2;
+    while used.contains(&result) {
+        result = format!("{}{}", name, i);
+        i += 1;
+    }
+    used.insert(result.clone());
+    result
+}
+
+/// Returns doc comments holding the given description, one for each line.
+pub(crate) fn docs(description: Option<&Value>) -> TokenStream {
+    let lines = description
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+        .lines()
+        .map(|l| format!(" {}", l.trim_end()));
+    quote! { #(#[doc = #lines])* }
+}
+
+/// Returns the type of a schema, ignoring `null` when it's given a list of
+/// types.
+fn primary_type(schema: &Value) -> Option<&str> {
+    match schema.get("type") {
+        Some(Value::String(t)) => Some(t),
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null"),
+        _ => None,
+    }
+}
+
+/// Returns whether the given schema describes an object.
+fn is_object(schema: &Value) -> bool {
+    match primary_type(schema) {
+        Some(t) => t == "object",
+        None => schema.get("properties").is_some() || schema.get("allOf").is_some(),
+    }
+}
+
+/// Returns whether the given schema only allows `null`.
+fn is_null(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("null")
+}
+
+/// Returns whether the given schema allows `null`, either using the `nullable`
+/// keyword of OpenAPI 3.0 or a list of types including `null`.
+fn nullable(schema: &Value) -> bool {
+    let composed = ["anyOf", "oneOf"]
+        .iter()
+        .filter_map(|k| schema.get(*k).and_then(Value::as_array))
+        .flatten()
+        .any(is_null);
+    let typed = match schema.get("type") {
+        Some(Value::Array(types)) => types.iter().any(|t| t == "null"),
+        _ => false,
+    };
+    composed || typed || schema.get("nullable") == Some(&Value::Bool(true))
+}
+
+/// Returns the values of a schema which is an enum of strings.
+fn string_enum(schema: &Value) -> Option<Vec<&str>> {
+    let values = schema.get("enum")?.as_array()?;
+    let values = values
+        .iter()
+        .filter(|v| !v.is_null())
+        .map(Value::as_str)
+        .collect::<Option<Vec<_>>>()?;
+    (!values.is_empty()).then_some(values)
+}
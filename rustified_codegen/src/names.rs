@@ -0,0 +1,82 @@
+//! Contains helpers for turning the names used in an OpenAPI document into
+//! Rust identifiers.
+
+use proc_macro2::{Ident, Span};
+
+/// Keywords which can't be used as identifiers, even in their raw form.
+const RESERVED: [&str; 5] = ["crate", "self", "Self", "super", "_"];
+
+/// Splits a name into its words at non-alphanumeric characters and at the
+/// boundaries of camel case, as in `petId` or `HTTPStatus`.
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let chars = name.chars().collect::<Vec<_>>();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_lower) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Returns the given name in `PascalCase`, as used for types and variants.
+pub(crate) fn pascal(name: &str) -> String {
+    let name = words(name)
+        .iter()
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(first) => first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<String>();
+    match name.chars().next() {
+        None => "Empty".to_string(),
+        Some(c) if c.is_numeric() => format!("V{}", name),
+        Some(_) => name,
+    }
+}
+
+/// Returns the given name in `snake_case`, as used for fields.
+pub(crate) fn snake(name: &str) -> String {
+    let name = words(name)
+        .iter()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    match name.chars().next() {
+        None => "value".to_string(),
+        Some(c) if c.is_numeric() => format!("_{}", name),
+        Some(_) => name,
+    }
+}
+
+/// Returns an identifier for the given name, using its raw form for keywords.
+pub(crate) fn ident(name: &str) -> Ident {
+    if RESERVED.contains(&name) {
+        return Ident::new(&format!("{}_", name), Span::call_site());
+    }
+    match syn::parse_str::<Ident>(name) {
+        Ok(id) => id,
+        Err(_) => Ident::new_raw(name, Span::call_site()),
+    }
+}
@@ -0,0 +1,116 @@
+use rustified::endpoint::Endpoint;
+use rustified_codegen::{generate, Error};
+use serde_json::json;
+
+#[allow(dead_code)]
+mod petstore {
+    include!("specs/petstore.rs");
+}
+
+const PETSTORE: &str = include_str!("specs/petstore.yaml");
+
+#[test]
+fn test_generate() {
+    let code = generate(PETSTORE).unwrap();
+    assert_eq!(
+        code,
+        include_str!("specs/petstore.rs"),
+        "Generated code differs, regenerate it with `cargo run -p rustified_codegen -- \
+         tests/specs/petstore.yaml -o tests/specs/petstore.rs`"
+    );
+}
+
+#[test]
+fn test_generate_json() {
+    let spec = json!({
+        "openapi": "3.1.0",
+        "info": { "title": "Test", "version": "1.0.0" },
+        "paths": {
+            "/users/{id}": {
+                "delete": {
+                    "parameters": [
+                        { "name": "id", "in": "path", "schema": { "type": "string" } },
+                        { "name": "Authorization", "in": "header", "schema": { "type": "string" } },
+                    ],
+                    "responses": { "204": { "description": "Deleted" } },
+                },
+            },
+        },
+    });
+    let code = generate(&spec.to_string()).unwrap();
+    assert!(code.contains("#[endpoint(path = \"users/{id}\", method = \"DELETE\")]"));
+    assert!(
+        code.contains("pub struct DeleteUsersId {\n    #[endpoint(path)]\n    pub id: String,\n}")
+    );
+}
+
+#[test]
+fn test_generate_invalid() {
+    let swagger = r#"{ "swagger": "2.0", "paths": {} }"#;
+    assert!(matches!(generate(swagger), Err(Error::Invalid { .. })));
+
+    let spec = json!({
+        "openapi": "3.0.0",
+        "paths": {
+            "/test": {
+                "get": {
+                    "parameters": [{ "$ref": "#/components/parameters/Missing" }],
+                    "responses": {},
+                },
+            },
+        },
+    });
+    assert!(matches!(
+        generate(&spec.to_string()),
+        Err(Error::Reference { reference }) if reference == "#/components/parameters/Missing"
+    ));
+    assert!(matches!(generate("{"), Err(Error::Parse { .. })));
+}
+
+#[test]
+fn test_generated() {
+    use petstore::*;
+
+    let endpoint = ListPets {
+        limit: Some(10),
+        tags: Some(vec!["a".to_string(), "b".to_string()]),
+        x_request_id: "1234".to_string(),
+    };
+    assert_eq!(endpoint.path(), "pets");
+    assert_eq!(endpoint.query().unwrap().unwrap(), "limit=10&tags=a,b");
+    assert_eq!(endpoint.headers().unwrap().unwrap()["X-Request-ID"], "1234");
+
+    let endpoint = CreatePet {
+        new_pet: NewPet {
+            name: "Rex".to_string(),
+            tag: None,
+        },
+    };
+    assert_eq!(endpoint.body().unwrap().unwrap(), br#"{"name":"Rex"}"#);
+
+    let endpoint = UpdatePet {
+        pet_id: 1,
+        name: None,
+        status: Some(UpdatePetStatus::Sold),
+    };
+    assert_eq!(endpoint.path(), "pets/1");
+    assert_eq!(endpoint.body().unwrap().unwrap(), br#"{"status":"sold"}"#);
+
+    let endpoint = UploadPhoto {
+        pet_id: 1,
+        file: rustified::multipart::File::new(b"data".to_vec()),
+        photo_caption: Some("Rex".to_string()),
+    };
+    let body = String::from_utf8(endpoint.body().unwrap().unwrap()).unwrap();
+    assert!(body.contains("name=\"photoCaption\""));
+
+    let pet: Pet = serde_json::from_value(json!({
+        "id": 1,
+        "name": "Rex",
+        "type": "guinea-pig",
+        "owner": { "displayName": "Alice" },
+    }))
+    .unwrap();
+    assert_eq!(pet.r#type, Some(PetType::GuineaPig));
+    assert_eq!(pet.owner.unwrap().display_name.unwrap(), "Alice");
+}
@@ -0,0 +1,103 @@
+// This file is generated by rustified_codegen. Do not edit it by hand.
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Error {
+    pub code: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct NewPet {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct PetOwner {
+    #[serde(rename = "displayName", skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+}
+/// A pet in the store.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Pet {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    pub id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<PetOwner>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Box<Pet>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<PetType>,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum PetType {
+    #[serde(rename = "cat")]
+    Cat,
+    #[serde(rename = "dog")]
+    Dog,
+    #[serde(rename = "guinea-pig")]
+    GuineaPig,
+}
+/// Lists all pets.
+#[derive(Clone, Debug, rustified_derive::Endpoint)]
+#[endpoint(path = "pets", response = "Vec<Pet>", error = "Error")]
+pub struct ListPets {
+    /// The maximum number of pets to return.
+    #[endpoint(query)]
+    pub limit: Option<i32>,
+    #[endpoint(query(style = "comma"))]
+    pub tags: Option<Vec<String>>,
+    #[endpoint(header = "X-Request-ID")]
+    pub x_request_id: String,
+}
+#[derive(Clone, Debug, rustified_derive::Endpoint)]
+#[endpoint(path = "pets", method = "POST", response = "Pet")]
+pub struct CreatePet {
+    #[endpoint(body)]
+    #[serde(flatten)]
+    pub new_pet: NewPet,
+}
+/// Returns a pet by its id.
+///
+/// Pets which were deleted are not returned.
+#[derive(Clone, Debug, rustified_derive::Endpoint)]
+#[endpoint(path = "pets/{pet_id}", response = "Pet", error = "Error")]
+pub struct GetPetsPetId {
+    #[endpoint(path)]
+    pub pet_id: i64,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum UpdatePetStatus {
+    #[serde(rename = "available")]
+    Available,
+    #[serde(rename = "sold")]
+    Sold,
+}
+#[derive(Clone, Debug, rustified_derive::Endpoint)]
+#[endpoint(path = "pets/{pet_id}", method = "PATCH")]
+pub struct UpdatePet {
+    #[endpoint(path)]
+    pub pet_id: i64,
+    #[endpoint(body)]
+    pub name: Option<String>,
+    #[endpoint(body)]
+    pub status: Option<UpdatePetStatus>,
+}
+#[derive(Clone, Debug, rustified_derive::Endpoint)]
+#[endpoint(
+    path = "pets/{pet_id}/photo",
+    method = "PUT",
+    response = "String",
+    response_type = "TEXT"
+)]
+pub struct UploadPhoto {
+    #[endpoint(path)]
+    pub pet_id: i64,
+    #[endpoint(file)]
+    pub file: rustified::multipart::File,
+    #[endpoint(part)]
+    #[serde(rename = "photoCaption")]
+    pub photo_caption: Option<String>,
+}
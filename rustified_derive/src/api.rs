@@ -0,0 +1,211 @@
+//! Contains the `api` attribute macro which generates a typed API client from
+//! a trait listing the endpoints of an API.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    spanned::Spanned, FnArg, Ident, ItemTrait, Pat, PathArguments, ReturnType, TraitItem,
+    TraitItemMethod, Type,
+};
+
+use crate::Error;
+
+/// Methods generated on every client, which API methods cannot be named after.
+const RESERVED: [&str; 2] = ["new", "client"];
+
+/// Generates a client struct named after the given trait, with a method for
+/// each method of the trait.
+///
+/// Each method of the trait returns the `Endpoint` it executes. When the method
+/// has a body it's used for creating the endpoint, otherwise the endpoint is
+/// created as a struct whose fields are named after the arguments. The
+/// generated methods execute the endpoint and return its parsed response,
+/// along with a blocking variant suffixed with `_block` when the `blocking`
+/// feature of `rustified` is enabled.
+pub(crate) fn expand(attr: TokenStream, item: ItemTrait) -> Result<TokenStream, Error> {
+    if !attr.is_empty() {
+        return Err(Error::new(
+            attr.span(),
+            "The api attribute does not take any parameters",
+        ));
+    }
+    if !item.generics.params.is_empty() {
+        return Err(Error::new(
+            item.generics.span(),
+            "API traits cannot be generic",
+        ));
+    }
+
+    let id = &item.ident;
+    let vis = &item.vis;
+    let docs = item.attrs.iter().filter(|a| a.path.is_ident("doc"));
+
+    let mut methods = Vec::new();
+    let mut blocking = Vec::new();
+    for trait_item in &item.items {
+        let method = match trait_item {
+            TraitItem::Method(m) => m,
+            i => {
+                return Err(Error::new(
+                    i.span(),
+                    "Only methods can be declared in an API trait",
+                ))
+            }
+        };
+        let ApiMethod {
+            name,
+            args,
+            endpoint,
+            create,
+        } = api_method(method)?;
+        let docs = method
+            .attrs
+            .iter()
+            .filter(|a| a.path.is_ident("doc"))
+            .collect::<Vec<_>>();
+        let (impl_generics, _, where_clause) = method.sig.generics.split_for_impl();
+        let result = quote! {
+            Result<
+                <#endpoint as rustified::endpoint::Endpoint>::Response,
+                rustified::errors::ClientError,
+            >
+        };
+
+        methods.push(quote! {
+            #(#docs)*
+            #vis async fn #name #impl_generics(&self, #(#args),*) -> #result #where_clause {
+                let endpoint: #endpoint = #create;
+                rustified::endpoint::Endpoint::exec(&endpoint, &self.client)
+                    .await?
+                    .parse()
+            }
+        });
+
+        let block_id = format_ident!("{}_block", name);
+        blocking.push(quote! {
+            #(#docs)*
+            #vis fn #block_id #impl_generics(&self, #(#args),*) -> #result #where_clause {
+                let endpoint: #endpoint = #create;
+                rustified::endpoint::Endpoint::exec_block(&endpoint, &self.client)?.parse()
+            }
+        });
+    }
+
+    Ok(quote! {
+        #(#docs)*
+        #[derive(Clone, Debug)]
+        #vis struct #id<C> {
+            client: C,
+        }
+
+        impl<C> #id<C> {
+            /// Returns a new API client which executes endpoints using the
+            /// given client.
+            #vis fn new(client: C) -> Self {
+                #id { client }
+            }
+
+            /// Returns the client used for executing endpoints.
+            #vis fn client(&self) -> &C {
+                &self.client
+            }
+        }
+
+        impl<C: rustified::client::Client> #id<C> {
+            #(#methods)*
+        }
+
+        rustified::__private::blocking! {
+            impl<C: rustified::blocking::client::Client> #id<C> {
+                #(#blocking)*
+            }
+        }
+    })
+}
+
+/// A method of an API trait.
+struct ApiMethod<'a> {
+    name: &'a Ident,
+    /// The arguments of the method, without its receiver.
+    args: Vec<&'a FnArg>,
+    /// The type of the endpoint executed by the method.
+    endpoint: &'a Type,
+    /// An expression which creates the endpoint.
+    create: TokenStream,
+}
+
+/// Parses a method of an API trait.
+fn api_method(method: &TraitItemMethod) -> Result<ApiMethod<'_>, Error> {
+    let sig = &method.sig;
+    let id = &sig.ident;
+    if RESERVED.contains(&id.to_string().as_str()) {
+        return Err(Error::new(
+            id.span(),
+            format!("API methods cannot be named `{}`", id).as_str(),
+        ));
+    }
+    if let Some(a) = &sig.asyncness {
+        return Err(Error::new(
+            a.span(),
+            "API methods are made async by the api attribute",
+        ));
+    }
+    let endpoint = match &sig.output {
+        ReturnType::Type(_, ty) => &**ty,
+        ReturnType::Default => {
+            return Err(Error::new(
+                id.span(),
+                "API methods must return the endpoint they execute",
+            ))
+        }
+    };
+
+    let args = sig
+        .inputs
+        .iter()
+        .filter(|a| matches!(a, FnArg::Typed(_)))
+        .collect::<Vec<_>>();
+    let create = match &method.default {
+        Some(block) => quote! { #block },
+        None => {
+            let mut names = Vec::new();
+            for arg in &args {
+                match arg {
+                    FnArg::Typed(t) => match &*t.pat {
+                        Pat::Ident(p) => names.push(&p.ident),
+                        p => {
+                            return Err(Error::new(
+                                p.span(),
+                                "Arguments must be named after the fields of the endpoint",
+                            ))
+                        }
+                    },
+                    FnArg::Receiver(_) => {}
+                }
+            }
+            let mut path =
+                match endpoint {
+                    Type::Path(p) if p.qself.is_none() => p.path.clone(),
+                    ty => return Err(Error::new(
+                        ty.span(),
+                        "Endpoints which aren't structs must be created in the body of the method",
+                    )),
+                };
+
+            // Generic arguments require a turbofish in struct expressions
+            if let Some(segment) = path.segments.last_mut() {
+                if let PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                    args.colon2_token = Some(syn::Token![::](Span::call_site()));
+                }
+            }
+            quote! { #path { #(#names),* } }
+        }
+    };
+
+    Ok(ApiMethod {
+        name: id,
+        args,
+        endpoint,
+        create,
+    })
+}
@@ -8,18 +8,24 @@
 extern crate synstructure;
 extern crate proc_macro;
 
+mod api;
 mod error;
+mod openapi;
 mod params;
 mod parse;
 
-use std::{collections::HashMap, convert::TryFrom};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+};
 
 use error::Error;
-use params::Parameters;
+use params::{
+    PageStrategy, Parameters, ParametersBuilder, RetryPolicy, StaticHeader, StatusResponse,
+};
 use proc_macro2::Span;
-use quote::quote;
-use regex::Regex;
-use syn::{self, spanned::Spanned, Field, Generics, Ident, Meta};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{self, ext::IdentExt, spanned::Spanned, Field, Generics, Ident, Meta};
 
 const MACRO_NAME: &str = "Endpoint";
 const ATTR_NAME: &str = "endpoint";
@@ -27,6 +33,10 @@ const ATTR_NAME: &str = "endpoint";
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub(crate) enum EndpointAttribute {
     Body,
+    File,
+    Header,
+    Part,
+    Path,
     Query,
     Raw,
     Skip,
@@ -38,8 +48,17 @@ impl TryFrom<&Meta> for EndpointAttribute {
 
     fn try_from(m: &Meta) -> Result<Self, Self::Error> {
         match m.path().get_ident() {
+            // Only headers can be given a value, which is their name
+            Some(i) if matches!(m, Meta::NameValue(_)) && i != "header" => Err(Error::new(
+                m.span(),
+                format!("Attribute does not take a value: {}", i).as_str(),
+            )),
             Some(i) => match i.to_string().to_lowercase().as_str() {
                 "body" => Ok(EndpointAttribute::Body),
+                "file" => Ok(EndpointAttribute::File),
+                "header" => Ok(EndpointAttribute::Header),
+                "part" => Ok(EndpointAttribute::Part),
+                "path" => Ok(EndpointAttribute::Path),
                 "query" => Ok(EndpointAttribute::Query),
                 "raw" => Ok(EndpointAttribute::Raw),
                 "skip" => Ok(EndpointAttribute::Skip),
@@ -53,87 +72,275 @@ impl TryFrom<&Meta> for EndpointAttribute {
     }
 }
 
+/// Determines how generated methods access the fields of the endpoint.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Receiver {
+    /// The fields of a struct, accessed through `self`.
+    Struct,
+    /// The fields of an enum variant, bound by reference when matching `self`.
+    Variant,
+}
+
+impl Receiver {
+    /// Returns an expression which references the field with the given name.
+    fn field(self, id: &Ident) -> proc_macro2::TokenStream {
+        match self {
+            Receiver::Struct => quote! { &self.#id },
+            Receiver::Variant => quote! { #id },
+        }
+    }
+}
+
 /// Generates the path string for the endpoint.
 ///
 /// The string supplied by the end-user supports basic interpolation using curly
 /// braces. For example,
-/// ```
+/// ```ignore
 /// endpoint(path = "user/{self.name}")
 /// ```
 /// Should produce:
+/// ```ignore
+/// format!("user/{}", rustified::http::escape_percent(&(self.name)));
 /// ```
-/// format!("user/{}", self.name);
-/// ```
-/// This is currently accomplished using a basic regular expression which
-/// matches contents in the braces, extracts them out, leaving behind the empty
-/// braces and placing the contents into the proper position in `format!`.
+/// The template is split into placeholders by [parse::path_template], leaving
+/// behind empty braces and placing the contents into the proper position in
+/// `format!`. Literal braces can be escaped by doubling them.
+///
+/// Braces containing just the name of a field, as in `{name}`, are replaced
+/// with the value of the field percent-encoded as a single path segment, or as
+/// several segments when the field is tagged with `#[endpoint(path(slashes))]`.
+/// Any other contents are parsed as an expression and every `self.field` it
+/// accesses must be one of the given field names. Errors point at the
+/// placeholder inside the path literal, or at fields tagged with
+/// [EndpointAttribute::Path] which the template doesn't use.
+///
+/// Returns the names of all fields used in the path along with the path.
 ///
 /// If no interpolation is needed the user provided string is fed into
 /// `String::from` without modification.
-fn gen_path(path: &syn::LitStr) -> Result<proc_macro2::TokenStream, Error> {
-    let re = Regex::new(r"\{(.*?)\}").unwrap();
-    let mut fmt_args: Vec<syn::Expr> = Vec::new();
-    for cap in re.captures_iter(path.value().as_str()) {
-        let expr = syn::parse_str(&cap[1]);
+fn gen_path(
+    path: &syn::LitStr,
+    fields: &HashMap<EndpointAttribute, Vec<Field>>,
+    names: &HashSet<String>,
+    receiver: Receiver,
+) -> Result<(proc_macro2::TokenStream, HashSet<String>), Error> {
+    let mut slashes = HashMap::new();
+    for field in fields.get(&EndpointAttribute::Path).into_iter().flatten() {
+        let id = field.ident.clone().unwrap();
+        slashes.insert(id.unraw().to_string(), parse::path_slashes(field)?);
+    }
+
+    let (template, placeholders) = parse::path_template(path)?;
+    let mut used = HashSet::new();
+    let mut fmt_args: Vec<proc_macro2::TokenStream> = Vec::new();
+    for placeholder in placeholders {
+        let content = placeholder.content.trim();
+
+        // A single field name is a percent-encoded path parameter
+        if let Ok(id) = syn::parse_str::<Ident>(content) {
+            if !names.contains(&id.unraw().to_string()) {
+                return Err(Error::new(
+                    placeholder.span,
+                    format!("Unknown field in path: {}", content).as_str(),
+                ));
+            }
+            let id = Ident::new_raw(&id.unraw().to_string(), placeholder.span);
+            let encode = match slashes.get(&id.unraw().to_string()) {
+                Some(true) => quote_spanned! { placeholder.span => rustified::http::encode_path },
+                _ => quote_spanned! { placeholder.span => rustified::http::encode_segment },
+            };
+            let field = receiver.field(&id);
+            used.insert(id.unraw().to_string());
+            fmt_args.push(quote_spanned! { placeholder.span => #encode(#field) });
+            continue;
+        }
+
+        let expr = content
+            .parse::<proc_macro2::TokenStream>()
+            .ok()
+            .and_then(|t| syn::parse2::<syn::Expr>(parse::respan(t, placeholder.span)).ok());
         match expr {
-            Ok(ex) => fmt_args.push(ex),
-            Err(_) => {
+            Some(mut ex) => {
+                parse::path_fields(&mut ex, names, &mut used, receiver)?;
+                fmt_args.push(quote! { rustified::http::escape_percent(&(#ex)) });
+            }
+            None => {
                 return Err(Error::new(
-                    path.span(),
-                    format!("Failed parsing format argument as expression: {}", &cap[1]).as_str(),
+                    placeholder.span,
+                    format!("Failed parsing format argument as expression: {}", content).as_str(),
                 ));
             }
         }
     }
-    let path = syn::LitStr::new(
-        re.replace_all(path.value().as_str(), "{}")
-            .to_string()
-            .as_str(),
-        Span::call_site(),
-    );
+
+    for field in fields.get(&EndpointAttribute::Path).into_iter().flatten() {
+        let id = field.ident.as_ref().unwrap();
+        if !used.contains(&id.unraw().to_string()) {
+            return Err(Error::new(
+                id.span(),
+                format!("Path field is not used in the path: {}", id.unraw()).as_str(),
+            ));
+        }
+    }
 
     if !fmt_args.is_empty() {
-        Ok(quote! {
-            format!(#path, #(#fmt_args),*)
-        })
+        let template = syn::LitStr::new(template.as_str(), Span::call_site());
+        Ok((
+            quote! {
+                format!(#template, #(#fmt_args),*)
+            },
+            used,
+        ))
     } else {
-        Ok(quote! {
-            String::from(#path)
-        })
+        let path = syn::LitStr::new(
+            template.replace("{{", "{").replace("}}", "}").as_str(),
+            Span::call_site(),
+        );
+        Ok((
+            quote! {
+                String::from(#path)
+            },
+            used,
+        ))
     }
 }
 
-/// Generates the query method for generating query parameters.
+/// Generates the body of the query method for generating query parameters.
 ///
 /// If any fields are found with the [EndpointAttribute::Query] attribute they
-/// are combined into a new struct and then serialized into a query string. If
-/// the attribute is not found on any of the fields the query method is not
+/// are combined into a new struct and then serialized into a query string
+/// using the given style, or the default style when none is given. Fields with
+/// a style of their own, as in `#[endpoint(query(style = comma))]`, are
+/// serialized using the matching function from `rustified::query`. If the
+/// attribute is not found on any of the fields the query method is not
 /// generated.
 fn gen_query(
     fields: &HashMap<EndpointAttribute, Vec<Field>>,
+    style: Option<&Ident>,
     serde_attrs: &[Meta],
-) -> proc_macro2::TokenStream {
-    let query_fields = fields.get(&EndpointAttribute::Query);
-    if let Some(v) = query_fields {
-        // Construct query function
-        let temp = parse::fields_to_struct(v, serde_attrs);
-        quote! {
-            fn query(&self) -> Result<Option<String>, ClientError> {
-                #temp
+    receiver: Receiver,
+) -> Result<Option<proc_macro2::TokenStream>, Error> {
+    let query_fields = match fields.get(&EndpointAttribute::Query) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    let mut styled = Vec::new();
+    for field in query_fields {
+        let mut field = field.clone();
+        if let Some(s) = parse::field_query_style(&field)? {
+            let func = syn::LitStr::new(
+                &format!("rustified::query::{}", query_style_fn(&s)),
+                s.span(),
+            );
+            field
+                .attrs
+                .push(syn::parse_quote!(#[serde(serialize_with = #func)]));
+        }
+        styled.push(field);
+    }
 
-                Ok(Some(build_query(&__temp)?))
+    // Construct query function
+    let temp = parse::fields_to_struct(&styled, serde_attrs, receiver);
+    let query = match style {
+        Some(s) => quote! {
+            rustified::query::to_string(&__temp, rustified::query::QueryStyle::#s)?
+        },
+        None => quote! { build_query(&__temp)? },
+    };
+    Ok(Some(quote! {
+        #temp
+
+        Ok(Some(#query))
+    }))
+}
+
+/// Returns the name of the function in `rustified::query` which serializes a
+/// field with the given variant of `QueryStyle`.
+fn query_style_fn(style: &Ident) -> &'static str {
+    match style.to_string().as_str() {
+        "Comma" => "comma",
+        "Brackets" => "brackets",
+        "DeepObject" => "deep_object",
+        _ => "repeat",
+    }
+}
+
+/// Generates the body of the headers method for generating request headers.
+///
+/// The fixed headers given by the `headers` parameter are added first. Each
+/// field with the [EndpointAttribute::Header] attribute is converted using
+/// `ToString` and added as a header. The header is named by the value of the
+/// attribute or, if it has none, after the field with underscores replaced by
+/// dashes. [Option] fields are only added when they contain a value. If there
+/// are no fixed headers and the attribute is not found on any of the fields
+/// the headers method is not generated.
+fn gen_headers(
+    fields: &HashMap<EndpointAttribute, Vec<Field>>,
+    fixed: &[StaticHeader],
+    receiver: Receiver,
+) -> Result<Option<proc_macro2::TokenStream>, Error> {
+    let header_fields = fields
+        .get(&EndpointAttribute::Header)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    if fixed.is_empty() && header_fields.is_empty() {
+        return Ok(None);
+    }
+
+    let mut headers = fixed
+        .iter()
+        .map(|h| {
+            let (name, value) = (&h.name, &h.value);
+            quote! {
+                __headers.append(
+                    rustified::__private::http::header::HeaderName::from_static(#name),
+                    rustified::__private::http::HeaderValue::from_static(#value),
+                );
             }
+        })
+        .collect::<Vec<_>>();
+    for field in header_fields {
+        let field_ref = receiver.field(field.ident.as_ref().unwrap());
+        let name = parse::header_name(field)?;
+        let value = quote! {
+            rustified::http::build_header(#name, __value)?
+        };
+        let header = quote! {
+            rustified::__private::http::header::HeaderName::from_static(#name)
+        };
+        if parse::is_std_option(&field.ty) {
+            headers.push(quote! {
+                if let Some(__value) = #field_ref {
+                    __headers.append(#header, #value);
+                }
+            });
+        } else {
+            headers.push(quote! {
+                let __value = #field_ref;
+                __headers.append(#header, #value);
+            });
         }
-    } else {
-        quote! {}
     }
+
+    Ok(Some(quote! {
+        let mut __headers = rustified::__private::http::HeaderMap::new();
+        #(#headers)*
+
+        Ok(Some(__headers))
+    }))
 }
 
-/// Generates the body method for generating the request body.
+/// Generates the body of the body method for generating the request body.
 ///
 /// The final result is determined by which attributes are present and/or
 /// missing on the struct fields. The following order is respected:
 ///
+/// * If any fields are found with the [EndpointAttribute::Part] or
+///   [EndpointAttribute::File] attributes they are encoded as the parts of a
+///   `multipart/form-data` body, text parts first. Part fields are converted
+///   using `ToString` and file fields are expected to be a `multipart::File`.
+///   [Option] fields are left out of the body when they are [Option::None].
 /// * If a field is found with the [EndpointAttribute::Raw] attribute that field
 ///   is returned directly as the request body. The assumption is this field
 ///   will always be a [Vec<u8>].
@@ -148,45 +355,349 @@ fn gen_query(
 fn gen_body(
     fields: &HashMap<EndpointAttribute, Vec<Field>>,
     serde_attrs: &[Meta],
-) -> Result<proc_macro2::TokenStream, Error> {
-    // Check for a raw field first
-    if let Some(v) = fields.get(&EndpointAttribute::Raw) {
+    receiver: Receiver,
+) -> Result<Option<proc_macro2::TokenStream>, Error> {
+    // Check for multipart fields first
+    if is_multipart(fields) {
+        for attr in [EndpointAttribute::Raw, EndpointAttribute::Body].iter() {
+            if let Some(v) = fields.get(attr) {
+                return Err(Error::new(
+                    v[0].span(),
+                    "Cannot mix body or raw fields with file or part fields",
+                ));
+            }
+        }
+
+        let parts = fields
+            .get(&EndpointAttribute::Part)
+            .into_iter()
+            .flatten()
+            .map(|f| gen_part(f, quote! { text }, quote! { .to_string() }, receiver));
+        let files = fields
+            .get(&EndpointAttribute::File)
+            .into_iter()
+            .flatten()
+            .map(|f| gen_part(f, quote! { file }, quote! {}, receiver));
+        Ok(Some(quote! {
+            let mut __form = rustified::multipart::Form::new();
+            #(#parts)*
+            #(#files)*
+
+            Ok(Some(__form.finish()))
+        }))
+    // Then for a raw field
+    } else if let Some(v) = fields.get(&EndpointAttribute::Raw) {
         if v.len() > 1 {
             return Err(Error::new(v[1].span(), "May only mark one field as raw"));
         }
 
-        let id = v[0].ident.clone().unwrap();
-        Ok(quote! {
-            fn body(&self) -> Result<Option<Vec<u8>>, ClientError>{
-                Ok(Some(self.#id.clone()))
-            }
-        })
+        let field = receiver.field(v[0].ident.as_ref().unwrap());
+        Ok(Some(quote! {
+            Ok(Some((#field).clone()))
+        }))
     // Then for any body fields
     } else if let Some(v) = fields.get(&EndpointAttribute::Body) {
-        let temp = parse::fields_to_struct(v, serde_attrs);
-        Ok(quote! {
-            fn body(&self) -> Result<Option<Vec<u8>>, ClientError> {
-                #temp
+        let temp = parse::fields_to_struct(v, serde_attrs, receiver);
+        Ok(Some(quote! {
+            #temp
 
-                Ok(Some(build_body(&__temp, Self::REQUEST_BODY_TYPE)?))
-            }
-        })
+            Ok(Some(build_body::<Self::RequestCodec>(&__temp)?))
+        }))
     // Then for any untagged fields
     } else if let Some(v) = fields.get(&EndpointAttribute::Untagged) {
-        let temp = parse::fields_to_struct(v, serde_attrs);
-        Ok(quote! {
-            fn body(&self) -> Result<Option<Vec<u8>>, ClientError> {
-                #temp
+        let temp = parse::fields_to_struct(v, serde_attrs, receiver);
+        Ok(Some(quote! {
+            #temp
 
-                Ok(Some(build_body(&__temp, Self::REQUEST_BODY_TYPE)?))
-            }
-        })
+            Ok(Some(build_body::<Self::RequestCodec>(&__temp)?))
+        }))
     // Leave it undefined if no body fields found
     } else {
-        Ok(quote! {})
+        Ok(None)
+    }
+}
+
+/// Returns `true` if any fields are tagged as parts of a multipart body.
+fn is_multipart(fields: &HashMap<EndpointAttribute, Vec<Field>>) -> bool {
+    fields.contains_key(&EndpointAttribute::File) || fields.contains_key(&EndpointAttribute::Part)
+}
+
+/// Generates the statement adding a single field to a multipart form.
+///
+/// The `method` is the `Form` method used to add the part and `convert` is
+/// appended to the field value before it's passed on. Parts are named after
+/// the field unless it's renamed with `#[serde(rename = "...")]`. Fields which
+/// are an [Option] are only added when they contain a value.
+fn gen_part(
+    field: &Field,
+    method: proc_macro2::TokenStream,
+    convert: proc_macro2::TokenStream,
+    receiver: Receiver,
+) -> proc_macro2::TokenStream {
+    let id = field.ident.clone().unwrap();
+    let name = parse::serde_name(field);
+    let value = receiver.field(&id);
+    if parse::is_std_option(&field.ty) {
+        quote! {
+            if let Some(__value) = #value {
+                __form.#method(#name, __value #convert);
+            }
+        }
+    } else {
+        quote! {
+            __form.#method(#name, (#value) #convert);
+        }
+    }
+}
+
+/// Returns the codec type for a request or response body.
+///
+/// Codecs are either given by path using the `*_codec` parameters or chosen
+/// from the built-in codecs by name using the `*_type` parameters. Several
+/// names can be separated with `|`, in which case the codecs are negotiated
+/// using `Negotiate`. When neither parameter is present the codec named by
+/// `default` is used.
+fn gen_codec(
+    name: Option<syn::Expr>,
+    codec: Option<syn::Type>,
+    default: &str,
+) -> Result<syn::Type, Error> {
+    let name = match (name, codec) {
+        (Some(n), Some(_)) => {
+            return Err(Error::new(
+                n.span(),
+                "Cannot specify both a type and a codec for the same body",
+            ))
+        }
+        (None, Some(c)) => return Ok(c),
+        (Some(n), None) => n,
+        (None, None) => syn::parse_str(default).unwrap(),
+    };
+
+    let mut codecs = Vec::<syn::Type>::new();
+    gen_codec_names(&name, &mut codecs)?;
+    if codecs.len() == 1 {
+        Ok(codecs.remove(0))
+    } else {
+        Ok(syn::parse_quote! { rustified::codec::Negotiate<(#(#codecs),*)> })
     }
 }
 
+/// Resolves the built-in codec names found in a `|` separated expression.
+fn gen_codec_names(name: &syn::Expr, codecs: &mut Vec<syn::Type>) -> Result<(), Error> {
+    if let syn::Expr::Binary(b) = name {
+        if let syn::BinOp::BitOr(_) = b.op {
+            gen_codec_names(&b.left, codecs)?;
+            return gen_codec_names(&b.right, codecs);
+        }
+    }
+
+    let codec = match name {
+        syn::Expr::Path(p) => p
+            .path
+            .get_ident()
+            .and_then(|i| match i.to_string().as_str() {
+                "BYTES" => Some("Bytes"),
+                "CBOR" => Some("Cbor"),
+                "FORM" => Some("Form"),
+                "JSON" => Some("Json"),
+                "MSGPACK" => Some("MessagePack"),
+                "MULTIPART" => Some("Multipart"),
+                "NDJSON" => Some("Ndjson"),
+                "TEXT" => Some("Text"),
+                "XML" => Some("Xml"),
+                _ => None,
+            }),
+        _ => None,
+    };
+    match codec {
+        Some(c) => {
+            codecs.push(syn::parse_str(format!("rustified::codec::{}", c).as_str()).unwrap());
+            Ok(())
+        }
+        None => Err(Error::new(name.span(), "Unknown body type")),
+    }
+}
+
+/// Generates the error method for endpoints with an `error` type.
+///
+/// The body of responses whose status code isn't accepted is decoded into the
+/// error type and returned in a `ClientError::Api`. If decoding fails a
+/// `ClientError::ServerResponseError` is returned instead, as if there was no
+/// error type.
+fn gen_error(error: Option<syn::Type>) -> proc_macro2::TokenStream {
+    let error = match error {
+        Some(e) => e,
+        None => return quote! {},
+    };
+
+    quote! {
+        fn error(
+            &self,
+            response: &rustified::__private::http::Response<Vec<u8>>,
+        ) -> ClientError {
+            let status = response.status().as_u16();
+            match <Self::ResponseCodec as rustified::codec::Codec>::decode_response::<#error>(
+                response,
+            ) {
+                Ok(error) => ClientError::Api {
+                    status,
+                    headers: response.headers().clone(),
+                    error: rustified::errors::ApiError::new(error),
+                },
+                Err(_) => ClientError::ServerResponseError {
+                    code: status,
+                    content: String::from_utf8(response.body().to_vec()).ok(),
+                },
+            }
+        }
+    }
+}
+
+/// Generates the retry method for endpoints with a `retry` policy.
+fn gen_retry(retry: Option<RetryPolicy>) -> proc_macro2::TokenStream {
+    let retry = match retry {
+        Some(r) => r,
+        None => return quote! {},
+    };
+
+    let mut policy = match retry.max {
+        Some(max) => quote! { rustified::retry::Retry::new(#max) },
+        None => quote! { rustified::retry::Retry::default() },
+    };
+    if let Some(backoff) = retry.backoff {
+        policy = quote! { #policy.backoff(rustified::retry::Backoff::#backoff) };
+    }
+    if let Some(delay) = retry.delay {
+        policy = quote! { #policy.delay(std::time::Duration::from_millis(#delay)) };
+    }
+    if let Some(max_delay) = retry.max_delay {
+        policy = quote! { #policy.max_delay(std::time::Duration::from_millis(#max_delay)) };
+    }
+    if let Some(on) = retry.on {
+        policy = quote! { #policy.on(&[#(#on),*]) };
+    }
+    if let Some(non_idempotent) = retry.non_idempotent {
+        policy = quote! { #policy.non_idempotent(#non_idempotent) };
+    }
+
+    quote! {
+        fn retry(&self) -> Option<rustified::retry::Retry> {
+            Some(#policy)
+        }
+    }
+}
+
+/// Generates the enum holding the response of an endpoint which maps its
+/// responses to status codes using the `responses` parameter.
+///
+/// The enum is named after the endpoint with a `Response` suffix and has a
+/// variant for each status code, named after its reason phrase, as in
+/// `NotFound(T)`. The `default` response is held by a `Default(u16, T)`
+/// variant along with the status code. The enum implements `FromResponse` by
+/// decoding the body into the variant matching the status code of the
+/// response. Responses with unmapped status codes are errors unless there's a
+/// `default` response.
+///
+/// Returns the type of the enum along with its definition.
+fn gen_responses(
+    id: &Ident,
+    vis: &syn::Visibility,
+    responses: &[StatusResponse],
+) -> (syn::Type, proc_macro2::TokenStream) {
+    let response_id = format_ident!("{}Response", id);
+    let mut variants = Vec::new();
+    let mut arms = Vec::new();
+    let mut statuses = Vec::new();
+    let mut default = None;
+    for response in responses {
+        let ty = &response.ty;
+        match response.status {
+            Some(status) => {
+                let var = status_variant(status, response.span);
+                variants.push(quote! { #var(#ty), });
+                arms.push(quote! { #status => C::decode_response(response).map(Self::#var), });
+                statuses.push(status);
+            }
+            None => {
+                let var = Ident::new("Default", response.span);
+                variants.push(quote! { #var(u16, #ty), });
+                default = Some(quote! {
+                    status => C::decode_response(response).map(|r| Self::#var(status, r)),
+                });
+            }
+        }
+    }
+
+    let (accepts, fallback) = match default {
+        Some(d) => (
+            quote! {
+                fn accepts(_status: u16) -> bool {
+                    true
+                }
+            },
+            d,
+        ),
+        None => (
+            quote! {
+                fn accepts(status: u16) -> bool {
+                    matches!(status, #(#statuses)|*)
+                }
+            },
+            quote! {
+                status => Err(rustified::errors::ClientError::ServerResponseError {
+                    code: status,
+                    content: String::from_utf8(response.body().to_vec()).ok(),
+                }),
+            },
+        ),
+    };
+    let doc = format!(
+        "The response of a [`{}`] endpoint, chosen by the status code of the response.",
+        id
+    );
+
+    (
+        syn::parse_quote! { #response_id },
+        quote! {
+            #[doc = #doc]
+            #vis enum #response_id {
+                #(#variants)*
+            }
+
+            impl<C: rustified::codec::Codec> rustified::endpoint::FromResponse<C> for #response_id {
+                #accepts
+
+                fn from_response(
+                    response: &rustified::__private::http::Response<Vec<u8>>,
+                ) -> Result<Self, rustified::errors::ClientError> {
+                    match response.status().as_u16() {
+                        #(#arms)*
+                        #fallback
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// Returns the name of the variant holding the response for the given status
+/// code, which is its canonical reason phrase in camel case.
+fn status_variant(status: u16, span: Span) -> Ident {
+    let reason = http::StatusCode::from_u16(status)
+        .ok()
+        .and_then(|s| s.canonical_reason());
+    let name = match reason {
+        Some(r) => r
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(|w| w[..1].to_ascii_uppercase() + &w[1..].to_ascii_lowercase())
+            .collect(),
+        None => format!("Status{}", status),
+    };
+    Ident::new(&name, span)
+}
+
 /// Generates `builder()` and `exec_*` helper methods for use with
 /// `derive_builder`.
 ///
@@ -210,114 +721,306 @@ fn gen_builder(id: &Ident, generics: &Generics) -> proc_macro2::TokenStream {
 
 /// Parses parameters passed into the `endpoint` attribute attached to the
 /// struct.
-fn parse_params(attr: &Meta) -> Result<Parameters, Error> {
-    // Parse the attribute as a key/value pair list
-    let kv = parse::attr_kv(attr)?;
+fn parse_params(attr: &syn::Attribute) -> Result<Parameters, Error> {
+    // Convert map to Parameters
+    params::Parameters::new(parse::params(attr)?)
+}
 
-    // Create map from key/value pair list
-    let map = parse::to_map(&kv)?;
+/// Returns the `endpoint` attribute in the given list of attributes, if any.
+///
+/// Fails at the given [Span] when the attribute is present more than once.
+fn endpoint_attr(attrs: &[syn::Attribute], span: Span) -> Result<Option<syn::Attribute>, Error> {
+    let mut attrs = attrs
+        .iter()
+        .filter(|a| a.path.is_ident(ATTR_NAME))
+        .cloned()
+        .collect::<Vec<_>>();
+    if attrs.len() > 1 {
+        return Err(Error::new(
+            span,
+            format!("Cannot define the {} attribute more than once", ATTR_NAME).as_str(),
+        ));
+    }
 
-    // Convert map to Parameters
-    params::Parameters::new(map)
+    Ok(attrs.pop())
 }
 
-/// Implements `Endpoint` on the provided struct.
-fn endpoint_derive(s: synstructure::Structure) -> proc_macro2::TokenStream {
-    // Parse `endpoint` attributes attached to input struct
-    let attrs = match parse::attributes(&s.ast().attrs, ATTR_NAME) {
-        Ok(v) => v,
-        Err(e) => return e.into_tokens(),
-    };
+/// Returns the `serde` attributes attached to the container, which are passed
+/// on to the structs generated for serialization.
+///
+/// The serialized container is named after `name` unless it's been renamed,
+/// formats like XML use it as the root element.
+fn gen_serde_attrs(attrs: &[syn::Attribute], name: &Ident) -> Vec<Meta> {
+    let mut serde_attrs = parse::attributes(attrs, "serde").unwrap_or_default();
+    if !parse::has_serde_param(&serde_attrs, "rename") {
+        let name = syn::LitStr::new(name.unraw().to_string().as_str(), name.span());
+        serde_attrs.push(syn::parse_quote!(serde(rename = #name)));
+    }
+    serde_attrs
+}
 
-    // Parse `endpoint` attributes attached to input struct fields
-    let field_attrs = match parse::field_attributes(&s.ast().data) {
-        Ok(v) => v,
-        Err(e) => return e.into_tokens(),
+/// Returns the request and response codec types of an endpoint.
+///
+/// Endpoints with multipart fields must use the multipart codec for their
+/// requests, which is also the default for them.
+fn gen_codecs(
+    request_type: Option<syn::Expr>,
+    request_codec: Option<syn::Type>,
+    response_type: Option<syn::Expr>,
+    response_codec: Option<syn::Type>,
+    multipart: bool,
+) -> Result<(syn::Type, syn::Type), Error> {
+    // Multipart fields always use the multipart codec
+    if multipart {
+        let is_multipart = request_type
+            .as_ref()
+            .is_none_or(|ty| matches!(ty, syn::Expr::Path(p) if p.path.is_ident("MULTIPART")));
+        if !is_multipart || request_codec.is_some() {
+            return Err(Error::new(
+                Span::call_site(),
+                "File and part fields require the MULTIPART request type",
+            ));
+        }
+    }
+
+    let request_default = match multipart {
+        true => "MULTIPART",
+        false => "JSON",
     };
+    Ok((
+        gen_codec(request_type, request_codec, request_default)?,
+        gen_codec(response_type, response_codec, "JSON")?,
+    ))
+}
 
-    // Verify attribute is present
-    if attrs.is_empty() {
-        return Error::new(
-            Span::call_site(),
-            format!(
-                "Deriving `{}` requires attaching an `{}` attribute",
-                MACRO_NAME, ATTR_NAME
-            )
-            .as_str(),
-        )
-        .into_tokens();
+/// The generated bodies of the `Endpoint` methods for a single operation,
+/// which is either a struct or a variant of an enum.
+struct Operation {
+    path: proc_macro2::TokenStream,
+    query: Option<proc_macro2::TokenStream>,
+    headers: Option<proc_macro2::TokenStream>,
+    body: Option<proc_macro2::TokenStream>,
+    multipart: bool,
+    spec: openapi::Spec,
+}
+
+/// Generates the [Operation] for the given path template and fields.
+fn gen_operation(
+    path: &syn::LitStr,
+    fields: &syn::Fields,
+    headers: &[StaticHeader],
+    query_style: Option<&Ident>,
+    serde_attrs: &[Meta],
+    receiver: Receiver,
+) -> Result<Operation, Error> {
+    // Parse `endpoint` attributes attached to the fields
+    let mut field_attrs = parse::field_attributes(fields)?;
+    let names = fields
+        .iter()
+        .filter_map(|f| f.ident.as_ref().map(|i| i.unraw().to_string()))
+        .collect::<HashSet<String>>();
+
+    // Generate path string
+    let (path_fmt, path_fields) = gen_path(path, &field_attrs, &names, receiver)?;
+
+    // Fields used in the path are left out of the untagged body
+    if let Some(v) = field_attrs.get_mut(&EndpointAttribute::Untagged) {
+        v.retain(|f| !path_fields.contains(&f.ident.as_ref().unwrap().unraw().to_string()));
+        if v.is_empty() {
+            field_attrs.remove(&EndpointAttribute::Untagged);
+        }
     }
 
-    // Verify there's only one instance of the attribute present
-    if attrs.len() > 1 {
-        return Error::new(
-            Span::call_site(),
-            format!("Cannot define the {} attribute more than once", ATTR_NAME).as_str(),
-        )
-        .into_tokens();
+    Ok(Operation {
+        path: path_fmt,
+        spec: openapi::Spec::new(path, &field_attrs, fields, query_style)?,
+        query: gen_query(&field_attrs, query_style, serde_attrs, receiver)?,
+        headers: gen_headers(&field_attrs, headers, receiver)?,
+        body: gen_body(&field_attrs, serde_attrs, receiver)?,
+        multipart: is_multipart(&field_attrs),
+    })
+}
+
+/// Generates the optional `Endpoint` methods from their bodies, leaving out
+/// the methods without one.
+fn gen_methods(
+    query: Option<proc_macro2::TokenStream>,
+    headers: Option<proc_macro2::TokenStream>,
+    body: Option<proc_macro2::TokenStream>,
+) -> proc_macro2::TokenStream {
+    let query = query.map(|q| {
+        quote! {
+            fn query(&self) -> Result<Option<String>, ClientError> {
+                #q
+            }
+        }
+    });
+    let headers = headers.map(|h| {
+        quote! {
+            fn headers(
+                &self,
+            ) -> Result<Option<rustified::__private::http::HeaderMap>, ClientError> {
+                #h
+            }
+        }
+    });
+    let body = body.map(|b| {
+        quote! {
+            fn body(&self) -> Result<Option<Vec<u8>>, ClientError> {
+                #b
+            }
+        }
+    });
+
+    quote! {
+        #query
+
+        #headers
+
+        #body
     }
+}
 
-    // Parse endpoint attribute parameters
-    let params = match parse_params(&attrs[0]) {
-        Ok(v) => v,
-        Err(e) => return e.into_tokens(),
+/// Implements `Paginated` on the endpoint using the given pagination.
+///
+/// The type of the items defaults to `T` when the response is a `Vec<T>`.
+fn gen_paginate(
+    id: &Ident,
+    generics: &Generics,
+    response: &syn::Type,
+    paginate: params::Paginate,
+) -> Result<proc_macro2::TokenStream, Error> {
+    let item = match paginate.item {
+        Some(item) => item,
+        None => parse::vec_item(response).cloned().ok_or_else(|| {
+            Error::new(
+                paginate.span,
+                "Paginated endpoints whose response isn't a `Vec` must declare the type of \
+                 their items, as in `item = User`",
+            )
+        })?,
     };
-
-    let path = params.path;
-    let method = params.method;
-    let response = params.response;
-    let request_type = params.request_type;
-    let response_type = params.response_type;
-    let id = &s.ast().ident;
-
-    // Find serde attributes
-    let serde_attrs = parse::attributes(&s.ast().attrs, "serde");
-    let serde_attrs = if let Ok(v) = serde_attrs {
-        v
-    } else {
-        Vec::<Meta>::new()
+    let mut pagination = match paginate.strategy {
+        PageStrategy::Cursor(field, param) => {
+            quote! { rustified::paginate::Pagination::cursor(#field, #param) }
+        }
+        PageStrategy::Offset(param) => quote! { rustified::paginate::Pagination::offset(#param) },
+        PageStrategy::Page(param) => quote! { rustified::paginate::Pagination::page(#param) },
+        PageStrategy::Link => quote! { rustified::paginate::Pagination::link() },
     };
+    if let Some(limit) = paginate.limit {
+        pagination = quote! { #pagination.limit(#limit) };
+    }
+    if let Some(items) = paginate.items {
+        pagination = quote! { #pagination.items(#items) };
+    }
 
-    // Generate path string
-    let path = match gen_path(&path) {
-        Ok(a) => a,
-        Err(e) => return e.into_tokens(),
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics rustified::paginate::Paginated for #id #ty_generics #where_clause {
+            type Item = #item;
+
+            fn pagination(&self) -> rustified::paginate::Pagination {
+                #pagination
+            }
+        }
+    })
+}
+
+/// Implements `Endpoint` on the provided struct.
+fn struct_derive(
+    ast: &syn::DeriveInput,
+    fields: &syn::Fields,
+) -> Result<proc_macro2::TokenStream, Error> {
+    // Verify attribute is present
+    let attr = match endpoint_attr(&ast.attrs, Span::call_site())? {
+        Some(a) => a,
+        None => {
+            return Err(Error::new(
+                Span::call_site(),
+                format!(
+                    "Deriving `{}` requires attaching an `{}` attribute",
+                    MACRO_NAME, ATTR_NAME
+                )
+                .as_str(),
+            ))
+        }
     };
 
-    // Generate query function
-    let query = gen_query(&field_attrs, &serde_attrs);
+    // Parse endpoint attribute parameters
+    let params = parse_params(&attr)?;
+    let id = &ast.ident;
+
+    // Generate the methods from the fields
+    let serde_attrs = gen_serde_attrs(&ast.attrs, id);
+    let op = gen_operation(
+        &params.path,
+        fields,
+        &params.headers,
+        params.query_style.as_ref(),
+        &serde_attrs,
+        Receiver::Struct,
+    )?;
+
+    // Resolve codecs
+    let (request_codec, response_codec) = gen_codecs(
+        params.request_type,
+        params.request_codec,
+        params.response_type,
+        params.response_codec,
+        op.multipart,
+    )?;
 
-    // Generate body function
-    let body = match gen_body(&field_attrs, &serde_attrs) {
-        Ok(d) => d,
-        Err(e) => return e.into_tokens(),
+    let path = op.path;
+    let method = params.method;
+    let (response, responses) = match &params.responses {
+        Some(r) => gen_responses(id, &ast.vis, r),
+        None => (params.response, quote! {}),
+    };
+    let spec = op.spec.into_operation(
+        &id.unraw().to_string(),
+        &ast.attrs,
+        &method,
+        openapi::gen_responses(
+            &response,
+            params.responses.as_deref(),
+            params.error.as_ref(),
+        ),
+    );
+    let describe = openapi::gen_describe(id, &ast.generics, vec![spec]);
+    let paginate = match params.paginate {
+        Some(p) => gen_paginate(id, &ast.generics, &response, p)?,
+        None => quote! {},
     };
+    let methods = gen_methods(op.query, op.headers, op.body);
+    let error = gen_error(params.error);
+    let retry = gen_retry(params.retry);
 
     // Generate helper functions when deriving Builder
     let builder = match params.builder {
-        true => gen_builder(&s.ast().ident, &s.ast().generics),
+        true => gen_builder(id, &ast.generics),
         false => quote! {},
     };
 
     // Capture generic information
-    let (impl_generics, ty_generics, where_clause) = s.ast().generics.split_for_impl();
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
     // Generate Endpoint implementation
-    let const_name = format!("_DERIVE_Endpoint_FOR_{}", id);
-    let const_ident = Ident::new(const_name.as_str(), Span::call_site());
-    quote! {
-        const #const_ident: () = {
+    Ok(quote! {
+        #responses
+
+        const _: () = {
             use rustified::__private::serde::Serialize;
             use rustified::http::{build_body, build_query};
             use rustified::client::Client;
             use rustified::endpoint::Endpoint;
-            use rustified::enums::{RequestMethod, RequestType, ResponseType};
+            use rustified::enums::RequestMethod;
             use rustified::errors::ClientError;
 
             impl #impl_generics Endpoint for #id #ty_generics #where_clause {
                 type Response = #response;
-                const REQUEST_BODY_TYPE: RequestType = RequestType::#request_type;
-                const RESPONSE_BODY_TYPE: ResponseType = ResponseType::#response_type;
+                type RequestCodec = #request_codec;
+                type ResponseCodec = #response_codec;
 
                 fn path(&self) -> String {
                     #path
@@ -327,15 +1030,395 @@ fn endpoint_derive(s: synstructure::Structure) -> proc_macro2::TokenStream {
                     RequestMethod::#method
                 }
 
-                #query
+                #methods
 
+                #error
 
-                #body
+                #retry
             }
 
+            #describe
+
+            #paginate
+
             #builder
         };
+    })
+}
+
+/// Implements `Endpoint` on the provided enum, with each variant being a
+/// separate operation.
+///
+/// The codecs, and optionally the method and response, are declared in an
+/// `endpoint` attribute on the enum while each variant declares its own path
+/// and optionally its method and response. When the variants declare their
+/// responses an untagged enum named after the endpoint with a `Response`
+/// suffix is generated, holding the response of each variant.
+fn enum_derive(
+    ast: &syn::DeriveInput,
+    data: &syn::DataEnum,
+) -> Result<proc_macro2::TokenStream, Error> {
+    let id = &ast.ident;
+
+    // Parse the parameters shared by all variants
+    let shared = match endpoint_attr(&ast.attrs, Span::call_site())? {
+        Some(attr) => {
+            let map = parse::params(&attr)?;
+            for key in map.keys() {
+                if key == "paginate" {
+                    return Err(Error::new(
+                        key.span(),
+                        "Pagination is only supported on endpoints which are structs",
+                    ));
+                }
+                if key == "path" || key == "builder" {
+                    return Err(Error::new(
+                        key.span(),
+                        format!("Parameter must be declared on each variant: {}", key).as_str(),
+                    ));
+                }
+            }
+            ParametersBuilder::new(&map)?
+        }
+        None => ParametersBuilder::default(),
+    };
+
+    if data.variants.is_empty() {
+        return Err(Error::new(
+            Span::call_site(),
+            format!("Deriving `{}` requires at least one variant", MACRO_NAME).as_str(),
+        ));
     }
+
+    let mut paths = Vec::new();
+    let mut request_methods = Vec::new();
+    let mut queries = Vec::new();
+    let mut headers = Vec::new();
+    let mut bodies = Vec::new();
+    let mut defined = [false; 3];
+    let mut responses = Vec::new();
+    let mut variants = Vec::new();
+    let mut operations = Vec::new();
+    let mut multipart = false;
+    for variant in data.variants.iter() {
+        let var = &variant.ident;
+        if let syn::Fields::Unnamed(f) = &variant.fields {
+            return Err(Error::new(
+                f.span(),
+                "Variants must have named fields or no fields",
+            ));
+        }
+
+        // Parse the parameters of the variant
+        let attr = endpoint_attr(&variant.attrs, var.span())?.ok_or_else(|| {
+            Error::new(
+                var.span(),
+                format!("Each variant requires an `{}` attribute", ATTR_NAME).as_str(),
+            )
+        })?;
+        let map = parse::params(&attr)?;
+        for key in map.keys() {
+            match key.to_string().as_str() {
+                "path" | "method" | "headers" | "query" => {}
+                "response" if shared.response.is_some() || shared.responses.is_some() => {
+                    return Err(Error::new(
+                        key.span(),
+                        "Cannot declare a response on both the enum and its variants",
+                    ))
+                }
+                "response" => {}
+                "paginate" => {
+                    return Err(Error::new(
+                        key.span(),
+                        "Pagination is only supported on endpoints which are structs",
+                    ))
+                }
+                "request_type" | "response_type" | "request_codec" | "response_codec"
+                | "builder" | "responses" | "error" | "retry" => {
+                    return Err(Error::new(
+                        key.span(),
+                        format!("Parameter must be declared on the enum: {}", key).as_str(),
+                    ))
+                }
+                _ => return Err(Error::new(key.span(), "Unknown parameter")),
+            }
+        }
+        let params = ParametersBuilder::new(&map)?;
+        let path = params
+            .path
+            .ok_or_else(|| Error::new(var.span(), "Missing required parameter: path"))?;
+        let method = params
+            .method
+            .or_else(|| shared.method.clone())
+            .unwrap_or_else(|| syn::parse_str("GET").unwrap());
+        let response = params
+            .response
+            .clone()
+            .or_else(|| shared.response.clone())
+            .unwrap_or_else(|| syn::parse_quote! { () });
+        let name = var.unraw().to_string();
+        if let Some(r) = params.response {
+            responses.push((var.clone(), r));
+        }
+        variants.push(quote! {
+            Self::#var { .. } => Some(#name),
+        });
+        let fixed = shared
+            .headers
+            .iter()
+            .chain(params.headers.iter())
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // Generate the methods from the fields of the variant
+        let serde_attrs = gen_serde_attrs(&ast.attrs, var);
+        let op = gen_operation(
+            &path,
+            &variant.fields,
+            &fixed,
+            params.query_style.as_ref().or(shared.query_style.as_ref()),
+            &serde_attrs,
+            Receiver::Variant,
+        )?;
+        multipart |= op.multipart;
+        operations.push(op.spec.into_operation(
+            &format!("{}{}", id.unraw(), var.unraw()),
+            &variant.attrs,
+            &method,
+            openapi::gen_responses(
+                &response,
+                shared.responses.as_deref(),
+                shared.error.as_ref(),
+            ),
+        ));
+
+        let bindings = variant.fields.iter().map(|f| f.ident.clone().unwrap());
+        let pattern = quote! { Self::#var { #(#bindings),* } };
+        let path = op.path;
+        paths.push(quote! {
+            #[allow(unused_variables)]
+            #pattern => #path,
+        });
+        request_methods.push(quote! {
+            Self::#var { .. } => RequestMethod::#method,
+        });
+        let arm = |method: Option<proc_macro2::TokenStream>| match method {
+            Some(m) => quote! {
+                #[allow(unused_variables)]
+                #pattern => {
+                    #m
+                }
+            },
+            None => quote! {
+                Self::#var { .. } => Ok(None),
+            },
+        };
+        defined[0] |= op.query.is_some();
+        defined[1] |= op.headers.is_some();
+        defined[2] |= op.body.is_some();
+        queries.push(arm(op.query));
+        headers.push(arm(op.headers));
+        bodies.push(arm(op.body));
+    }
+
+    // Resolve codecs
+    let (request_codec, response_codec) = gen_codecs(
+        shared.request_type,
+        shared.request_codec,
+        shared.response_type,
+        shared.response_codec,
+        multipart,
+    )?;
+
+    // Generate an enum holding the response of each variant
+    let (response, response_enum) = match (shared.response, responses.is_empty()) {
+        (Some(r), _) => (r, quote! {}),
+        (None, _) if shared.responses.is_some() => {
+            gen_responses(id, &ast.vis, shared.responses.as_ref().unwrap())
+        }
+        (None, true) => (syn::parse_quote! { () }, quote! {}),
+        (None, false) => gen_variant_responses(id, &ast.vis, &responses),
+    };
+
+    // Only generate the methods defined by at least one of the variants
+    let dispatch = |arms: Vec<proc_macro2::TokenStream>, defined: bool| {
+        defined.then(|| {
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        })
+    };
+    let methods = gen_methods(
+        dispatch(queries, defined[0]),
+        dispatch(headers, defined[1]),
+        dispatch(bodies, defined[2]),
+    );
+    let error = gen_error(shared.error);
+    let retry = gen_retry(shared.retry);
+    let describe = openapi::gen_describe(id, &ast.generics, operations);
+
+    // Capture generic information
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    // Generate Endpoint implementation
+    Ok(quote! {
+        #response_enum
+
+        const _: () = {
+            use rustified::__private::serde::Serialize;
+            use rustified::http::{build_body, build_query};
+            use rustified::client::Client;
+            use rustified::endpoint::Endpoint;
+            use rustified::enums::RequestMethod;
+            use rustified::errors::ClientError;
+
+            impl #impl_generics Endpoint for #id #ty_generics #where_clause {
+                type Response = #response;
+                type RequestCodec = #request_codec;
+                type ResponseCodec = #response_codec;
+
+                fn path(&self) -> String {
+                    match self {
+                        #(#paths)*
+                    }
+                }
+
+                fn method(&self) -> RequestMethod {
+                    match self {
+                        #(#request_methods)*
+                    }
+                }
+
+                fn variant(&self) -> Option<&'static str> {
+                    match self {
+                        #(#variants)*
+                    }
+                }
+
+                #methods
+
+                #error
+
+                #retry
+            }
+
+            #describe
+        };
+    })
+}
+
+/// Generates an enum holding the response of each variant of an enum endpoint
+/// which declares its own `response`.
+///
+/// Responses are parsed into the variant named by their
+/// `rustified::endpoint::Variant` extension, so variants sharing the same
+/// response type are still told apart. Responses of variants without a
+/// `response` result in an error.
+fn gen_variant_responses(
+    id: &Ident,
+    vis: &syn::Visibility,
+    responses: &[(Ident, syn::Type)],
+) -> (syn::Type, proc_macro2::TokenStream) {
+    let response_id = format_ident!("{}Response", id);
+    let variants = responses.iter().map(|(var, ty)| quote! { #var(#ty), });
+    let arms = responses.iter().map(|(var, _)| {
+        let name = var.unraw().to_string();
+        quote! {
+            Some(rustified::endpoint::Variant(#name)) => {
+                C::decode_response(response).map(Self::#var)
+            }
+        }
+    });
+    let doc = format!(
+        "The response of a [`{}`] endpoint, chosen by the variant which was executed.",
+        id
+    );
+
+    (
+        syn::parse_quote! { #response_id },
+        quote! {
+            #[doc = #doc]
+            #vis enum #response_id {
+                #(#variants)*
+            }
+
+            impl<C: rustified::codec::Codec> rustified::endpoint::FromResponse<C> for #response_id {
+                fn from_response(
+                    response: &rustified::__private::http::Response<Vec<u8>>,
+                ) -> Result<Self, rustified::errors::ClientError> {
+                    match rustified::endpoint::Variant::of(response) {
+                        #(#arms)*
+                        _ => Err(rustified::__private::variant_error(response)),
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// Implements `Endpoint` on the provided struct or enum.
+fn endpoint_derive(s: synstructure::Structure) -> proc_macro2::TokenStream {
+    let ast = s.ast();
+    let result = match &ast.data {
+        syn::Data::Struct(data) => struct_derive(ast, &data.fields),
+        syn::Data::Enum(data) => enum_derive(ast, data),
+        syn::Data::Union(_) => Err(Error::new(
+            Span::call_site(),
+            format!("Cannot derive `{}` on a union", MACRO_NAME).as_str(),
+        )),
+    };
+    result.unwrap_or_else(|e| e.into_tokens())
 }
 
 synstructure::decl_derive!([Endpoint, attributes(endpoint, serde)] => endpoint_derive);
+
+/// Implements `Schema` on the provided struct or enum.
+fn schema_derive(s: synstructure::Structure) -> proc_macro2::TokenStream {
+    openapi::gen_schema(s.ast()).unwrap_or_else(|e| e.into_tokens())
+}
+
+synstructure::decl_derive!(
+    [Schema, attributes(serde)] =>
+    /// Implements `rustified::openapi::Schema` on a struct or enum, describing
+    /// it as it's serialized by serde. The implementation is only generated
+    /// when the `openapi` feature of `rustified` is enabled.
+    schema_derive
+);
+
+/// Generates a typed API client from a trait listing the endpoints of an API.
+///
+/// The trait is replaced by a struct of the same name which wraps a client.
+/// Each method of the trait returns the type of the endpoint it executes and
+/// becomes an async method returning the parsed response of the endpoint. The
+/// endpoint is created from the arguments of the method, which are named after
+/// its fields, or by the body of the method when one is given:
+///
+/// ```ignore
+/// #[rustified::api]
+/// pub trait GitHub {
+///     fn get_user(name: String) -> GetUser;
+///
+///     fn delete_repo(owner: String, repo: String) -> Repos {
+///         Repos::Delete { owner, repo }
+///     }
+/// }
+///
+/// let github = GitHub::new(Client::default("https://api.github.com"));
+/// let user = github.get_user("octocat".to_string()).await?;
+/// ```
+///
+/// When the `blocking` feature of `rustified` is enabled, a blocking variant
+/// suffixed with `_block` is generated for each method.
+#[proc_macro_attribute]
+pub fn api(
+    attr: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let item = syn::parse_macro_input!(item as syn::ItemTrait);
+    api::expand(attr.into(), item)
+        .unwrap_or_else(|e| e.into_tokens())
+        .into()
+}
@@ -0,0 +1,453 @@
+//! Contains the generation of the `Describe` and `Schema` implementations used
+//! by the `rustified::openapi` module, which are only compiled when the
+//! `openapi` feature of `rustified` is enabled.
+
+use std::collections::HashMap;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{ext::IdentExt, Attribute, Field, Generics, Ident, Lit, LitStr, Meta, Type};
+
+use crate::{params::StatusResponse, parse, EndpointAttribute, Error};
+
+/// The parts of an operation which are determined by its path and fields.
+pub(crate) struct Spec {
+    /// The path template in the form used by OpenAPI, as in `/users/{name}`.
+    path: String,
+    parameters: Vec<TokenStream>,
+    request: Option<TokenStream>,
+}
+
+impl Spec {
+    /// Returns the [Spec] of an operation with the given path and fields.
+    ///
+    /// Placeholders in the path become path parameters, named after the field
+    /// they refer to. Query and header fields become parameters and the fields
+    /// sent in the body become the properties of the request body schema.
+    pub fn new(
+        path: &LitStr,
+        fields: &HashMap<EndpointAttribute, Vec<Field>>,
+        all_fields: &syn::Fields,
+        query_style: Option<&Ident>,
+    ) -> Result<Spec, Error> {
+        let find = |name: &str| {
+            all_fields
+                .iter()
+                .find(|f| f.ident.as_ref().map(|i| i.unraw() == name) == Some(true))
+        };
+
+        // Convert the path template
+        let (template, placeholders) = parse::path_template(path)?;
+        let mut placeholders = placeholders.into_iter();
+        let mut spec_path = String::new();
+        let mut parameters = Vec::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, chars.peek()) {
+                ('{', Some('{')) | ('}', Some('}')) => {
+                    chars.next();
+                    spec_path.push(c);
+                }
+                ('{', Some('}')) => {
+                    chars.next();
+                    let content = placeholders.next().unwrap().content;
+                    let content = content.trim();
+                    let name = content.strip_prefix("self.").unwrap_or(content);
+                    let schema = match find(name) {
+                        Some(f) => schema(&f.ty),
+                        None => {
+                            quote! { rustified::__private::serde_json::json!({ "type": "string" }) }
+                        }
+                    };
+                    spec_path.push_str(&format!("{{{}}}", name));
+                    parameters.push(parameter(name, quote! { Path }, true, schema, None));
+                }
+                _ => spec_path.push(c),
+            }
+        }
+        if !spec_path.starts_with('/') {
+            spec_path.insert(0, '/');
+        }
+
+        for field in fields.get(&EndpointAttribute::Query).into_iter().flatten() {
+            let style = parse::field_query_style(field)?.or_else(|| query_style.cloned());
+            let style = style.map(|s| quote! { Some(rustified::query::QueryStyle::#s) });
+            parameters.push(parameter(
+                &parse::serde_name(field),
+                quote! { Query },
+                !parse::is_std_option(&field.ty),
+                schema(&field.ty),
+                style,
+            ));
+        }
+        for field in fields.get(&EndpointAttribute::Header).into_iter().flatten() {
+            parameters.push(parameter(
+                &parse::header_name(field)?.value(),
+                quote! { Header },
+                !parse::is_std_option(&field.ty),
+                schema(&field.ty),
+                None,
+            ));
+        }
+
+        Ok(Spec {
+            path: spec_path,
+            parameters,
+            request: request(fields),
+        })
+    }
+
+    /// Returns an expression creating the `rustified::openapi::Operation` with
+    /// the given name, doc comments, method and responses.
+    pub fn into_operation(
+        self,
+        id: &str,
+        docs: &[Attribute],
+        method: &syn::Expr,
+        responses: Vec<TokenStream>,
+    ) -> TokenStream {
+        let (summary, description) = doc_comments(docs);
+        let summary = option(summary);
+        let description = option(description);
+        let path = self.path;
+        let parameters = self.parameters;
+        let request = match self.request {
+            Some(r) => quote! { Some(#r) },
+            None => quote! { None },
+        };
+        quote! {
+            rustified::openapi::Operation {
+                id: #id.to_string(),
+                path: #path.to_string(),
+                method: rustified::enums::RequestMethod::#method,
+                summary: #summary,
+                description: #description,
+                parameters: vec![#(#parameters),*],
+                request: #request,
+                responses: vec![#(#responses),*],
+            }
+        }
+    }
+}
+
+/// Returns the expressions creating the `rustified::openapi::Response`s of an
+/// operation.
+///
+/// Responses mapped to status codes are used when given, otherwise the single
+/// response is used for the `200` status code. The error type, if any, is used
+/// for the default response. Responses of type `()` have no content.
+pub(crate) fn gen_responses(
+    response: &Type,
+    responses: Option<&[StatusResponse]>,
+    error: Option<&Type>,
+) -> Vec<TokenStream> {
+    let content = |ty: &Type| match ty {
+        Type::Tuple(t) if t.elems.is_empty() => quote! { None },
+        ty => {
+            let schema = schema(ty);
+            quote! {
+                Some(rustified::openapi::Content {
+                    media_type: __response_type.to_string(),
+                    schema: #schema,
+                })
+            }
+        }
+    };
+    let mut result = Vec::new();
+    let mut default = false;
+    match responses {
+        Some(responses) => {
+            for r in responses {
+                default |= r.status.is_none();
+                let status = option(r.status);
+                let content = content(&r.ty);
+                result.push(quote! {
+                    rustified::openapi::Response { status: #status, content: #content }
+                });
+            }
+        }
+        None => {
+            let content = content(response);
+            result.push(quote! {
+                rustified::openapi::Response { status: Some(200), content: #content }
+            });
+        }
+    }
+    if let (Some(e), false) = (error, default) {
+        let content = content(e);
+        result.push(quote! {
+            rustified::openapi::Response { status: None, content: #content }
+        });
+    }
+    result
+}
+
+/// Generates the implementation of `Describe` returning the given operations,
+/// which is left out unless the `openapi` feature of `rustified` is enabled.
+pub(crate) fn gen_describe(
+    id: &Ident,
+    generics: &Generics,
+    operations: Vec<TokenStream>,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        rustified::__private::openapi! {
+            impl #impl_generics rustified::openapi::Describe for #id #ty_generics #where_clause {
+                fn operations() -> Vec<rustified::openapi::Operation> {
+                    use rustified::codec::Codec;
+                    use rustified::openapi::__private::{SchemaOf, ViaAny, ViaSchema};
+
+                    let __request_type =
+                        <<Self as Endpoint>::RequestCodec as Codec>::content_type();
+                    let __response_type =
+                        <<Self as Endpoint>::ResponseCodec as Codec>::content_type();
+                    vec![#(#operations),*]
+                }
+            }
+        }
+    }
+}
+
+/// Generates the implementation of `Schema` for a struct or enum, which is left
+/// out unless the `openapi` feature of `rustified` is enabled.
+///
+/// Structs with named fields are described as objects whose properties are
+/// named as the fields are serialized. [Option] fields and fields with
+/// `#[serde(default)]` or `#[serde(skip_serializing_if = "...")]` aren't
+/// required, while fields with `#[serde(skip)]` are left out. Newtype structs
+/// are described by the schema of their field and other tuple structs as
+/// arrays. Enums must be externally tagged, which is the default, or untagged.
+pub(crate) fn gen_schema(ast: &syn::DeriveInput) -> Result<TokenStream, Error> {
+    let id = &ast.ident;
+    let schema = match &ast.data {
+        syn::Data::Struct(data) => fields_schema(&data.fields)?,
+        syn::Data::Enum(data) => enum_schema(&ast.attrs, data)?,
+        syn::Data::Union(_) => {
+            return Err(Error::new(
+                Span::call_site(),
+                "Cannot derive `Schema` on a union",
+            ))
+        }
+    };
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    Ok(quote! {
+        rustified::__private::openapi! {
+            impl #impl_generics rustified::openapi::Schema for #id #ty_generics #where_clause {
+                fn schema() -> rustified::__private::serde_json::Value {
+                    use rustified::openapi::__private::{SchemaOf, ViaAny, ViaSchema};
+
+                    #schema
+                }
+            }
+        }
+    })
+}
+
+/// Returns an expression creating the schema of a struct or enum variant with
+/// the given fields.
+fn fields_schema(fields: &syn::Fields) -> Result<TokenStream, Error> {
+    let schema = match fields {
+        syn::Fields::Named(named) => {
+            let mut properties = Vec::new();
+            for field in named.named.iter() {
+                let serde = parse::attributes(&field.attrs, "serde")?;
+                if parse::has_serde_param(&serde, "skip")
+                    || parse::has_serde_param(&serde, "skip_serializing")
+                {
+                    continue;
+                }
+                if parse::has_serde_param(&serde, "flatten") {
+                    return Err(Error::new(
+                        field.ident.as_ref().unwrap().span(),
+                        "Flattened fields are not supported by `Schema`",
+                    ));
+                }
+                let name = parse::serde_name(field);
+                let required = !parse::is_std_option(&field.ty)
+                    && !parse::has_serde_param(&serde, "default")
+                    && !parse::has_serde_param(&serde, "skip_serializing_if");
+                let schema = schema(&field.ty);
+                properties.push(quote! { (#name, #schema, #required) });
+            }
+            quote! { rustified::openapi::object(vec![#(#properties),*]) }
+        }
+        syn::Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+            schema(&unnamed.unnamed[0].ty)
+        }
+        syn::Fields::Unnamed(unnamed) => {
+            let items = unnamed.unnamed.iter().map(|f| schema(&f.ty));
+            let len = unnamed.unnamed.len();
+            quote! {{
+                let items: Vec<rustified::__private::serde_json::Value> = vec![#(#items),*];
+                rustified::__private::serde_json::json!({
+                    "type": "array",
+                    "prefixItems": items,
+                    "minItems": #len,
+                    "maxItems": #len,
+                })
+            }}
+        }
+        syn::Fields::Unit => {
+            quote! { rustified::__private::serde_json::json!({ "type": "null" }) }
+        }
+    };
+    Ok(schema)
+}
+
+/// Returns an expression creating the schema of an enum, which is one of the
+/// schemas of its variants.
+///
+/// Unit variants of an externally tagged enum are serialized as strings and
+/// are combined into a single string schema listing their names. Other
+/// variants are objects with a single property named after the variant, unless
+/// the enum is untagged.
+fn enum_schema(attrs: &[Attribute], data: &syn::DataEnum) -> Result<TokenStream, Error> {
+    let serde = parse::attributes(attrs, "serde")?;
+    if parse::has_serde_param(&serde, "tag") || parse::has_serde_param(&serde, "content") {
+        return Err(Error::new(
+            Span::call_site(),
+            "Only externally tagged and untagged enums are supported by `Schema`",
+        ));
+    }
+    let untagged = parse::has_serde_param(&serde, "untagged");
+
+    let mut names = Vec::new();
+    let mut variants = Vec::new();
+    for variant in data.variants.iter() {
+        if parse::has_serde_param(&parse::attributes(&variant.attrs, "serde")?, "skip") {
+            continue;
+        }
+        let name = parse::serde_rename(&variant.attrs)
+            .unwrap_or_else(|| variant.ident.unraw().to_string());
+        match (&variant.fields, untagged) {
+            (syn::Fields::Unit, false) => names.push(name),
+            (fields, false) => {
+                let schema = fields_schema(fields)?;
+                variants.push(quote! { rustified::openapi::object(vec![(#name, #schema, true)]) });
+            }
+            (fields, true) => variants.push(fields_schema(fields)?),
+        }
+    }
+    if !names.is_empty() {
+        variants.insert(
+            0,
+            quote! {
+                rustified::__private::serde_json::json!({ "type": "string", "enum": [#(#names),*] })
+            },
+        );
+    }
+
+    if variants.len() == 1 {
+        return Ok(variants.remove(0));
+    }
+    Ok(quote! {{
+        let variants: Vec<rustified::__private::serde_json::Value> = vec![#(#variants),*];
+        rustified::__private::serde_json::json!({ "oneOf": variants })
+    }})
+}
+
+/// Returns an expression taking the schema of the given type, which is empty
+/// when the type doesn't implement `Schema`.
+fn schema(ty: &Type) -> TokenStream {
+    quote! { (&&SchemaOf::<#ty>::new()).schema() }
+}
+
+fn parameter(
+    name: &str,
+    location: TokenStream,
+    required: bool,
+    schema: TokenStream,
+    style: Option<TokenStream>,
+) -> TokenStream {
+    let style = style.unwrap_or_else(|| quote! { None });
+    quote! {
+        rustified::openapi::Parameter {
+            name: #name.to_string(),
+            location: rustified::openapi::Location::#location,
+            required: #required,
+            schema: #schema,
+            style: #style,
+        }
+    }
+}
+
+/// Returns an expression creating the request body of an operation, if it has
+/// one.
+fn request(fields: &HashMap<EndpointAttribute, Vec<Field>>) -> Option<TokenStream> {
+    let binary = quote! {
+        rustified::__private::serde_json::json!({ "type": "string", "format": "binary" })
+    };
+    let properties = |fields: &[&Field]| {
+        let properties = fields.iter().map(|f| {
+            let name = parse::serde_name(f);
+            let required = !parse::is_std_option(&f.ty);
+            let schema = schema(&f.ty);
+            quote! { (#name, #schema, #required) }
+        });
+        quote! { rustified::openapi::object(vec![#(#properties),*]) }
+    };
+    let get = |attr: EndpointAttribute| fields.get(&attr).into_iter().flatten().collect::<Vec<_>>();
+
+    let schema = if fields.contains_key(&EndpointAttribute::Part)
+        || fields.contains_key(&EndpointAttribute::File)
+    {
+        let parts = get(EndpointAttribute::Part).into_iter().map(|f| {
+            let name = parse::serde_name(f);
+            let required = !parse::is_std_option(&f.ty);
+            quote! { (#name, rustified::__private::serde_json::json!({ "type": "string" }), #required) }
+        });
+        let files = get(EndpointAttribute::File).into_iter().map(|f| {
+            let name = parse::serde_name(f);
+            let required = !parse::is_std_option(&f.ty);
+            quote! { (#name, #binary, #required) }
+        });
+        quote! { rustified::openapi::object(vec![#(#parts,)* #(#files),*]) }
+    } else if fields.contains_key(&EndpointAttribute::Raw) {
+        binary
+    } else if fields.contains_key(&EndpointAttribute::Body) {
+        properties(&get(EndpointAttribute::Body))
+    } else if fields.contains_key(&EndpointAttribute::Untagged) {
+        properties(&get(EndpointAttribute::Untagged))
+    } else {
+        return None;
+    };
+
+    Some(quote! {
+        rustified::openapi::Content {
+            media_type: __request_type.to_string(),
+            schema: #schema,
+        }
+    })
+}
+
+/// Splits the doc comments into a summary, which is their first paragraph,
+/// and a description holding the rest.
+fn doc_comments(attrs: &[Attribute]) -> (Option<String>, Option<String>) {
+    let lines = attrs
+        .iter()
+        .filter(|a| a.path.is_ident("doc"))
+        .filter_map(|a| match a.parse_meta() {
+            Ok(Meta::NameValue(nv)) => match nv.lit {
+                Lit::Str(lit) => Some(lit.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let mut paragraphs = lines.split(|l| l.is_empty()).filter(|p| !p.is_empty());
+    let summary = paragraphs.next().map(|p| p.join(" "));
+    let description = paragraphs.map(|p| p.join("\n")).collect::<Vec<_>>();
+    let description = match description.is_empty() {
+        true => None,
+        false => Some(description.join("\n\n")),
+    };
+    (summary, description)
+}
+
+fn option<T: quote::ToTokens>(value: Option<T>) -> TokenStream {
+    match value {
+        Some(v) => quote! { Some(#v.into()) },
+        None => quote! { None },
+    }
+}
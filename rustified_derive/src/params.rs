@@ -1,9 +1,12 @@
 use std::collections::HashMap;
 
 use proc_macro2::Span;
-use syn::{Expr, Ident, LitStr, Type};
+use syn::{spanned::Spanned, Expr, Ident, LitStr, Type};
 
-use crate::Error;
+use crate::{
+    parse::{self, Value},
+    Error,
+};
 
 /// Used for building the parameter list for the derive function
 #[derive(Default, Debug)]
@@ -13,7 +16,15 @@ pub struct ParametersBuilder {
     pub response: Option<Type>,
     pub request_type: Option<Expr>,
     pub response_type: Option<Expr>,
+    pub request_codec: Option<Type>,
+    pub response_codec: Option<Type>,
     pub builder: Option<bool>,
+    pub responses: Option<Vec<StatusResponse>>,
+    pub error: Option<Type>,
+    pub headers: Option<Vec<StaticHeader>>,
+    pub query_style: Option<Ident>,
+    pub paginate: Option<Paginate>,
+    pub retry: Option<RetryPolicy>,
 }
 
 /// Represents all valid parameters that can be passed to the derive function
@@ -22,44 +33,122 @@ pub struct Parameters {
     pub path: LitStr,
     pub method: Expr,
     pub response: Type,
-    pub request_type: Expr,
-    pub response_type: Expr,
+    pub request_type: Option<Expr>,
+    pub response_type: Option<Expr>,
+    pub request_codec: Option<Type>,
+    pub response_codec: Option<Type>,
     pub builder: bool,
+    pub responses: Option<Vec<StatusResponse>>,
+    pub error: Option<Type>,
+    pub headers: Vec<StaticHeader>,
+    pub query_style: Option<Ident>,
+    pub paginate: Option<Paginate>,
+    pub retry: Option<RetryPolicy>,
 }
 
-impl Parameters {
-    /// Given a map of identities to literal strings, builds a new instance of
-    /// [Parameters] using the contents of the map.
-    ///
-    /// The only required parameter is `path` and not providing it will cause
-    /// the function to fail. All other parameters are optional and will have
-    /// sane defaults provided if they are not found in the map.
-    pub fn new(map: HashMap<Ident, LitStr>) -> Result<Parameters, Error> {
+/// A header with a fixed value given by the `headers` parameter
+#[derive(Clone, Debug)]
+pub struct StaticHeader {
+    /// The lowercase name of the header
+    pub name: LitStr,
+    pub value: LitStr,
+}
+
+/// A response type mapped to a status code by the `responses` parameter
+#[derive(Debug)]
+pub struct StatusResponse {
+    /// The status code, or [None] for the `default` response
+    pub status: Option<u16>,
+    pub span: Span,
+    pub ty: Type,
+}
+
+/// The pagination of an endpoint given by the `paginate` parameter
+#[derive(Debug)]
+pub struct Paginate {
+    pub span: Span,
+    pub strategy: PageStrategy,
+    /// The query parameter holding the number of items per page
+    pub limit: Option<LitStr>,
+    /// The field of the response body holding the items of a page
+    pub items: Option<LitStr>,
+    /// The type of the items of a page, if given explicitly
+    pub item: Option<Type>,
+}
+
+/// The way the next page is requested, mirroring `Strategy`
+#[derive(Debug)]
+pub enum PageStrategy {
+    /// The field holding the cursor and the query parameter it's sent in
+    Cursor(LitStr, LitStr),
+    Offset(LitStr),
+    Page(LitStr),
+    Link,
+}
+
+/// The retry policy of an endpoint given by the `retry` parameter, leaving the
+/// options which aren't given to their defaults
+#[derive(Debug, Default)]
+pub struct RetryPolicy {
+    pub max: Option<u32>,
+    /// The variant of `Backoff`
+    pub backoff: Option<Ident>,
+    /// The delay before the first retry in milliseconds
+    pub delay: Option<u64>,
+    /// The longest delay between retries in milliseconds
+    pub max_delay: Option<u64>,
+    pub on: Option<Vec<u16>>,
+    pub non_idempotent: Option<bool>,
+}
+
+impl ParametersBuilder {
+    /// Given a map of identities to their values, builds a new instance of
+    /// [ParametersBuilder] using the contents of the map.
+    pub fn new(map: &HashMap<Ident, Value>) -> Result<ParametersBuilder, Error> {
         let mut builder = ParametersBuilder::default();
-        for key in map.keys() {
+        for (key, value) in map {
             match key.to_string().as_str() {
-                "path" => builder.path = Some(map[key].clone()),
-                "method" => {
-                    builder.method = Some(parse(&map[key])?);
-                }
-                "response" => {
-                    builder.response = Some(parse(&map[key])?);
-                }
-                "request_type" => {
-                    builder.request_type = Some(parse(&map[key])?);
-                }
-                "response_type" => {
-                    builder.response_type = Some(parse(&map[key])?);
-                }
-                "builder" => {
-                    builder.builder = Some(true);
-                }
+                "path" => builder.path = Some(string(key, value)?.clone()),
+                "method" => builder.method = Some(expr(key, value)?),
+                "response" => builder.response = Some(ty(key, value)?),
+                "request_type" => builder.request_type = Some(expr(key, value)?),
+                "response_type" => builder.response_type = Some(expr(key, value)?),
+                "request_codec" => builder.request_codec = Some(ty(key, value)?),
+                "response_codec" => builder.response_codec = Some(ty(key, value)?),
+                "builder" => builder.builder = Some(boolean(key, value)?),
+                "error" => builder.error = Some(ty(key, value)?),
+                "responses" => builder.responses = Some(responses(value)?),
+                "headers" => builder.headers = Some(headers(value)?),
+                "query" => builder.query_style = Some(query(value)?),
+                "paginate" => builder.paginate = Some(paginate(value)?),
+                "retry" => builder.retry = Some(retry(value)?),
                 _ => {
                     return Err(Error::new(key.span(), "Unknown parameter"));
                 }
             }
         }
 
+        if builder.response.is_some() && builder.responses.is_some() {
+            let key = map.keys().find(|k| *k == "responses").unwrap();
+            return Err(Error::new(
+                key.span(),
+                "Cannot specify both a response and responses",
+            ));
+        }
+
+        Ok(builder)
+    }
+}
+
+impl Parameters {
+    /// Given a map of identities to literal strings, builds a new instance of
+    /// [Parameters] using the contents of the map.
+    ///
+    /// The only required parameter is `path` and not providing it will cause
+    /// the function to fail. All other parameters are optional and will have
+    /// sane defaults provided if they are not found in the map.
+    pub fn new(map: HashMap<Ident, Value>) -> Result<Parameters, Error> {
+        let builder = ParametersBuilder::new(&map)?;
         let params = Parameters {
             path: match builder.path {
                 Some(p) => p,
@@ -76,19 +165,430 @@ impl Parameters {
             response: builder
                 .response
                 .unwrap_or_else(|| syn::parse_str("()").unwrap()),
-            request_type: builder
-                .request_type
-                .unwrap_or_else(|| syn::parse_str("JSON").unwrap()),
-            response_type: builder
-                .response_type
-                .unwrap_or_else(|| syn::parse_str("JSON").unwrap()),
+            request_type: builder.request_type,
+            response_type: builder.response_type,
+            request_codec: builder.request_codec,
+            response_codec: builder.response_codec,
             builder: builder.builder.unwrap_or(false),
+            responses: builder.responses,
+            error: builder.error,
+            headers: builder.headers.unwrap_or_default(),
+            query_style: builder.query_style,
+            paginate: builder.paginate,
+            retry: builder.retry,
         };
 
         Ok(params)
     }
 }
 
+/// Converts the types mapped to status codes by the `responses` parameter into
+/// a list of [StatusResponse]s, failing if a status code is mapped twice
+fn responses(value: &Value) -> Result<Vec<StatusResponse>, Error> {
+    let types = match value {
+        Value::Responses(types) => types,
+        v => {
+            return Err(Error::new(
+                v.span(),
+                "Expected status codes mapped to types, as in `responses(200 = User)`",
+            ))
+        }
+    };
+    let mut result = Vec::<StatusResponse>::new();
+    for t in types {
+        if result.iter().any(|r| r.status == t.status) {
+            return Err(Error::new(t.span, "Status code is mapped more than once"));
+        }
+        result.push(StatusResponse {
+            status: t.status,
+            span: t.span,
+            ty: match &t.ty {
+                Value::Str(lit) => parse(lit)?,
+                Value::Type(ty) => (**ty).clone(),
+                v => return Err(Error::new(v.span(), "Expected a type, as in `200 = User`")),
+            },
+        });
+    }
+    if result.is_empty() {
+        return Err(Error::new(
+            Span::call_site(),
+            "The responses parameter cannot be empty",
+        ));
+    }
+
+    Ok(result)
+}
+
+/// Converts the headers given by the `headers` parameter, in the form of
+/// `Name: value`, into a list of [StaticHeader]s, failing if a name or value is
+/// invalid
+fn headers(value: &Value) -> Result<Vec<StaticHeader>, Error> {
+    let values = match value {
+        Value::List(_, values) => values,
+        v => {
+            return Err(Error::new(
+                v.span(),
+                "Expected a list of headers, as in `headers(\"Accept: text/plain\")`",
+            ))
+        }
+    };
+    let mut result = Vec::new();
+    for value in values {
+        let lit = match value {
+            Value::Str(lit) => lit,
+            v => {
+                return Err(Error::new(
+                    v.span(),
+                    "Expected a header in the form of `Name: value`",
+                ))
+            }
+        };
+        let value = lit.value();
+        let (name, value) = match value.split_once(':') {
+            Some((n, v)) => (n.trim(), v.trim()),
+            None => {
+                return Err(Error::new(
+                    lit.span(),
+                    "Expected a header in the form of `Name: value`",
+                ))
+            }
+        };
+        if http::header::HeaderName::from_bytes(name.as_bytes()).is_err() {
+            return Err(Error::new(
+                lit.span(),
+                format!("Invalid header name: {}", name).as_str(),
+            ));
+        }
+        // Values are created with `HeaderValue::from_static`, which only accepts
+        // visible ASCII characters, spaces and tabs
+        if !value
+            .bytes()
+            .all(|b| b == b'\t' || (b' '..=b'~').contains(&b))
+        {
+            return Err(Error::new(
+                lit.span(),
+                format!(
+                    "Invalid value for header {}, only visible ASCII characters are allowed",
+                    name
+                )
+                .as_str(),
+            ));
+        }
+        result.push(StaticHeader {
+            name: LitStr::new(&name.to_lowercase(), lit.span()),
+            value: LitStr::new(value, lit.span()),
+        });
+    }
+
+    Ok(result)
+}
+
+/// Returns the variant of `QueryStyle` given by the `query` parameter, as in
+/// `query(style = comma)`
+fn query(value: &Value) -> Result<Ident, Error> {
+    let params = match value {
+        Value::Params(_, params) => params,
+        v => {
+            return Err(Error::new(
+                v.span(),
+                "Expected query options, as in `query(style = comma)`",
+            ))
+        }
+    };
+    let mut style = None;
+    for (key, value) in params {
+        if key != "style" {
+            return Err(Error::new(
+                key.span(),
+                "Unknown query option, expected `style`",
+            ));
+        }
+        style = Some(match value {
+            Value::Str(lit) => parse::query_style(&lit.value(), lit.span())?,
+            Value::Type(ty) => match &**ty {
+                Type::Path(p) if p.path.get_ident().is_some() => {
+                    let id = p.path.get_ident().unwrap();
+                    parse::query_style(&id.to_string(), id.span())?
+                }
+                _ => {
+                    return Err(Error::new(
+                        ty.span(),
+                        "Expected a query style, as in `comma`",
+                    ))
+                }
+            },
+            v => {
+                return Err(Error::new(
+                    v.span(),
+                    "Expected a query style, as in `comma`",
+                ))
+            }
+        });
+    }
+
+    style.ok_or_else(|| Error::new(value.span(), "Missing query option: style"))
+}
+
+/// Returns the pagination given by the `paginate` parameter, as in
+/// `paginate(cursor = "next_cursor", param = "cursor")`
+fn paginate(value: &Value) -> Result<Paginate, Error> {
+    let (span, params) = match value {
+        Value::Params(span, params) => (*span, params),
+        v => {
+            return Err(Error::new(
+                v.span(),
+                "Expected pagination options, as in `paginate(page = \"page\")`",
+            ))
+        }
+    };
+
+    let mut strategies = Vec::new();
+    let (mut cursor, mut param, mut link) = (None, None, None);
+    let (mut limit, mut items, mut item) = (None, None, None);
+    for (key, value) in params {
+        match key.to_string().as_str() {
+            "cursor" => cursor = Some(string(key, value)?.clone()),
+            "param" => param = Some((key, string(key, value)?.clone())),
+            "offset" => strategies.push((key, PageStrategy::Offset(string(key, value)?.clone()))),
+            "page" => strategies.push((key, PageStrategy::Page(string(key, value)?.clone()))),
+            "link" => link = Some((key, boolean(key, value)?)),
+            "limit" => limit = Some(string(key, value)?.clone()),
+            "items" => items = Some(string(key, value)?.clone()),
+            "item" => item = Some(ty(key, value)?),
+            _ => {
+                return Err(Error::new(
+                    key.span(),
+                    "Unknown pagination option, expected one of `cursor`, `param`, `offset`, \
+                     `page`, `link`, `limit`, `items` or `item`",
+                ))
+            }
+        }
+    }
+
+    let key = |name: &str| params.keys().find(|k| *k == name).unwrap();
+    match (cursor, param) {
+        (Some(cursor), Some((_, param))) => {
+            strategies.push((key("cursor"), PageStrategy::Cursor(cursor, param)))
+        }
+        (Some(_), None) => {
+            return Err(Error::new(
+                key("cursor").span(),
+                "A cursor requires the query parameter it's sent in, as in `param = \"cursor\"`",
+            ))
+        }
+        (None, Some((key, _))) => {
+            return Err(Error::new(
+                key.span(),
+                "The param option can only be used along with a cursor",
+            ))
+        }
+        (None, None) => {}
+    }
+    if let Some((key, true)) = link {
+        strategies.push((key, PageStrategy::Link));
+    }
+
+    if strategies.len() > 1 {
+        let key = strategies
+            .iter()
+            .map(|(k, _)| *k)
+            .max_by_key(|k| k.to_string())
+            .unwrap();
+        return Err(Error::new(
+            key.span(),
+            "Only one of `cursor`, `offset`, `page` or `link` can be used",
+        ));
+    }
+    let strategy = match strategies.pop() {
+        Some((_, strategy)) => strategy,
+        None => {
+            return Err(Error::new(
+                span,
+                "Missing pagination strategy, expected one of `cursor`, `offset`, `page` or \
+                 `link`",
+            ))
+        }
+    };
+    if limit.is_some() && !matches!(strategy, PageStrategy::Offset(_) | PageStrategy::Page(_)) {
+        return Err(Error::new(
+            key("limit").span(),
+            "The limit option can only be used along with `offset` or `page`",
+        ));
+    }
+
+    Ok(Paginate {
+        span,
+        strategy,
+        limit,
+        items,
+        item,
+    })
+}
+
+/// Returns the retry policy given by the `retry` parameter, as in
+/// `retry(max = 3, backoff = "exponential", on = [502, 503, 504])`
+fn retry(value: &Value) -> Result<RetryPolicy, Error> {
+    let params = match value {
+        Value::Params(_, params) => params,
+        Value::List(_, values) if values.is_empty() => return Ok(RetryPolicy::default()),
+        v => {
+            return Err(Error::new(
+                v.span(),
+                "Expected retry options, as in `retry(max = 3)`",
+            ))
+        }
+    };
+
+    let mut policy = RetryPolicy::default();
+    for (key, value) in params {
+        match key.to_string().as_str() {
+            "max" => policy.max = Some(integer(key, value)?),
+            "backoff" => policy.backoff = Some(backoff(value)?),
+            "delay" => policy.delay = Some(integer(key, value)?),
+            "max_delay" => policy.max_delay = Some(integer(key, value)?),
+            "on" => policy.on = Some(status_codes(value)?),
+            "non_idempotent" => policy.non_idempotent = Some(boolean(key, value)?),
+            _ => {
+                return Err(Error::new(
+                    key.span(),
+                    "Unknown retry option, expected one of `max`, `backoff`, `delay`, \
+                     `max_delay`, `on` or `non_idempotent`",
+                ))
+            }
+        }
+    }
+
+    Ok(policy)
+}
+
+/// Returns the variant of `Backoff` given by the `backoff` retry option
+fn backoff(value: &Value) -> Result<Ident, Error> {
+    let (name, span) = match value {
+        Value::Str(lit) => (lit.value(), lit.span()),
+        Value::Type(ty) => match &**ty {
+            Type::Path(p) if p.path.get_ident().is_some() => {
+                let id = p.path.get_ident().unwrap();
+                (id.to_string(), id.span())
+            }
+            _ => (String::new(), ty.span()),
+        },
+        v => (String::new(), v.span()),
+    };
+    match name.as_str() {
+        "constant" => Ok(Ident::new("Constant", span)),
+        "exponential" => Ok(Ident::new("Exponential", span)),
+        _ => Err(Error::new(
+            span,
+            "Unknown backoff, expected `constant` or `exponential`",
+        )),
+    }
+}
+
+/// Returns the status codes given by the `on` retry option, as in
+/// `on = [502, 503]`
+fn status_codes(value: &Value) -> Result<Vec<u16>, Error> {
+    let values = match value {
+        Value::List(_, values) => values,
+        v => {
+            return Err(Error::new(
+                v.span(),
+                "Expected a list of status codes, as in `on = [502, 503]`",
+            ))
+        }
+    };
+    values
+        .iter()
+        .map(|v| match v {
+            Value::Int(lit) => match lit.base10_parse::<u16>() {
+                Ok(s) if (100..=599).contains(&s) => Ok(s),
+                _ => Err(Error::new(
+                    lit.span(),
+                    "Status codes must be between 100 and 599",
+                )),
+            },
+            v => Err(Error::new(v.span(), "Expected a status code, as in `503`")),
+        })
+        .collect()
+}
+
+/// Returns the string literal passed into the parameter with the given key,
+/// failing if the value is of another kind
+fn string<'a>(key: &Ident, value: &'a Value) -> Result<&'a LitStr, Error> {
+    match value {
+        Value::Str(lit) => Ok(lit),
+        v => Err(Error::new(
+            v.span(),
+            format!(
+                "Expected a string literal for {}, as in `{} = \"...\"`",
+                key, key
+            )
+            .as_str(),
+        )),
+    }
+}
+
+/// Returns the type passed into the parameter with the given key, either as a
+/// path or in string literal form
+fn ty(key: &Ident, value: &Value) -> Result<Type, Error> {
+    match value {
+        Value::Str(lit) => parse(lit),
+        Value::Type(ty) => Ok((**ty).clone()),
+        v => Err(Error::new(
+            v.span(),
+            format!("Expected a type for {}, as in `{} = Vec<User>`", key, key).as_str(),
+        )),
+    }
+}
+
+/// Returns the expression passed into the parameter with the given key, either
+/// as an identifier or in string literal form
+fn expr(key: &Ident, value: &Value) -> Result<Expr, Error> {
+    match value {
+        Value::Str(lit) => parse(lit),
+        Value::Type(ty) if matches!(&**ty, Type::Path(p) if p.qself.is_none()) => {
+            Ok(syn::parse_quote!(#ty))
+        }
+        v => Err(Error::new(
+            v.span(),
+            format!("Expected an identifier for {}, as in `{} = NAME`", key, key).as_str(),
+        )),
+    }
+}
+
+/// Returns the integer passed into the parameter with the given key, either as
+/// a literal or in string literal form
+fn integer<T>(key: &Ident, value: &Value) -> Result<T, Error>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let parsed = match value {
+        Value::Int(lit) => lit.base10_parse().ok(),
+        Value::Str(lit) => lit.value().parse().ok(),
+        _ => None,
+    };
+    parsed.ok_or_else(|| {
+        Error::new(
+            value.span(),
+            format!("Expected an integer for {}, as in `{} = 3`", key, key).as_str(),
+        )
+    })
+}
+
+/// Returns the boolean passed into the parameter with the given key, either as
+/// a literal or in string literal form
+fn boolean(key: &Ident, value: &Value) -> Result<bool, Error> {
+    match value {
+        Value::Bool(lit) => Ok(lit.value),
+        Value::Str(lit) if lit.value() == "true" => Ok(true),
+        Value::Str(lit) if lit.value() == "false" => Ok(false),
+        v => Err(Error::new(
+            v.span(),
+            format!("Expected a boolean for {}, as in `{} = true`", key, key).as_str(),
+        )),
+    }
+}
+
 /// Parses a [LitStr] into `T` and returns an error if it fails
 fn parse<T: syn::parse::Parse>(value: &LitStr) -> Result<T, Error> {
     value
@@ -3,11 +3,19 @@ use std::{
     convert::TryFrom,
 };
 
+use proc_macro2::{Group, Literal, Span, TokenStream, TokenTree};
 use syn::{
-    spanned::Spanned, Attribute, Field, Ident, LitStr, Meta, MetaNameValue, NestedMeta, Type,
+    bracketed,
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    token, Attribute, Expr, Field, Ident, Lit, LitBool, LitInt, LitStr, Member, Meta, NestedMeta,
+    Token, Type,
 };
 
-use crate::{EndpointAttribute, Error};
+use crate::{EndpointAttribute, Error, Receiver};
 
 /// Returns all [Meta] values contained in a [Meta::List].
 ///
@@ -41,55 +49,171 @@ pub(crate) fn attr_list(attr: &Meta) -> Result<Vec<Meta>, Error> {
     }
 }
 
-/// Returns all [MetaNameValue] values contained in a [Meta::List].
-///
-/// For example:
-/// ```
-/// #[endpoint(path = "my/path", method = "POST")]
-/// ```
-/// Would return individual [MetaNameValue] values for `path` and `method`. This
-/// function fails if the [Meta::List] is empty, contains literals, or cannot
-/// be parsed as name/value pairs.
-pub(crate) fn attr_kv(attr: &Meta) -> Result<Vec<MetaNameValue>, Error> {
-    let meta_list = attr_list(attr)?;
-    let mut result = Vec::<MetaNameValue>::new();
-    for meta in meta_list.iter() {
-        if let syn::Meta::NameValue(nv_meta) = meta {
-            result.push(nv_meta.clone());
-        } else {
-            return Err(Error::new(
-                attr.span(),
-                "Cannot parse attribute as a key/value list",
+/// The value of a parameter passed into an `endpoint` attribute attached to a
+/// struct, enum or enum variant.
+pub(crate) enum Value {
+    /// A string literal, as in `path = "my/path"`.
+    Str(LitStr),
+    /// A boolean, as in `builder = true`.
+    Bool(LitBool),
+    /// An integer literal, which is parsed by the parameter accepting it.
+    Int(LitInt),
+    /// An identifier, path or type, as in `method = POST` or
+    /// `response = Vec<User>`.
+    Type(Box<Type>),
+    /// A list of values, as in `headers("Accept: text/plain")` or
+    /// `headers = ["Accept: text/plain"]`.
+    List(Span, Vec<Value>),
+    /// A list of nested parameters, as in `query(style = comma)`.
+    Params(Span, HashMap<Ident, Value>),
+    /// A list of types mapped to status codes, as in `responses(200 = User)`.
+    Responses(Vec<StatusType>),
+}
+
+impl Value {
+    /// Returns the [Span] of the value.
+    pub fn span(&self) -> Span {
+        match self {
+            Value::Str(lit) => lit.span(),
+            Value::Bool(lit) => lit.span(),
+            Value::Int(lit) => lit.span(),
+            Value::Type(ty) => ty.span(),
+            Value::List(span, _) | Value::Params(span, _) => *span,
+            Value::Responses(types) => types.first().map_or_else(Span::call_site, |t| t.span),
+        }
+    }
+}
+
+impl Parse for Value {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(token::Bracket) {
+            let content;
+            let bracket = bracketed!(content in input);
+            let values = content.parse_terminated::<Value, Token![,]>(Value::parse)?;
+            return Ok(Value::List(bracket.span, values.into_iter().collect()));
+        }
+        if input.peek(Lit) {
+            return match input.parse()? {
+                Lit::Str(lit) => Ok(Value::Str(lit)),
+                Lit::Bool(lit) => Ok(Value::Bool(lit)),
+                Lit::Int(lit) => Ok(Value::Int(lit)),
+                lit => Err(syn::Error::new(
+                    lit.span(),
+                    "Values must be strings, booleans, integers, paths or lists",
+                )),
+            };
+        }
+
+        input
+            .parse()
+            .map(|ty| Value::Type(Box::new(ty)))
+            .map_err(|e| {
+                syn::Error::new(
+                    e.span(),
+                    "Values must be strings, booleans, integers, paths or lists",
+                )
+            })
+    }
+}
+
+/// A type mapped to a status code, as in `200 = User`.
+pub(crate) struct StatusType {
+    /// The status code, or [None] for the `default` type which is used for
+    /// all other status codes.
+    pub status: Option<u16>,
+    /// The span of the status code.
+    pub span: Span,
+    /// The type, either as a path or a string literal.
+    pub ty: Value,
+}
+
+/// A single parameter passed into an `endpoint` attribute.
+struct Param(Ident, Value);
+
+impl Parse for Param {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let id: Ident = input.parse()?;
+        if input.peek(token::Paren) {
+            let content;
+            let paren = parenthesized!(content in input);
+            if id == "responses" {
+                let types = content.parse_terminated::<StatusType, Token![,]>(StatusType::parse)?;
+                return Ok(Param(id, Value::Responses(types.into_iter().collect())));
+            }
+            if content.peek(Ident) && content.peek2(Token![=]) {
+                let params = content.parse_terminated::<Param, Token![,]>(Param::parse)?;
+                let params = params.into_iter().map(|Param(id, v)| (id, v)).collect();
+                return Ok(Param(id, Value::Params(paren.span, params)));
+            }
+            let values = content.parse_terminated::<Value, Token![,]>(Value::parse)?;
+            return Ok(Param(
+                id,
+                Value::List(paren.span, values.into_iter().collect()),
             ));
         }
+
+        input.parse::<Token![=]>()?;
+        Ok(Param(id, input.parse()?))
+    }
+}
+
+impl Parse for StatusType {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let (status, span) = if input.peek(LitInt) {
+            let lit: LitInt = input.parse()?;
+            match lit.base10_parse::<u16>() {
+                Ok(s) if (100..=599).contains(&s) => (Some(s), lit.span()),
+                _ => {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        "Status codes must be between 100 and 599",
+                    ))
+                }
+            }
+        } else {
+            let id: Ident = input.parse()?;
+            if id != "default" {
+                return Err(syn::Error::new(
+                    id.span(),
+                    "Expected a status code or `default`",
+                ));
+            }
+            (None, id.span())
+        };
+        input.parse::<Token![=]>()?;
+
+        Ok(StatusType {
+            status,
+            span,
+            ty: input.parse()?,
+        })
     }
-    Ok(result)
 }
 
-/// Converts a list of [MetaNameValue] values into a [HashMap].
+/// Returns the parameters passed into an `endpoint` attribute attached to a
+/// struct, enum or enum variant.
 ///
-/// For example, assuming the below has been parsed into [MetaNameValue]'s:
+/// For example:
 /// ```
 /// #[endpoint(path = "my/path", method = "POST")]
 /// ```
 /// Would return a [HashMap] mapping individual ID's (i.e. `path` and `method`)
-/// to their [LitStr] values (i.e. "m/path" and "POST"). This function fails if
-/// the values cannot be parsed as string literals.
-pub(crate) fn to_map(values: &[MetaNameValue]) -> Result<HashMap<Ident, LitStr>, Error> {
-    let mut map = HashMap::<Ident, LitStr>::new();
-    for value in values.iter() {
-        let id = value.path.get_ident().unwrap().clone();
-        if let syn::Lit::Str(lit) = &value.lit {
-            map.insert(id, lit.clone());
-        } else {
-            return Err(Error::new(
-                value.span(),
-                "Values must be in string literal form",
-            ));
-        }
+/// to their values (i.e. "my/path" and "POST"). This function fails if the
+/// attribute is empty or any of its values cannot be parsed.
+pub(crate) fn params(attr: &Attribute) -> Result<HashMap<Ident, Value>, Error> {
+    if attr.tokens.is_empty() {
+        return Err(Error::new(
+            attr.path.span(),
+            "Cannot parse attribute as list",
+        ));
     }
 
-    Ok(map)
+    let params = attr.parse_args_with(Punctuated::<Param, Token![,]>::parse_terminated)?;
+    if params.is_empty() {
+        return Err(Error::new(attr.path.span(), "Attribute cannot be empty"));
+    }
+
+    Ok(params.into_iter().map(|Param(id, v)| (id, v)).collect())
 }
 
 /// Searches a list of [Attribute]'s and returns any matching
@@ -97,76 +221,444 @@ pub(crate) fn to_map(values: &[MetaNameValue]) -> Result<HashMap<Ident, LitStr>,
 pub(crate) fn attributes(attrs: &[Attribute], name: &str) -> Result<Vec<Meta>, Error> {
     let mut result = Vec::<Meta>::new();
     for attr in attrs.iter() {
-        let meta = attr.parse_meta().map_err(Error::from)?;
-        match meta.path().is_ident(name) {
-            true => {
-                result.push(meta);
-            }
-            false => {}
+        let meta = if attr.path.is_ident(crate::ATTR_NAME) {
+            let mut attr = attr.clone();
+            attr.tokens = quote_styles(attr.tokens);
+            attr.parse_meta()
+        } else {
+            attr.parse_meta()
+        };
+        let meta = meta.map_err(Error::from)?;
+        if meta.path().is_ident(name) {
+            result.push(meta);
         }
     }
 
     Ok(result)
 }
 
+/// Replaces identifiers given to a `style` option with string literals, so that
+/// `query(style = comma)` can be parsed as a [Meta] in the same way as
+/// `query(style = "comma")`.
+fn quote_styles(tokens: TokenStream) -> TokenStream {
+    let mut result: Vec<TokenTree> = Vec::new();
+    for token in tokens {
+        let styled = match result.as_slice() {
+            [.., TokenTree::Ident(key), TokenTree::Punct(eq)] => {
+                key == "style" && eq.as_char() == '='
+            }
+            _ => false,
+        };
+        result.push(match token {
+            TokenTree::Ident(id) if styled => {
+                let mut lit = Literal::string(&id.to_string());
+                lit.set_span(id.span());
+                TokenTree::Literal(lit)
+            }
+            TokenTree::Group(g) => {
+                let mut group = Group::new(g.delimiter(), quote_styles(g.stream()));
+                group.set_span(g.span());
+                TokenTree::Group(group)
+            }
+            t => t,
+        });
+    }
+
+    result.into_iter().collect()
+}
+
+/// Returns `true` if any of the given `serde` attributes contain a parameter
+/// with the given name.
+pub(crate) fn has_serde_param(attrs: &[Meta], name: &str) -> bool {
+    attrs.iter().any(|attr| match attr {
+        Meta::List(list) => list.nested.iter().any(|nested| match nested {
+            NestedMeta::Meta(m) => m.path().is_ident(name),
+            _ => false,
+        }),
+        _ => false,
+    })
+}
+
+/// Returns the name a field is serialized with, which is its name unless it's
+/// renamed with `#[serde(rename = "...")]`.
+pub(crate) fn serde_name(field: &Field) -> String {
+    serde_rename(&field.attrs).unwrap_or_else(|| field.ident.as_ref().unwrap().unraw().to_string())
+}
+
+/// Returns the name given by `#[serde(rename = "...")]` in the given
+/// attributes, if any.
+pub(crate) fn serde_rename(attrs: &[Attribute]) -> Option<String> {
+    attributes(attrs, "serde")
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|m| match m {
+            Meta::List(list) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .find_map(|nested| match nested {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("rename") => match nv.lit {
+                Lit::Str(lit) => Some(lit.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
 /// Returns a mapping of endpoint attributes to a list of their fields.
 ///
 /// Parses all [Attribute]'s on the given [syn::Field]'s, searching for any
 /// attributes which match [crate::ATTR_NAME] and creating a map of attributes
-/// to a list of their associated fields.
+/// to a list of their associated fields. The fields are those of a struct or of
+/// a single enum variant.
 pub(crate) fn field_attributes(
-    data: &syn::Data,
+    fields: &syn::Fields,
 ) -> Result<HashMap<EndpointAttribute, Vec<Field>>, Error> {
     let mut result = HashMap::<EndpointAttribute, Vec<Field>>::new();
-    if let syn::Data::Struct(data) = data {
-        for field in data.fields.iter() {
-            // Collect all `endpoint` attributes attached to this field
-            let attrs = attributes(&field.attrs, crate::ATTR_NAME)?;
-
-            // Add field as untagged is no attributes were found
-            if attrs.is_empty() {
-                match result.get_mut(&EndpointAttribute::Untagged) {
-                    Some(r) => {
-                        r.push(field.clone());
-                    }
-                    None => {
-                        result.insert(EndpointAttribute::Untagged, vec![field.clone()]);
+    for field in fields.iter() {
+        // Collect all `endpoint` attributes attached to this field
+        let attrs = attributes(&field.attrs, crate::ATTR_NAME)?;
+
+        // Add field as untagged is no attributes were found
+        if attrs.is_empty() {
+            match result.get_mut(&EndpointAttribute::Untagged) {
+                Some(r) => {
+                    r.push(field.clone());
+                }
+                None => {
+                    result.insert(EndpointAttribute::Untagged, vec![field.clone()]);
+                }
+            }
+        }
+
+        // Combine all meta parameters from each attribute
+        let attrs = attrs
+            .iter()
+            .map(attr_list)
+            .collect::<Result<Vec<Vec<Meta>>, Error>>()?;
+
+        // Flatten and eliminate duplicates
+        let attrs = attrs.into_iter().flatten().collect::<HashSet<Meta>>();
+
+        // Add this field to the list of fields for each attribute
+        for attr in attrs.iter() {
+            let attr_ty = EndpointAttribute::try_from(attr)?;
+            match result.get_mut(&attr_ty) {
+                Some(r) => {
+                    r.push(field.clone());
+                }
+                None => {
+                    result.insert(attr_ty, vec![field.clone()]);
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Returns the name of the header for a field with the `header` attribute.
+///
+/// The name is taken from the value of the attribute, as in
+/// `#[endpoint(header = "X-Name")]`, or otherwise derived from the name of the
+/// field by replacing underscores with dashes. Names are validated and
+/// converted to lowercase.
+pub(crate) fn header_name(field: &Field) -> Result<LitStr, Error> {
+    let mut name = None;
+    for attr in attributes(&field.attrs, crate::ATTR_NAME)?.iter() {
+        for meta in attr_list(attr)? {
+            if let Meta::NameValue(nv) = meta {
+                match &nv.lit {
+                    syn::Lit::Str(lit) if nv.path.is_ident("header") => name = Some(lit.clone()),
+                    _ => {
+                        return Err(Error::new(
+                            nv.lit.span(),
+                            "Header names must be in string literal form",
+                        ))
                     }
                 }
             }
+        }
+    }
+    let name = name.unwrap_or_else(|| {
+        let id = field.ident.clone().unwrap();
+        LitStr::new(&id.unraw().to_string().replace('_', "-"), id.span())
+    });
 
-            // Combine all meta parameters from each attribute
-            let attrs = attrs
-                .iter()
-                .map(attr_list)
-                .collect::<Result<Vec<Vec<Meta>>, Error>>()?;
+    // Header names must be tokens as defined by RFC 7230
+    let valid = !name.value().is_empty()
+        && name
+            .value()
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c));
+    if !valid {
+        return Err(Error::new(
+            name.span(),
+            format!("Invalid header name: {}", name.value()).as_str(),
+        ));
+    }
 
-            // Flatten and eliminate duplicates
-            let attrs = attrs.into_iter().flatten().collect::<HashSet<Meta>>();
+    Ok(LitStr::new(&name.value().to_lowercase(), name.span()))
+}
 
-            // Add this field to the list of fields for each attribute
-            for attr in attrs.iter() {
-                let attr_ty = EndpointAttribute::try_from(attr)?;
-                match result.get_mut(&attr_ty) {
-                    Some(r) => {
-                        r.push(field.clone());
+/// Returns `true` if a field with the `path` attribute may contain slashes, as
+/// in `#[endpoint(path(slashes))]`.
+pub(crate) fn path_slashes(field: &Field) -> Result<bool, Error> {
+    let mut slashes = false;
+    for attr in attributes(&field.attrs, crate::ATTR_NAME)?.iter() {
+        for meta in attr_list(attr)? {
+            if let Meta::List(_) = meta {
+                if !meta.path().is_ident("path") {
+                    continue;
+                }
+                for option in attr_list(&meta)? {
+                    match option {
+                        Meta::Path(p) if p.is_ident("slashes") => slashes = true,
+                        _ => {
+                            return Err(Error::new(
+                                option.span(),
+                                "Unknown path option, expected `slashes`",
+                            ))
+                        }
                     }
+                }
+            }
+        }
+    }
+
+    Ok(slashes)
+}
+
+/// Returns the query style of a field with the `query` attribute, if it has
+/// one, as in `#[endpoint(query(style = comma))]`. The style can also be given
+/// as a string, as in `#[endpoint(query(style = "comma"))]`.
+pub(crate) fn field_query_style(field: &Field) -> Result<Option<Ident>, Error> {
+    let mut style = None;
+    for attr in attributes(&field.attrs, crate::ATTR_NAME)?.iter() {
+        for meta in attr_list(attr)? {
+            if !matches!(meta, Meta::List(_)) || !meta.path().is_ident("query") {
+                continue;
+            }
+            for option in attr_list(&meta)? {
+                match option {
+                    Meta::NameValue(nv) if nv.path.is_ident("style") => match &nv.lit {
+                        Lit::Str(lit) => style = Some(query_style(&lit.value(), lit.span())?),
+                        lit => {
+                            return Err(Error::new(
+                                lit.span(),
+                                "Expected a query style, as in `comma`",
+                            ))
+                        }
+                    },
+                    _ => {
+                        return Err(Error::new(
+                            option.span(),
+                            "Unknown query option, expected `style`",
+                        ))
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(style)
+}
+
+/// Returns the variant of `QueryStyle` named by the given query style.
+pub(crate) fn query_style(name: &str, span: Span) -> Result<Ident, Error> {
+    let variant = match name {
+        "repeat" => "Repeat",
+        "comma" => "Comma",
+        "brackets" => "Brackets",
+        "deepObject" => "DeepObject",
+        _ => {
+            return Err(Error::new(
+                span,
+                "Unknown query style, expected `repeat`, `comma`, `brackets` or `deepObject`",
+            ))
+        }
+    };
+
+    Ok(Ident::new(variant, span))
+}
+
+/// A placeholder in a path template.
+pub(crate) struct Placeholder {
+    /// The text between the braces.
+    pub content: String,
+    /// The span of the placeholder within the path literal.
+    pub span: Span,
+}
+
+/// Splits a path template into a `format!` string and its placeholders.
+///
+/// For example:
+/// ```ignore
+/// "user/{self.name}/{{literal}}"
+/// ```
+/// Would return `"user/{}/{{literal}}"` along with a single placeholder
+/// containing `self.name`. This function fails if the template contains
+/// unbalanced or empty braces.
+pub(crate) fn path_template(path: &LitStr) -> Result<(String, Vec<Placeholder>), Error> {
+    let value = path.value();
+    let mut template = String::new();
+    let mut placeholders = Vec::new();
+    let mut chars = value.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' if chars.peek().map(|(_, c)| *c) == Some('{') => {
+                chars.next();
+                template.push_str("{{");
+            }
+            '{' => {
+                let end = value[i + 1..]
+                    .find(['{', '}'])
+                    .map(|j| i + 1 + j)
+                    .filter(|j| value[*j..].starts_with('}'));
+                let end = match end {
+                    Some(e) => e,
                     None => {
-                        result.insert(attr_ty, vec![field.clone()]);
+                        return Err(Error::new(
+                            lit_span(path, i, i + 1),
+                            "Unclosed `{` in path, use `{{` for a literal brace",
+                        ))
                     }
+                };
+                let content = &value[i + 1..end];
+                if content.trim().is_empty() {
+                    return Err(Error::new(
+                        lit_span(path, i, end + 1),
+                        "Empty placeholder in path",
+                    ));
+                }
+                placeholders.push(Placeholder {
+                    content: content.to_string(),
+                    span: lit_span(path, i, end + 1),
+                });
+                template.push_str("{}");
+                while chars.peek().is_some_and(|(j, _)| *j <= end) {
+                    chars.next();
                 }
             }
+            '}' if chars.peek().map(|(_, c)| *c) == Some('}') => {
+                chars.next();
+                template.push_str("}}");
+            }
+            '}' => {
+                return Err(Error::new(
+                    lit_span(path, i, i + 1),
+                    "Unmatched `}` in path, use `}}` for a literal brace",
+                ))
+            }
+            c => template.push(c),
         }
     }
 
-    Ok(result)
+    Ok((template, placeholders))
+}
+
+/// Returns the span of the given byte range of the value of a string literal.
+///
+/// Falls back to the span of the whole literal when the compiler doesn't
+/// support spans within literals or the literal contains escape sequences.
+fn lit_span(lit: &LitStr, start: usize, end: usize) -> Span {
+    let token = lit.token();
+    let repr = token.to_string();
+    let value = lit.value();
+    let offset = match repr.find('"') {
+        Some(o) => o + 1,
+        None => return lit.span(),
+    };
+    if repr.get(offset..offset + value.len()) != Some(value.as_str()) {
+        return lit.span();
+    }
+    token
+        .subspan(offset + start..offset + end)
+        .unwrap_or_else(|| lit.span())
+}
+
+/// Sets the span of all tokens in the given [TokenStream] to the given span.
+pub(crate) fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Group(g) => {
+                let mut group = Group::new(g.delimiter(), respan(g.stream(), span));
+                group.set_span(span);
+                TokenTree::Group(group)
+            }
+            mut t => {
+                t.set_span(span);
+                t
+            }
+        })
+        .collect()
+}
+
+/// Checks that every `self.field` accessed by the given path expression is one
+/// of the given field names and adds it to `used`.
+///
+/// Fields of an enum variant are bound by reference, so when the receiver is
+/// [Receiver::Variant] every `self.field` is replaced with `(*field)`.
+///
+/// Method calls can't be checked here and are left for the compiler, which
+/// reports errors at the spans of the expression.
+pub(crate) fn path_fields(
+    expr: &mut Expr,
+    names: &HashSet<String>,
+    used: &mut HashSet<String>,
+    receiver: Receiver,
+) -> Result<(), Error> {
+    match expr {
+        Expr::Field(f) => {
+            let id = match (&*f.base, &f.member) {
+                (Expr::Path(base), Member::Named(id)) if base.path.is_ident("self") => id.clone(),
+                _ => return path_fields(&mut f.base, names, used, receiver),
+            };
+            let name = id.unraw().to_string();
+            if !names.contains(&name) {
+                return Err(Error::new(
+                    id.span(),
+                    format!("Unknown field in path: {}", name).as_str(),
+                ));
+            }
+            used.insert(name);
+            if let Receiver::Variant = receiver {
+                *expr = syn::parse_quote_spanned! { id.span() => (*#id) };
+            }
+            Ok(())
+        }
+        Expr::MethodCall(m) => {
+            path_fields(&mut m.receiver, names, used, receiver)?;
+            m.args
+                .iter_mut()
+                .try_for_each(|a| path_fields(a, names, used, receiver))
+        }
+        Expr::Call(c) => c
+            .args
+            .iter_mut()
+            .try_for_each(|a| path_fields(a, names, used, receiver)),
+        Expr::Binary(b) => {
+            path_fields(&mut b.left, names, used, receiver)?;
+            path_fields(&mut b.right, names, used, receiver)
+        }
+        Expr::Index(i) => {
+            path_fields(&mut i.expr, names, used, receiver)?;
+            path_fields(&mut i.index, names, used, receiver)
+        }
+        Expr::Paren(p) => path_fields(&mut p.expr, names, used, receiver),
+        Expr::Reference(r) => path_fields(&mut r.expr, names, used, receiver),
+        Expr::Unary(u) => path_fields(&mut u.expr, names, used, receiver),
+        _ => Ok(()),
+    }
 }
 
 /// Creates and instantiates a struct from a list of [Field]s.
 ///
 /// This function effectively creates a new struct from a list [Field]s and then
-/// instantiates it using the same field names from the parent struct. It's
-/// intended to be used to "split" a struct into smaller structs.
+/// instantiates it using the same field names from the parent struct or enum
+/// variant, accessed through the given [Receiver]. It's intended to be used to
+/// "split" a struct into smaller structs.
 ///
 /// The new struct will automatically derive `Serialize` and any [Option] fields
 /// will automatically be excluded from serialization if their value is
@@ -175,7 +667,11 @@ pub(crate) fn field_attributes(
 /// The result is a [proc_macro2::TokenStream] that contains the new struct and
 /// and it's instantiation. The instantiated variable can be accessed by it's
 /// static name of `__temp`.
-pub(crate) fn fields_to_struct(fields: &[Field], attrs: &[Meta]) -> proc_macro2::TokenStream {
+pub(crate) fn fields_to_struct(
+    fields: &[Field],
+    attrs: &[Meta],
+    receiver: Receiver,
+) -> proc_macro2::TokenStream {
     // Construct struct field definitions
     let def = fields
         .iter()
@@ -218,7 +714,8 @@ pub(crate) fn fields_to_struct(fields: &[Field], attrs: &[Meta]) -> proc_macro2:
         .iter()
         .map(|f| {
             let id = f.ident.clone().unwrap();
-            quote! { #id: &self.#id, }
+            let field = receiver.field(&id);
+            quote! { #id: #field, }
         })
         .collect::<Vec<proc_macro2::TokenStream>>();
 
@@ -250,3 +747,20 @@ pub(crate) fn is_std_option(ty: &Type) -> bool {
         false
     }
 }
+
+/// Returns `T` if the type refers to a `Vec<T>`
+pub(crate) fn vec_item(ty: &Type) -> Option<&Type> {
+    let segment = match ty {
+        Type::Path(tp) if tp.qself.is_none() => tp.path.segments.last()?,
+        _ => return None,
+    };
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if segment.ident == "Vec" => {
+            match args.args.first() {
+                Some(syn::GenericArgument::Type(item)) if args.args.len() == 1 => Some(item),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -1,8 +1,14 @@
 //! Contains the blocking [Client] trait for executing
 //! [Endpoints][crate::endpoint::Endpoint].
+use std::io::Read;
+
 use http::{Request, Response};
 
-use crate::{client::HTTP_SUCCESS_CODES, errors::ClientError};
+use crate::{
+    blocking::upload::Upload,
+    client::{check_response, AcceptedStatus},
+    errors::ClientError,
+};
 
 /// Represents an HTTP client which is capable of executing
 /// [Endpoints][crate::endpoint::Endpoint] by sending the [Request] generated
@@ -27,6 +33,7 @@ pub trait Client {
             req.uri(),
             req.body().len(),
         );
+        let accepted = AcceptedStatus::of(&req);
         let response = self.send(req)?;
 
         debug!(
@@ -35,15 +42,50 @@ pub trait Client {
             response.body().len()
         );
 
-        // Check response
-        if !HTTP_SUCCESS_CODES.contains(&response.status().as_u16()) {
-            return Err(ClientError::ServerResponseError {
-                code: response.status().as_u16(),
-                content: String::from_utf8(response.body().to_vec()).ok(),
-            });
-        }
+        check_response(response, accepted)
+    }
+
+    /// Sends the given [Request] with the given [Upload] as its body and
+    /// returns a [Response].
+    ///
+    /// The default implementation reads the whole upload into memory and
+    /// sends it using [Client::send]. Implementations should override it when
+    /// the underlying client supports streaming request bodies.
+    fn send_upload(
+        &self,
+        mut req: Request<Vec<u8>>,
+        body: Upload,
+    ) -> Result<Response<Vec<u8>>, ClientError> {
+        let (_, mut reader) = body.open()?;
+        reader
+            .read_to_end(req.body_mut())
+            .map_err(|e| ClientError::UploadError { source: e.into() })?;
+        self.send(req)
+    }
+
+    /// This method provides a common interface to
+    /// [Endpoints][crate::endpoint::Endpoint] for execution where the request
+    /// body is streamed from an [Upload].
+    #[instrument(skip(self, req, body), err)]
+    fn execute_upload(
+        &self,
+        req: Request<Vec<u8>>,
+        body: Upload,
+    ) -> Result<Response<Vec<u8>>, ClientError> {
+        debug!(
+            "Client sending {} request to {} with a streamed body",
+            req.method().to_string(),
+            req.uri(),
+        );
+        let accepted = AcceptedStatus::of(&req);
+        let response = self.send_upload(req, body)?;
+
+        debug!(
+            "Client received {} response with {} bytes of body data",
+            response.status().as_u16(),
+            response.body().len()
+        );
 
-        // Parse response content
-        Ok(response)
+        check_response(response, accepted)
     }
 }
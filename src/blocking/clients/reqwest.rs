@@ -6,7 +6,10 @@ use std::convert::TryFrom;
 
 use http::{Request, Response};
 
-use crate::{blocking::client::Client as RustifyClient, errors::ClientError};
+use crate::{
+    blocking::{client::Client as RustifyClient, upload::Upload},
+    errors::ClientError,
+};
 
 /// A client based on the
 /// [reqwest::blocking::Client][1] which can be used for executing
@@ -58,16 +61,17 @@ impl Client {
     }
 }
 
-impl RustifyClient for Client {
-    fn base(&self) -> &str {
-        self.base.as_str()
-    }
-
-    #[instrument(skip(self, req), err)]
-    fn send(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, ClientError> {
-        let request = reqwest::blocking::Request::try_from(req)
-            .map_err(|e| ClientError::ReqwestBuildError { source: e })?;
-
+impl Client {
+    /// Executes the given [reqwest::blocking::Request][1] using the backing
+    /// [reqwest::blocking::Client][2] and converts the result into a
+    /// [Response].
+    ///
+    /// [1]: https://docs.rs/reqwest/latest/reqwest/blocking/struct.Request.html
+    /// [2]: https://docs.rs/reqwest/latest/reqwest/blocking/struct.Client.html
+    fn execute_request(
+        &self,
+        request: reqwest::blocking::Request,
+    ) -> Result<Response<Vec<u8>>, ClientError> {
         let url_err = request.url().to_string();
         let method_err = request.method().to_string();
         let response = self
@@ -95,3 +99,32 @@ impl RustifyClient for Client {
             .map_err(|e| ClientError::ResponseError { source: e.into() })
     }
 }
+
+impl RustifyClient for Client {
+    fn base(&self) -> &str {
+        self.base.as_str()
+    }
+
+    #[instrument(skip(self, req), err)]
+    fn send(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, ClientError> {
+        let request = reqwest::blocking::Request::try_from(req)
+            .map_err(|e| ClientError::ReqwestBuildError { source: e })?;
+        self.execute_request(request)
+    }
+
+    #[instrument(skip(self, req, body), err)]
+    fn send_upload(
+        &self,
+        req: Request<Vec<u8>>,
+        body: Upload,
+    ) -> Result<Response<Vec<u8>>, ClientError> {
+        let mut request = reqwest::blocking::Request::try_from(req)
+            .map_err(|e| ClientError::ReqwestBuildError { source: e })?;
+        let (length, reader) = body.open()?;
+        *request.body_mut() = Some(match length {
+            Some(l) => reqwest::blocking::Body::sized(reader, l),
+            None => reqwest::blocking::Body::new(reader),
+        });
+        self.execute_request(request)
+    }
+}
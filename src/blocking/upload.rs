@@ -0,0 +1,109 @@
+//! Contains the blocking [Upload] type for sending request bodies without
+//! buffering them in memory.
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::PathBuf,
+};
+
+use http::Request;
+
+use crate::errors::ClientError;
+
+/// A request body which is streamed to the server instead of being held in
+/// memory, used with
+/// [Endpoint::exec_upload_block][crate::Endpoint::exec_upload_block].
+///
+/// This is the blocking counterpart of [crate::upload::Upload] and behaves the
+/// same way: uploads with a known length are sent with a `Content-Length`
+/// header while all others use chunked transfer encoding.
+///
+/// # Example
+/// ```
+/// use rustified::{blocking::{clients::reqwest::Client, upload::Upload}, Endpoint};
+/// use rustified_derive::Endpoint;
+///
+/// #[derive(Endpoint)]
+/// #[endpoint(path = "artifacts/{self.name}", method = "PUT")]
+/// struct PutArtifact {
+///     #[endpoint(skip)]
+///     name: String,
+/// }
+///
+/// let client = Client::default("http://myapi.com");
+/// let endpoint = PutArtifact {
+///     name: "build.tar".to_string(),
+/// };
+/// let result = endpoint.exec_upload_block(&client, Upload::file("target/build.tar"));
+/// ```
+pub struct Upload {
+    source: Source,
+    length: Option<u64>,
+    content_type: Option<String>,
+}
+
+enum Source {
+    File(PathBuf),
+    Reader(Box<dyn Read + Send>),
+}
+
+impl Upload {
+    /// Returns a new [Upload] which reads the file at the given path. The file
+    /// is only opened when the upload is sent.
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Upload::new(Source::File(path.into()))
+    }
+
+    /// Returns a new [Upload] which reads from the given [Read].
+    pub fn reader(reader: impl Read + Send + 'static) -> Self {
+        Upload::new(Source::Reader(Box::new(reader)))
+    }
+
+    /// Sets the length of the upload, which is sent as the `Content-Length`
+    /// header. The source must produce exactly this many bytes.
+    pub fn length(mut self, length: u64) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    /// Sets the content type of the upload.
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// Sets the `Content-Type` header of the given [Request] if a content type
+    /// was given for this upload.
+    pub fn apply(&self, req: &mut Request<Vec<u8>>) -> Result<(), ClientError> {
+        crate::upload::set_content_type(req, self.content_type.as_deref())
+    }
+
+    /// Opens the upload and returns its length, if known, along with the
+    /// reader to send.
+    pub fn open(self) -> Result<(Option<u64>, Box<dyn Read + Send>), ClientError> {
+        match self.source {
+            Source::File(path) => {
+                let file = File::open(&path).map_err(upload_error)?;
+                let length = match self.length {
+                    Some(l) => l,
+                    None => file.metadata().map_err(upload_error)?.len(),
+                };
+                Ok((Some(length), Box::new(file)))
+            }
+            Source::Reader(reader) => Ok((self.length, reader)),
+        }
+    }
+
+    fn new(source: Source) -> Self {
+        Upload {
+            source,
+            length: None,
+            content_type: None,
+        }
+    }
+}
+
+fn upload_error(e: io::Error) -> ClientError {
+    ClientError::UploadError { source: e.into() }
+}
@@ -1,15 +1,50 @@
 //! Contains the [Client] trait for executing
 //! [Endpoints][crate::endpoint::Endpoint].
-use std::ops::RangeInclusive;
+use std::{ops::RangeInclusive, pin::Pin};
 
 use async_trait::async_trait;
+use bytes::Bytes;
+use futures_core::Stream;
+use futures_util::{stream, TryStreamExt};
 use http::{Request, Response};
 
-use crate::errors::ClientError;
+use crate::{errors::ClientError, upload::Upload};
 
 /// An array of HTTP response codes which indicate a successful response
 pub const HTTP_SUCCESS_CODES: RangeInclusive<u16> = 200..=208;
 
+/// A [Request] extension which determines the status codes treated as
+/// successful when the request is executed.
+///
+/// [Endpoints][crate::endpoint::Endpoint] insert it into their requests using
+/// [FromResponse::accepts][crate::endpoint::FromResponse::accepts] of their
+/// response type. Responses with any other status code are returned as a
+/// [ClientError::ServerResponseError]. Requests without it only accept
+/// [HTTP_SUCCESS_CODES].
+#[derive(Clone, Copy, Debug)]
+pub struct AcceptedStatus(pub fn(u16) -> bool);
+
+impl AcceptedStatus {
+    /// Returns the [AcceptedStatus] of the given [Request].
+    pub fn of<B>(req: &Request<B>) -> Self {
+        req.extensions().get::<Self>().copied().unwrap_or_default()
+    }
+
+    /// Returns `true` if the given status code is accepted.
+    pub fn contains(&self, status: u16) -> bool {
+        (self.0)(status)
+    }
+}
+
+impl Default for AcceptedStatus {
+    fn default() -> Self {
+        AcceptedStatus(|status| HTTP_SUCCESS_CODES.contains(&status))
+    }
+}
+
+/// A response body which is received as a [Stream] of chunks.
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes, ClientError>> + Send>>;
+
 /// Represents an HTTP client which is capable of executing
 /// [Endpoints][crate::endpoint::Endpoint] by sending the [Request] generated
 /// by the Endpoint and returning a [Response].
@@ -19,6 +54,21 @@ pub trait Client: Sync + Send {
     /// should consolidate all errors into the [ClientError] type.
     async fn send(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, ClientError>;
 
+    /// Sends the given [Request] and returns a [Response] whose body is
+    /// received as a [ByteStream].
+    ///
+    /// The default implementation buffers the whole body using
+    /// [Client::send] and returns it as a single chunk. Implementations should
+    /// override it when the underlying client supports streaming.
+    async fn send_stream(
+        &self,
+        req: Request<Vec<u8>>,
+    ) -> Result<Response<ByteStream>, ClientError> {
+        let response = self.send(req).await?;
+        Ok(response
+            .map(|body| -> ByteStream { Box::pin(stream::iter(Some(Ok(Bytes::from(body))))) }))
+    }
+
     /// Returns the base URL the client is configured with. This is used for
     /// creating the fully qualified URLs used when executing
     /// [Endpoints][crate::endpoint::Endpoint].
@@ -34,6 +84,7 @@ pub trait Client: Sync + Send {
             req.uri(),
             req.body().len(),
         );
+        let accepted = AcceptedStatus::of(&req);
         let response = self.send(req).await?;
 
         debug!(
@@ -42,15 +93,125 @@ pub trait Client: Sync + Send {
             response.body().len()
         );
 
+        check_response(response, accepted)
+    }
+
+    /// Sends the given [Request] with the given [Upload] as its body and
+    /// returns a [Response].
+    ///
+    /// The default implementation reads the whole upload into memory and
+    /// sends it using [Client::send]. Implementations should override it when
+    /// the underlying client supports streaming request bodies.
+    async fn send_upload(
+        &self,
+        mut req: Request<Vec<u8>>,
+        body: Upload,
+    ) -> Result<Response<Vec<u8>>, ClientError> {
+        let (_, stream) = body.open().await?;
+        *req.body_mut() = stream
+            .try_fold(Vec::new(), |mut acc, chunk| async move {
+                acc.extend_from_slice(&chunk);
+                Ok(acc)
+            })
+            .await
+            .map_err(|e| ClientError::UploadError { source: e.into() })?;
+        self.send(req).await
+    }
+
+    /// This method provides a common interface to
+    /// [Endpoints][crate::endpoint::Endpoint] for execution where the request
+    /// body is streamed from an [Upload].
+    #[instrument(skip(self, req, body), err)]
+    async fn execute_upload(
+        &self,
+        req: Request<Vec<u8>>,
+        body: Upload,
+    ) -> Result<Response<Vec<u8>>, ClientError> {
+        debug!(
+            "Client sending {} request to {} with a streamed body",
+            req.method().to_string(),
+            req.uri(),
+        );
+        let accepted = AcceptedStatus::of(&req);
+        let response = self.send_upload(req, body).await?;
+
+        debug!(
+            "Client received {} response with {} bytes of body data",
+            response.status().as_u16(),
+            response.body().len()
+        );
+
+        check_response(response, accepted)
+    }
+
+    /// This method provides a common interface to
+    /// [Endpoints][crate::endpoint::Endpoint] for execution where the response
+    /// body is streamed.
+    ///
+    /// The status code is checked before any of the body is consumed. Only
+    /// unaccepted responses are read in full in order to populate the
+    /// returned [ClientError::ServerResponseError].
+    #[instrument(skip(self, req), err)]
+    async fn execute_stream(
+        &self,
+        req: Request<Vec<u8>>,
+    ) -> Result<Response<ByteStream>, ClientError> {
+        debug!(
+            "Client sending {} request to {} with {} bytes of data",
+            req.method().to_string(),
+            req.uri(),
+            req.body().len(),
+        );
+        let accepted = AcceptedStatus::of(&req);
+        let response = self.send_stream(req).await?;
+
+        debug!(
+            "Client received {} response with a streamed body",
+            response.status().as_u16(),
+        );
+
         // Check response
-        if !HTTP_SUCCESS_CODES.contains(&response.status().as_u16()) {
-            return Err(ClientError::ServerResponseError {
-                code: response.status().as_u16(),
-                content: String::from_utf8(response.body().to_vec()).ok(),
-            });
+        if !accepted.contains(response.status().as_u16()) {
+            return Err(response_error(&buffer(response).await));
         }
 
-        // Parse response content
         Ok(response)
     }
 }
+
+/// Reads the whole body of the given streamed [Response]. This is only used
+/// for unaccepted responses, so errors while reading the body are ignored and
+/// leave it empty.
+pub(crate) async fn buffer(response: Response<ByteStream>) -> Response<Vec<u8>> {
+    let (parts, body) = response.into_parts();
+    let body = body
+        .try_fold(Vec::new(), |mut acc, chunk| async move {
+            acc.extend_from_slice(&chunk);
+            Ok(acc)
+        })
+        .await
+        .unwrap_or_default();
+    Response::from_parts(parts, body)
+}
+
+/// Returns an error if the status code of the given [Response] is not
+/// accepted.
+pub(crate) fn check_response(
+    response: Response<Vec<u8>>,
+    accepted: AcceptedStatus,
+) -> Result<Response<Vec<u8>>, ClientError> {
+    if !accepted.contains(response.status().as_u16()) {
+        return Err(response_error(&response));
+    }
+
+    Ok(response)
+}
+
+/// Returns a [ClientError::ServerResponseError] for the given unsuccessful
+/// [Response].
+pub(crate) fn response_error(response: &Response<Vec<u8>>) -> ClientError {
+    ClientError::ServerResponseError {
+        code: response.status().as_u16(),
+        content: String::from_utf8(response.body().to_vec()).ok(),
+    }
+}
@@ -4,9 +4,14 @@
 use std::convert::TryFrom;
 
 use async_trait::async_trait;
-use http::{Request, Response};
+use futures_util::TryStreamExt;
+use http::{header::CONTENT_LENGTH, HeaderValue, Request, Response};
 
-use crate::{client::Client as RustifyClient, errors::ClientError};
+use crate::{
+    client::{ByteStream, Client as RustifyClient},
+    errors::ClientError,
+    upload::Upload,
+};
 
 /// A client based on the
 /// [reqwest::Client][1] which can be used for executing
@@ -61,34 +66,78 @@ impl Client {
     }
 }
 
-#[async_trait]
-impl RustifyClient for Client {
-    fn base(&self) -> &str {
-        self.base.as_str()
-    }
-
-    #[instrument(skip(self, req), err)]
-    async fn send(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, ClientError> {
+impl Client {
+    /// Executes the given [Request] using the backing [reqwest::Client][1].
+    ///
+    /// [1]: https://docs.rs/reqwest/latest/reqwest/struct.Client.html
+    async fn execute(&self, req: Request<Vec<u8>>) -> Result<reqwest::Response, ClientError> {
         let request = reqwest::Request::try_from(req)
             .map_err(|e| ClientError::ReqwestBuildError { source: e })?;
+        self.execute_request(request).await
+    }
 
+    /// Executes the given [reqwest::Request][1] using the backing
+    /// [reqwest::Client][2].
+    ///
+    /// [1]: https://docs.rs/reqwest/latest/reqwest/struct.Request.html
+    /// [2]: https://docs.rs/reqwest/latest/reqwest/struct.Client.html
+    async fn execute_request(
+        &self,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response, ClientError> {
         let url_err = request.url().to_string();
         let method_err = request.method().to_string();
-        let response = self
-            .http
+        self.http
             .execute(request)
             .await
             .map_err(|e| ClientError::RequestError {
                 source: e.into(),
                 url: url_err,
                 method: method_err,
-            })?;
+            })
+    }
+}
+
+#[async_trait]
+impl RustifyClient for Client {
+    fn base(&self) -> &str {
+        self.base.as_str()
+    }
+
+    #[instrument(skip(self, req), err)]
+    async fn send(&self, req: Request<Vec<u8>>) -> Result<Response<Vec<u8>>, ClientError> {
+        let response = self.execute(req).await?;
+        let http_resp = response_builder(&response);
 
-        let status_code = response.status().as_u16();
-        let mut http_resp = http::Response::builder().status(status_code);
-        for v in response.headers().into_iter() {
-            http_resp = http_resp.header(v.0, v.1);
+        http_resp
+            .body(
+                response
+                    .bytes()
+                    .await
+                    .map_err(|e| ClientError::ResponseError { source: e.into() })?
+                    .to_vec(),
+            )
+            .map_err(|e| ClientError::ResponseError { source: e.into() })
+    }
+
+    #[instrument(skip(self, req, body), err)]
+    async fn send_upload(
+        &self,
+        req: Request<Vec<u8>>,
+        body: Upload,
+    ) -> Result<Response<Vec<u8>>, ClientError> {
+        let mut request = reqwest::Request::try_from(req)
+            .map_err(|e| ClientError::ReqwestBuildError { source: e })?;
+        let (length, stream) = body.open().await?;
+        if let Some(l) = length {
+            request
+                .headers_mut()
+                .insert(CONTENT_LENGTH, HeaderValue::from(l));
         }
+        *request.body_mut() = Some(reqwest::Body::wrap_stream(stream));
+
+        let response = self.execute_request(request).await?;
+        let http_resp = response_builder(&response);
 
         http_resp
             .body(
@@ -100,4 +149,32 @@ impl RustifyClient for Client {
             )
             .map_err(|e| ClientError::ResponseError { source: e.into() })
     }
+
+    #[instrument(skip(self, req), err)]
+    async fn send_stream(
+        &self,
+        req: Request<Vec<u8>>,
+    ) -> Result<Response<ByteStream>, ClientError> {
+        let response = self.execute(req).await?;
+        let http_resp = response_builder(&response);
+
+        http_resp
+            .body(Box::pin(
+                response
+                    .bytes_stream()
+                    .map_err(|e| ClientError::ResponseError { source: e.into() }),
+            ) as ByteStream)
+            .map_err(|e| ClientError::ResponseError { source: e.into() })
+    }
+}
+
+/// Returns a response builder with the status and headers of the given
+/// [reqwest::Response].
+fn response_builder(response: &reqwest::Response) -> http::response::Builder {
+    let status_code = response.status().as_u16();
+    let mut http_resp = http::Response::builder().status(status_code);
+    for v in response.headers().into_iter() {
+        http_resp = http_resp.header(v.0, v.1);
+    }
+    http_resp
 }
@@ -0,0 +1,473 @@
+//! Contains the [Codec] trait and the built-in codecs used for encoding
+//! request bodies and decoding response bodies.
+
+use std::{borrow::Cow, marker::PhantomData};
+
+use http::{header::CONTENT_TYPE, HeaderValue, Response};
+use serde::{
+    de::{
+        value::{Error as ValueError, SeqDeserializer},
+        DeserializeOwned, Deserializer, IntoDeserializer, Visitor,
+    },
+    Serialize,
+};
+
+use crate::{errors::ClientError, multipart};
+
+/// Represents a body format which can be used for encoding the request body
+/// and/or decoding the response body of an [Endpoint][crate::Endpoint].
+///
+/// Codecs are selected through the [Endpoint::RequestCodec][1] and
+/// [Endpoint::ResponseCodec][2] associated types. When using the derive macro
+/// the built-in codecs can be selected by name with the `request_type` and
+/// `response_type` parameters, while any other type implementing this trait
+/// can be selected by path with the `request_codec` and `response_codec`
+/// parameters.
+///
+/// # Example
+/// ```
+/// use rustified::{codec::Codec, errors::ClientError};
+/// use serde::{de::DeserializeOwned, Serialize};
+///
+/// struct VendorJson;
+///
+/// impl Codec for VendorJson {
+///     fn content_type() -> &'static str {
+///         "application/vnd.vendor+json"
+///     }
+///
+///     fn encode<T: Serialize>(object: &T) -> Result<Vec<u8>, ClientError> {
+///         rustified::codec::Json::encode(object)
+///     }
+///
+///     fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, ClientError> {
+///         rustified::codec::Json::decode(body)
+///     }
+/// }
+/// ```
+///
+/// [1]: crate::endpoint::Endpoint::RequestCodec
+/// [2]: crate::endpoint::Endpoint::ResponseCodec
+pub trait Codec: Send + Sync {
+    /// Returns the media type of the bodies handled by this codec.
+    fn content_type() -> &'static str;
+
+    /// Returns the value of the `Accept` header sent along with requests whose
+    /// response is decoded by this codec.
+    ///
+    /// Defaults to [Codec::content_type].
+    fn accept() -> Cow<'static, str> {
+        Self::content_type().into()
+    }
+
+    /// Returns `true` if this codec is able to decode bodies of the given media
+    /// type, which is the value of a `Content-Type` header.
+    ///
+    /// Defaults to comparing the media type, without any parameters, against
+    /// [Codec::content_type].
+    fn matches(media_type: &str) -> bool {
+        essence(media_type).eq_ignore_ascii_case(Self::content_type())
+    }
+
+    /// Returns the value of the `Content-Type` header sent along with a
+    /// request body produced by this codec.
+    ///
+    /// Defaults to [Codec::content_type] and only needs to be implemented by
+    /// codecs whose header depends on the body, like multipart boundaries.
+    fn header(body: &[u8]) -> Result<HeaderValue, ClientError> {
+        let _ = body;
+        HeaderValue::from_str(Self::content_type())
+            .map_err(|e| ClientError::EndpointBuildError { source: e.into() })
+    }
+
+    /// Encodes the given object into a request body.
+    fn encode<T: Serialize>(object: &T) -> Result<Vec<u8>, ClientError>;
+
+    /// Decodes the given response body into `T`.
+    fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, ClientError>;
+
+    /// Decodes the body of the given [Response] into `T`.
+    ///
+    /// Defaults to [Codec::decode] and only needs to be implemented by codecs
+    /// which inspect the response headers, like [Negotiate].
+    fn decode_response<T: DeserializeOwned>(
+        response: &Response<Vec<u8>>,
+    ) -> Result<T, ClientError> {
+        Self::decode(response.body())
+    }
+}
+
+/// Encodes and decodes `application/json` bodies.
+///
+/// Objects which serialize into `null` or an empty JSON object produce an
+/// empty body.
+pub struct Json;
+
+impl Codec for Json {
+    fn content_type() -> &'static str {
+        "application/json"
+    }
+
+    fn matches(media_type: &str) -> bool {
+        let essence = essence(media_type).to_ascii_lowercase();
+        essence == Self::content_type() || essence.ends_with("+json")
+    }
+
+    fn encode<T: Serialize>(object: &T) -> Result<Vec<u8>, ClientError> {
+        let parse_data = serde_json::to_string(object)
+            .map_err(|e| ClientError::DataParseError { source: e.into() })?;
+        Ok(match parse_data.as_str() {
+            "null" => "".as_bytes().to_vec(),
+            "{}" => "".as_bytes().to_vec(),
+            _ => parse_data.as_bytes().to_vec(),
+        })
+    }
+
+    fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, ClientError> {
+        serde_json::from_slice(body).map_err(|e| parse_error(e.into(), body))
+    }
+}
+
+/// Encodes and decodes `application/x-ndjson` (newline-delimited JSON) bodies.
+///
+/// Requests are encoded from a sequence of records, one per line, and responses
+/// are decoded into a sequence of records. See [crate::ndjson] for details.
+pub struct Ndjson;
+
+impl Codec for Ndjson {
+    fn content_type() -> &'static str {
+        "application/x-ndjson"
+    }
+
+    fn encode<T: Serialize>(object: &T) -> Result<Vec<u8>, ClientError> {
+        crate::ndjson::to_vec(object)
+    }
+
+    fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, ClientError> {
+        crate::ndjson::from_slice(body)
+    }
+}
+
+/// Encodes and decodes `application/x-www-form-urlencoded` bodies.
+///
+/// Only flat key/value data is supported, nested values cause encoding to fail.
+pub struct Form;
+
+impl Codec for Form {
+    fn content_type() -> &'static str {
+        "application/x-www-form-urlencoded"
+    }
+
+    fn encode<T: Serialize>(object: &T) -> Result<Vec<u8>, ClientError> {
+        serde_urlencoded::to_string(object)
+            .map(String::into_bytes)
+            .map_err(|e| ClientError::DataParseError {
+                source: match &e {
+                    // Nested values are reported as unsupported keys, pairs or
+                    // values
+                    serde_urlencoded::ser::Error::Custom(msg) if msg.starts_with("unsupported") => {
+                        anyhow::anyhow!("Form bodies cannot contain nested values: {}", e)
+                    }
+                    _ => e.into(),
+                },
+            })
+    }
+
+    fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, ClientError> {
+        serde_urlencoded::from_bytes(body).map_err(|e| parse_error(e.into(), body))
+    }
+}
+
+/// Encodes `multipart/form-data` bodies.
+///
+/// Serialized objects are sent as text parts, see [crate::multipart] for
+/// sending files. Decoding multipart bodies is not supported.
+pub struct Multipart;
+
+impl Codec for Multipart {
+    fn content_type() -> &'static str {
+        "multipart/form-data"
+    }
+
+    fn header(body: &[u8]) -> Result<HeaderValue, ClientError> {
+        multipart::content_type(body)
+    }
+
+    fn encode<T: Serialize>(object: &T) -> Result<Vec<u8>, ClientError> {
+        let parse_data = Form::encode(object)?;
+        let mut form = multipart::Form::new();
+        for (k, v) in url::form_urlencoded::parse(&parse_data) {
+            form.text(&k, v);
+        }
+        Ok(form.finish())
+    }
+
+    fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, ClientError> {
+        Err(parse_error(
+            anyhow::anyhow!("Decoding multipart bodies is not supported"),
+            body,
+        ))
+    }
+}
+
+/// Encodes and decodes `application/xml` bodies.
+///
+/// The root element of an encoded body is named after the serialized type.
+#[cfg(feature = "xml")]
+pub struct Xml;
+
+#[cfg(feature = "xml")]
+impl Codec for Xml {
+    fn content_type() -> &'static str {
+        "application/xml"
+    }
+
+    fn matches(media_type: &str) -> bool {
+        let essence = essence(media_type).to_ascii_lowercase();
+        essence == Self::content_type() || essence == "text/xml" || essence.ends_with("+xml")
+    }
+
+    fn encode<T: Serialize>(object: &T) -> Result<Vec<u8>, ClientError> {
+        quick_xml::se::to_string(object)
+            .map(String::into_bytes)
+            .map_err(|e| ClientError::DataParseError { source: e.into() })
+    }
+
+    fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, ClientError> {
+        quick_xml::de::from_reader(body).map_err(|e| parse_error(e.into(), body))
+    }
+}
+
+/// Encodes and decodes `application/msgpack` bodies.
+///
+/// Structs are encoded as maps keyed by field name.
+#[cfg(feature = "msgpack")]
+pub struct MessagePack;
+
+#[cfg(feature = "msgpack")]
+impl Codec for MessagePack {
+    fn content_type() -> &'static str {
+        "application/msgpack"
+    }
+
+    fn encode<T: Serialize>(object: &T) -> Result<Vec<u8>, ClientError> {
+        rmp_serde::to_vec_named(object)
+            .map_err(|e| ClientError::DataParseError { source: e.into() })
+    }
+
+    fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, ClientError> {
+        rmp_serde::from_slice(body).map_err(|e| parse_error(e.into(), body))
+    }
+}
+
+/// Encodes and decodes `application/cbor` bodies.
+#[cfg(feature = "cbor")]
+pub struct Cbor;
+
+#[cfg(feature = "cbor")]
+impl Codec for Cbor {
+    fn content_type() -> &'static str {
+        "application/cbor"
+    }
+
+    fn encode<T: Serialize>(object: &T) -> Result<Vec<u8>, ClientError> {
+        let mut body = Vec::new();
+        ciborium::ser::into_writer(object, &mut body)
+            .map_err(|e| ClientError::DataParseError { source: e.into() })?;
+        Ok(body)
+    }
+
+    fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, ClientError> {
+        ciborium::de::from_reader(body).map_err(|e| parse_error(e.into(), body))
+    }
+}
+
+/// Passes `text/plain` bodies through as strings.
+///
+/// Responses are validated as UTF-8 and handed over as-is, which makes this
+/// codec suitable for a `Response` of [String]. Requests can only be encoded
+/// from a single string value. Any text media type is accepted.
+pub struct Text;
+
+impl Codec for Text {
+    fn content_type() -> &'static str {
+        "text/plain"
+    }
+
+    fn accept() -> Cow<'static, str> {
+        "text/*".into()
+    }
+
+    fn matches(media_type: &str) -> bool {
+        essence(media_type)
+            .to_ascii_lowercase()
+            .starts_with("text/")
+    }
+
+    fn encode<T: Serialize>(object: &T) -> Result<Vec<u8>, ClientError> {
+        match serde_json::to_value(object) {
+            Ok(serde_json::Value::String(s)) => Ok(s.into_bytes()),
+            _ => Err(ClientError::DataParseError {
+                source: anyhow::anyhow!("Text bodies can only be encoded from a string"),
+            }),
+        }
+    }
+
+    fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, ClientError> {
+        let text =
+            String::from_utf8(body.to_vec()).map_err(|e| ClientError::ResponseConversionError {
+                source: e.utf8_error().into(),
+                content: e.into_bytes(),
+            })?;
+        T::deserialize(IntoDeserializer::<ValueError>::into_deserializer(text))
+            .map_err(|e| parse_error(e.into(), body))
+    }
+}
+
+/// Passes `application/octet-stream` bodies through as raw bytes.
+///
+/// Responses are handed over as-is, which makes this codec suitable for a
+/// `Response` of [`Vec<u8>`] or [bytes::Bytes]. Requests with a raw body should
+/// use a `#[endpoint(raw)]` field instead. Any media type is accepted.
+pub struct Bytes;
+
+impl Codec for Bytes {
+    fn content_type() -> &'static str {
+        "application/octet-stream"
+    }
+
+    fn accept() -> Cow<'static, str> {
+        "*/*".into()
+    }
+
+    fn matches(_: &str) -> bool {
+        true
+    }
+
+    fn encode<T: Serialize>(object: &T) -> Result<Vec<u8>, ClientError> {
+        match serde_json::to_value(object) {
+            Ok(serde_json::Value::String(s)) => Ok(s.into_bytes()),
+            _ => Err(ClientError::DataParseError {
+                source: anyhow::anyhow!(
+                    "Byte bodies can only be encoded from a string, use a raw field instead"
+                ),
+            }),
+        }
+    }
+
+    fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, ClientError> {
+        T::deserialize(BytesDeserializer(body.to_vec())).map_err(|e| parse_error(e.into(), body))
+    }
+}
+
+/// Chooses between several codecs based on the media type of the response.
+///
+/// `C` is a tuple of up to six codecs, ordered by preference. The `Accept`
+/// header lists all of them and the response body is decoded by the first codec
+/// that [matches][Codec::matches] the `Content-Type` header of the response. A
+/// response with a media type none of them match fails with a
+/// [ClientError::ResponseMediaTypeError], while a response without a
+/// `Content-Type` header is decoded by the first codec. Request bodies are
+/// always encoded with the first codec.
+///
+/// When using the derive macro the built-in codecs can be negotiated between by
+/// separating their names with `|`, for example `response_type = "JSON | XML"`.
+pub struct Negotiate<C>(PhantomData<C>);
+
+macro_rules! impl_negotiate {
+    ($first:ident $(, $rest:ident)*) => {
+        impl<$first: Codec, $($rest: Codec),*> Codec for Negotiate<($first, $($rest,)*)> {
+            fn content_type() -> &'static str {
+                $first::content_type()
+            }
+
+            fn accept() -> Cow<'static, str> {
+                [$first::accept() $(, $rest::accept())*].join(", ").into()
+            }
+
+            fn matches(media_type: &str) -> bool {
+                $first::matches(media_type) $(|| $rest::matches(media_type))*
+            }
+
+            fn header(body: &[u8]) -> Result<HeaderValue, ClientError> {
+                $first::header(body)
+            }
+
+            fn encode<T: Serialize>(object: &T) -> Result<Vec<u8>, ClientError> {
+                $first::encode(object)
+            }
+
+            fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, ClientError> {
+                $first::decode(body)
+            }
+
+            fn decode_response<T: DeserializeOwned>(
+                response: &Response<Vec<u8>>,
+            ) -> Result<T, ClientError> {
+                let media_type = match response.headers().get(CONTENT_TYPE) {
+                    Some(m) => String::from_utf8_lossy(m.as_bytes()).into_owned(),
+                    None => return $first::decode_response(response),
+                };
+
+                if $first::matches(&media_type) {
+                    return $first::decode_response(response);
+                }
+                $(
+                    if $rest::matches(&media_type) {
+                        return $rest::decode_response(response);
+                    }
+                )*
+
+                Err(ClientError::ResponseMediaTypeError {
+                    media_type,
+                    expected: Self::accept().into_owned(),
+                    content: String::from_utf8(response.body().to_vec()).ok(),
+                })
+            }
+        }
+    };
+}
+
+impl_negotiate!(A);
+impl_negotiate!(A, B);
+impl_negotiate!(A, B, C);
+impl_negotiate!(A, B, C, D);
+impl_negotiate!(A, B, C, D, E);
+impl_negotiate!(A, B, C, D, E, F);
+
+/// A [Deserializer] which hands over a byte buffer without parsing it.
+///
+/// Sequences are supported so that [`Vec<u8>`] can be filled directly.
+struct BytesDeserializer(Vec<u8>);
+
+impl<'de> Deserializer<'de> for BytesDeserializer {
+    type Error = ValueError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_byte_buf(self.0)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(SeqDeserializer::new(self.0.into_iter()))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Returns the media type without any parameters.
+fn essence(media_type: &str) -> &str {
+    media_type.split(';').next().unwrap_or_default().trim()
+}
+
+/// Returns a [ClientError::ResponseParseError] containing the given body.
+fn parse_error(source: anyhow::Error, body: &[u8]) -> ClientError {
+    ClientError::ResponseParseError {
+        source,
+        content: String::from_utf8(body.to_vec()).ok(),
+        line: None,
+    }
+}
@@ -1,17 +1,22 @@
 //! Contains the [Endpoint] trait and supporting traits/functions.
 
-use std::marker::PhantomData;
+use std::{marker::PhantomData, time::Duration};
 
 use async_trait::async_trait;
-use http::{Request, Response};
+use http::{HeaderMap, Request, Response};
 use serde::de::DeserializeOwned;
 
 #[cfg(feature = "blocking")]
-use crate::blocking::client::Client as BlockingClient;
+use crate::blocking::{client::Client as BlockingClient, upload::Upload as BlockingUpload};
 use crate::{
-    client::Client,
-    enums::{RequestMethod, RequestType, ResponseType},
+    client::{AcceptedStatus, ByteStream, Client, HTTP_SUCCESS_CODES},
+    codec::{Codec, Json, Ndjson},
+    enums::RequestMethod,
     errors::ClientError,
+    ndjson::{LineStream, Lines},
+    retry::{copy_request, Retry},
+    sse::EventStream,
+    upload::Upload,
 };
 
 /// Represents a generic wrapper that can be applied to [Endpoint] results.
@@ -26,6 +31,48 @@ pub trait Wrapper: DeserializeOwned + Send + Sync {
     type Value;
 }
 
+/// Represents a type which the response of an [Endpoint] can be parsed into,
+/// decoding the response body using the [Codec] `C`.
+///
+/// This trait is implemented for all types implementing [DeserializeOwned],
+/// which are parsed from successful responses. The derive macro implements it
+/// for the enums generated by the `responses` parameter, which are parsed into
+/// the variant matching the status code of the response.
+pub trait FromResponse<C: Codec>: Sized + Send + Sync {
+    /// Returns `true` if responses with the given status code can be parsed
+    /// into this type. Responses with any other status code are returned as
+    /// the error of the endpoint, see [Endpoint::error].
+    ///
+    /// Defaults to [HTTP_SUCCESS_CODES].
+    fn accepts(status: u16) -> bool {
+        HTTP_SUCCESS_CODES.contains(&status)
+    }
+
+    /// Parses the given [Response] into this type.
+    fn from_response(response: &Response<Vec<u8>>) -> Result<Self, ClientError>;
+}
+
+impl<T: DeserializeOwned + Send + Sync, C: Codec> FromResponse<C> for T {
+    fn from_response(response: &Response<Vec<u8>>) -> Result<Self, ClientError> {
+        C::decode_response(response)
+    }
+}
+
+/// A [Response] extension holding the name of the variant of an enum
+/// [Endpoint] which was executed, see [Endpoint::variant].
+///
+/// The response enums generated for enum endpoints use it to parse responses
+/// into the variant of the operation which was executed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Variant(pub &'static str);
+
+impl Variant {
+    /// Returns the [Variant] of the given [Response], if any.
+    pub fn of<B>(resp: &Response<B>) -> Option<Self> {
+        resp.extensions().get::<Self>().copied()
+    }
+}
+
 /// Represents an [Endpoint] that has had [MiddleWare] applied to it.
 ///
 /// This type wraps [Endpoint] by implementng it. The primary difference is
@@ -50,9 +97,8 @@ impl<'a, E: Endpoint, M: MiddleWare> MutatedEndpoint<'a, E, M> {
 #[async_trait]
 impl<E: Endpoint, M: MiddleWare> Endpoint for MutatedEndpoint<'_, E, M> {
     type Response = E::Response;
-
-    const REQUEST_BODY_TYPE: RequestType = E::REQUEST_BODY_TYPE;
-    const RESPONSE_BODY_TYPE: ResponseType = E::RESPONSE_BODY_TYPE;
+    type RequestCodec = E::RequestCodec;
+    type ResponseCodec = E::ResponseCodec;
 
     fn path(&self) -> String {
         self.endpoint.path()
@@ -66,10 +112,26 @@ impl<E: Endpoint, M: MiddleWare> Endpoint for MutatedEndpoint<'_, E, M> {
         self.endpoint.query()
     }
 
+    fn headers(&self) -> Result<Option<HeaderMap>, ClientError> {
+        self.endpoint.headers()
+    }
+
     fn body(&self) -> Result<Option<Vec<u8>>, ClientError> {
         self.endpoint.body()
     }
 
+    fn error(&self, response: &Response<Vec<u8>>) -> ClientError {
+        self.endpoint.error(response)
+    }
+
+    fn retry(&self) -> Option<Retry> {
+        self.endpoint.retry()
+    }
+
+    fn variant(&self) -> Option<&'static str> {
+        self.endpoint.variant()
+    }
+
     #[instrument(skip(self), err)]
     fn url(&self, base: &str) -> Result<http::Uri, ClientError> {
         self.endpoint.url(base)
@@ -77,14 +139,15 @@ impl<E: Endpoint, M: MiddleWare> Endpoint for MutatedEndpoint<'_, E, M> {
 
     #[instrument(skip(self), err)]
     fn request(&self, base: &str) -> Result<Request<Vec<u8>>, ClientError> {
-        let mut req = crate::http::build_request(
+        let mut req = crate::http::build_request::<Self::RequestCodec, Self::ResponseCodec>(
             base,
             &self.path(),
             self.method(),
             self.query()?,
+            self.headers()?,
             self.body()?,
-            Self::REQUEST_BODY_TYPE,
         )?;
+        accept::<Self>(&mut req);
 
         self.middleware.request(self, &mut req)?;
         Ok(req)
@@ -94,24 +157,58 @@ impl<E: Endpoint, M: MiddleWare> Endpoint for MutatedEndpoint<'_, E, M> {
     async fn exec(
         &self,
         client: &impl Client,
-    ) -> Result<EndpointResult<Self::Response>, ClientError> {
+    ) -> Result<EndpointResult<Self::Response, Self::ResponseCodec>, ClientError> {
         debug!("Executing endpoint");
 
         let req = self.request(client.base())?;
         let resp = exec_mut(client, self, req, self.middleware).await?;
-        Ok(EndpointResult::new(resp, Self::RESPONSE_BODY_TYPE))
+        Ok(EndpointResult::new(resp))
+    }
+
+    #[instrument(skip(self, client, body), err)]
+    async fn exec_upload(
+        &self,
+        client: &impl Client,
+        body: Upload,
+    ) -> Result<EndpointResult<Self::Response, Self::ResponseCodec>, ClientError> {
+        debug!("Executing endpoint");
+
+        let mut req = upload_request(self, client.base())?;
+        body.apply(&mut req)?;
+        self.middleware.request(self, &mut req)?;
+        accept_any(&mut req);
+        let mut resp = check(self, client.execute_upload(req, body).await?)?;
+        self.middleware.response(self, &mut resp)?;
+        Ok(EndpointResult::new(resp))
     }
 
     #[cfg(feature = "blocking")]
     fn exec_block(
         &self,
         client: &impl BlockingClient,
-    ) -> Result<EndpointResult<Self::Response>, ClientError> {
+    ) -> Result<EndpointResult<Self::Response, Self::ResponseCodec>, ClientError> {
         debug!("Executing endpoint");
 
         let req = self.request(client.base())?;
         let resp = exec_block_mut(client, self, req, self.middleware)?;
-        Ok(EndpointResult::new(resp, Self::RESPONSE_BODY_TYPE))
+        Ok(EndpointResult::new(resp))
+    }
+
+    #[cfg(feature = "blocking")]
+    fn exec_upload_block(
+        &self,
+        client: &impl BlockingClient,
+        body: BlockingUpload,
+    ) -> Result<EndpointResult<Self::Response, Self::ResponseCodec>, ClientError> {
+        debug!("Executing endpoint");
+
+        let mut req = upload_request(self, client.base())?;
+        body.apply(&mut req)?;
+        self.middleware.request(self, &mut req)?;
+        accept_any(&mut req);
+        let mut resp = check(self, client.execute_upload(req, body)?)?;
+        self.middleware.response(self, &mut resp)?;
+        Ok(EndpointResult::new(resp))
     }
 }
 
@@ -123,21 +220,85 @@ impl<E: Endpoint, M: MiddleWare> Endpoint for MutatedEndpoint<'_, E, M> {
 /// Endpoint consists of:
 ///   * An `action` which is combined with the base URL of a Client to form a
 ///     fully qualified URL.
-///   * A `method` of type [RequestType] which determines the HTTP method used
+///   * A `method` of type [RequestMethod] which determines the HTTP method used
 ///     when a Client executes this endpoint.
-///   * A `ResponseType` type which determines the type of response this
-///     Endpoint will return when executed.
+///   * A `Response` type which determines the type of response this Endpoint
+///     will return when executed.
+///   * A `RequestCodec` and `ResponseCodec` which determine the format of the
+///     request and response bodies (see [Codec]).
+///
+/// The parameters of the `endpoint` attribute can be given in string literal
+/// form or as plain values, as in `method = POST`, `builder = true` and
+/// `response = Vec<User>`.
 ///
 /// The fields of the struct act as a representation of data that will be
 /// serialized and sent to the remote server. Where and how each field appears
 /// in the final request is determined by how they are tagged with attributes.
 /// For example, fields with `#[endpoint(query)]` will show up as a query
 /// parameter and fields with `#[endpoint(body)]` will show up in the body in
-/// the format specified by [Endpoint::REQUEST_BODY_TYPE]. By default, if no
+/// the format specified by [Endpoint::RequestCodec]. By default, if no
 /// fields are tagged with `#[endpoint(body)]` or `#[endpoint(raw)]` then any
 /// untagged fields are assumed to be tagged with `#[endpoint(body)]` (this
 /// reduces a large amount of boilerplate). Fields that should be excluded from
-/// this behavior can be tagged with `#[endpoint(skip)]`.
+/// this behavior can be tagged with `#[endpoint(skip)]`. Fields tagged with
+/// `#[endpoint(part)]` or `#[endpoint(file)]` are sent as the parts of a
+/// `multipart/form-data` body instead, see [crate::multipart] for details.
+/// Sequences and maps in query parameters are serialized with the style given
+/// by `query(style = comma)`, or per field with
+/// `#[endpoint(query(style = comma))]` (see [crate::query]).
+/// Fields tagged with `#[endpoint(header)]` are sent as request headers named
+/// after the field, with underscores replaced by dashes, or as the header given
+/// with `#[endpoint(header = "X-Name")]`. Their values are converted using
+/// [ToString]. Fields tagged with `#[endpoint(path)]` are referenced by name in
+/// the path template, as in `path = "users/{name}"`, and are percent-encoded
+/// as a single path segment (see [crate::http::encode_segment]). Fields whose
+/// values are meant to contain slashes can be tagged with
+/// `#[endpoint(path(slashes))]` instead. Any field used in the path template
+/// is left out of the body of untagged fields, and the derive macro fails if
+/// the template refers to a field which doesn't exist. Headers with a fixed
+/// value are given in the `Name: value` form by passing
+/// `headers("Accept: application/vnd.github+json")`, where the value may only
+/// contain visible ASCII characters, and are sent before any header fields and
+/// [MiddleWare] are applied.
+///
+/// The macro can also be derived on an enum with one variant per operation.
+/// Each variant declares its own `path` and `method` in an `endpoint`
+/// attribute and its fields are tagged the same way as the fields of a struct.
+/// Fixed `headers` declared on the enum are sent along with those of each
+/// variant. The codecs, a default `method` and a shared `response` are
+/// declared in an optional `endpoint` attribute on the enum. When the variants
+/// declare their own `response` instead, an enum named after the endpoint with
+/// a `Response` suffix is generated, with a variant holding the response of
+/// each variant of the endpoint. Responses are parsed into the variant which
+/// was executed (see [Variant]).
+///
+/// Endpoints which return a different response for each status code can map
+/// them with `responses(200 = "User", 404 = "NotFound", default = "ApiError")`
+/// in place of `response`. This generates an enum named after the endpoint
+/// with a `Response` suffix, which has a variant named after the reason phrase
+/// of each status code, as in `NotFound(NotFound)`, and a `Default(u16, T)`
+/// variant for all other status codes. Responses with a mapped status code are
+/// parsed into the enum even when they're unsuccessful (see [FromResponse]).
+///
+/// The body of unsuccessful responses can be decoded into a typed error by
+/// passing `error = "ApiError"`. Such responses are then returned as a
+/// [ClientError::Api] holding the decoded body, which can be retrieved using
+/// [ClientError::api_error] (see [Endpoint::error]).
+///
+/// Failed requests are retried by passing
+/// `retry(max = 3, backoff = "exponential", on = [502, 503, 504])`, which sets
+/// the policy returned by [Endpoint::retry]. Only idempotent methods are
+/// retried unless `non_idempotent = true` is given (see [crate::retry]).
+///
+/// Endpoints whose results are split over several pages can declare how the
+/// next page is requested with `paginate(cursor = "next_cursor", param =
+/// "cursor")`, `paginate(offset = "offset")`, `paginate(page = "page")` or
+/// `paginate(link = true)`, which implements
+/// [Paginated][crate::paginate::Paginated] (see [crate::paginate]).
+///
+/// With the `openapi` feature enabled the macro also implements
+/// `rustified::openapi::Describe`, which describes the operations of the
+/// endpoint for generating an OpenAPI document.
 ///
 /// It's worth noting that fields which have the [Option] type and whose value,
 /// at runtime, is [Option::None] will not be serialized. This avoids defining
@@ -176,13 +337,15 @@ pub trait Endpoint: Send + Sync + Sized {
     /// The type that the raw response from executing this endpoint will
     /// deserialized into. This type is passed on to the [EndpointResult] and is
     /// used to determine the type returned when the `parse()` method is called.
-    type Response: DeserializeOwned + Send + Sync;
+    /// It also determines which status codes are accepted, see
+    /// [FromResponse::accepts].
+    type Response: FromResponse<Self::ResponseCodec>;
 
-    /// The content type of the request body
-    const REQUEST_BODY_TYPE: RequestType;
+    /// The [Codec] used for encoding the request body.
+    type RequestCodec: Codec;
 
-    /// The content type of the response body
-    const RESPONSE_BODY_TYPE: ResponseType;
+    /// The [Codec] used for decoding the response body.
+    type ResponseCodec: Codec;
 
     /// The relative URL path that represents the location of this Endpoint.
     /// This is combined with the base URL from a
@@ -198,11 +361,44 @@ pub trait Endpoint: Send + Sync + Sized {
         Ok(None)
     }
 
+    /// Optional headers to add to the request. These replace any headers of
+    /// the same name set by the [Codecs][Codec].
+    fn headers(&self) -> Result<Option<HeaderMap>, ClientError> {
+        Ok(None)
+    }
+
     /// Optional data to add to the body of the request.
     fn body(&self) -> Result<Option<Vec<u8>>, ClientError> {
         Ok(None)
     }
 
+    /// Returns the error for a [Response] whose status code isn't accepted by
+    /// the [Endpoint::Response].
+    ///
+    /// Defaults to a [ClientError::ServerResponseError] holding the response
+    /// body. The derive macro overrides it when given an `error` type, which
+    /// the response body is decoded into and returned as a [ClientError::Api].
+    fn error(&self, response: &Response<Vec<u8>>) -> ClientError {
+        crate::client::response_error(response)
+    }
+
+    /// Returns the policy for retrying failed requests to this endpoint, see
+    /// [crate::retry].
+    ///
+    /// Defaults to [None], which never retries. The derive macro overrides it
+    /// when given the `retry` parameter.
+    fn retry(&self) -> Option<Retry> {
+        None
+    }
+
+    /// Returns the name of the variant of an enum endpoint, which is added to
+    /// its responses as a [Variant] extension.
+    ///
+    /// Defaults to [None]. The derive macro overrides it for enums.
+    fn variant(&self) -> Option<&'static str> {
+        None
+    }
+
     /// Returns the full URL address of the endpoint using the base address.
     #[instrument(skip(self), err)]
     fn url(&self, base: &str) -> Result<http::Uri, ClientError> {
@@ -213,14 +409,16 @@ pub trait Endpoint: Send + Sync + Sized {
     /// this endpoint.
     #[instrument(skip(self), err)]
     fn request(&self, base: &str) -> Result<Request<Vec<u8>>, ClientError> {
-        crate::http::build_request(
+        let mut req = crate::http::build_request::<Self::RequestCodec, Self::ResponseCodec>(
             base,
             &self.path(),
             self.method(),
             self.query()?,
+            self.headers()?,
             self.body()?,
-            Self::REQUEST_BODY_TYPE,
-        )
+        )?;
+        accept::<Self>(&mut req);
+        Ok(req)
     }
 
     /// Executes the Endpoint using the given [Client].
@@ -228,15 +426,94 @@ pub trait Endpoint: Send + Sync + Sized {
     async fn exec(
         &self,
         client: &impl Client,
-    ) -> Result<EndpointResult<Self::Response>, ClientError> {
+    ) -> Result<EndpointResult<Self::Response, Self::ResponseCodec>, ClientError> {
         debug!("Executing endpoint");
 
         let req = self.request(client.base())?;
-        let resp = exec(client, req).await?;
-        Ok(EndpointResult::new(resp, Self::RESPONSE_BODY_TYPE))
+        let resp = exec(client, self, req).await?;
+        Ok(EndpointResult::new(resp))
     }
 
-    fn with_middleware<M: MiddleWare>(self, middleware: &M) -> MutatedEndpoint<Self, M> {
+    /// Executes the Endpoint using the given [Client] and returns the response
+    /// body as a [ByteStream] instead of buffering it.
+    ///
+    /// The status code of the response is checked before the body is consumed,
+    /// unaccepted responses are returned as the [Endpoint::error]. Response
+    /// [MiddleWare] is not applied to streamed responses.
+    #[instrument(skip(self, client), err)]
+    async fn exec_stream(&self, client: &impl Client) -> Result<Response<ByteStream>, ClientError> {
+        debug!("Executing endpoint");
+
+        let mut req = self.request(client.base())?;
+        accept_any(&mut req);
+        check_stream(self, client.execute_stream(req).await?).await
+    }
+
+    /// Executes the Endpoint using the given [Client] and streams the given
+    /// [Upload] as the request body.
+    ///
+    /// The body returned by [Endpoint::body] is not sent. The request is sent
+    /// as `application/octet-stream` unless the upload sets a content type.
+    #[instrument(skip(self, client, body), err)]
+    async fn exec_upload(
+        &self,
+        client: &impl Client,
+        body: Upload,
+    ) -> Result<EndpointResult<Self::Response, Self::ResponseCodec>, ClientError> {
+        debug!("Executing endpoint");
+
+        let mut req = upload_request(self, client.base())?;
+        body.apply(&mut req)?;
+        accept_any(&mut req);
+        let resp = check(self, client.execute_upload(req, body).await?)?;
+        Ok(EndpointResult::new(resp))
+    }
+
+    /// Executes the Endpoint using the given [Client] and decodes the records
+    /// of its NDJSON response body as they're received, see [crate::ndjson].
+    ///
+    /// The status code of the response is checked before the body is consumed,
+    /// unaccepted responses are returned as the [Endpoint::error]. Response
+    /// [MiddleWare] is not applied to streamed responses.
+    #[instrument(skip(self, client), err)]
+    async fn exec_lines<T>(&self, client: &impl Client) -> Result<LineStream<T>, ClientError>
+    where
+        T: DeserializeOwned + Send + 'static,
+        Self: Endpoint<Response = Vec<T>, ResponseCodec = Ndjson>,
+    {
+        debug!("Executing endpoint");
+
+        let mut req = self.request(client.base())?;
+        accept_any(&mut req);
+        let resp = check_stream(self, client.execute_stream(req).await?).await?;
+        Ok(crate::ndjson::stream(resp.into_body()))
+    }
+
+    /// Executes the Endpoint using the given [Client] and returns the
+    /// server-sent events of its `text/event-stream` response, see
+    /// [crate::sse].
+    ///
+    /// The request is only sent once the stream is first polled and is sent
+    /// again whenever the server closes the connection, which is why the stream
+    /// borrows the endpoint. Unaccepted responses are returned as the
+    /// [Endpoint::error]. Response [MiddleWare] is not applied to event
+    /// streams.
+    #[instrument(skip(self, client), err)]
+    fn exec_events<'a>(
+        &'a self,
+        client: &'a impl Client,
+    ) -> Result<EventStream<'a, Self::Response, Self::ResponseCodec>, ClientError>
+    where
+        Self::Response: DeserializeOwned + 'a,
+        Self::ResponseCodec: 'a,
+    {
+        debug!("Executing endpoint");
+
+        let req = self.request(client.base())?;
+        Ok(crate::sse::stream(self, client, req))
+    }
+
+    fn with_middleware<M: MiddleWare>(self, middleware: &M) -> MutatedEndpoint<'_, Self, M> {
         MutatedEndpoint::new(self, middleware)
     }
 
@@ -246,12 +523,30 @@ pub trait Endpoint: Send + Sync + Sized {
     fn exec_block(
         &self,
         client: &impl BlockingClient,
-    ) -> Result<EndpointResult<Self::Response>, ClientError> {
+    ) -> Result<EndpointResult<Self::Response, Self::ResponseCodec>, ClientError> {
         debug!("Executing endpoint");
 
         let req = self.request(client.base())?;
-        let resp = exec_block(client, req)?;
-        Ok(EndpointResult::new(resp, Self::RESPONSE_BODY_TYPE))
+        let resp = exec_block(client, self, req)?;
+        Ok(EndpointResult::new(resp))
+    }
+
+    /// Executes the Endpoint using the given [Client] and streams the given
+    /// [Upload][BlockingUpload] as the request body.
+    #[cfg(feature = "blocking")]
+    #[instrument(skip(self, client, body), err)]
+    fn exec_upload_block(
+        &self,
+        client: &impl BlockingClient,
+        body: BlockingUpload,
+    ) -> Result<EndpointResult<Self::Response, Self::ResponseCodec>, ClientError> {
+        debug!("Executing endpoint");
+
+        let mut req = upload_request(self, client.base())?;
+        body.apply(&mut req)?;
+        accept_any(&mut req);
+        let resp = check(self, client.execute_upload(req, body)?)?;
+        Ok(EndpointResult::new(resp))
     }
 }
 
@@ -260,19 +555,18 @@ pub trait Endpoint: Send + Sync + Sized {
 /// All [Endpoint] executions will result in an [EndpointResult] which wraps
 /// the actual HTTP [Response] and the final result type. The response can be
 /// parsed into the final result type by calling `parse()` or optionally
-/// wrapped by a [Wrapper] by calling `wrap()`.
-pub struct EndpointResult<T: DeserializeOwned + Send + Sync> {
+/// wrapped by a [Wrapper] by calling `wrap()`. In both cases the response body
+/// is decoded using the [Codec] `C`.
+pub struct EndpointResult<T: FromResponse<C>, C: Codec = Json> {
     pub response: Response<Vec<u8>>,
-    pub ty: ResponseType,
-    inner: PhantomData<T>,
+    inner: PhantomData<(T, C)>,
 }
 
-impl<T: DeserializeOwned + Send + Sync> EndpointResult<T> {
+impl<T: FromResponse<C>, C: Codec> EndpointResult<T, C> {
     /// Returns a new [EndpointResult].
-    pub fn new(response: Response<Vec<u8>>, ty: ResponseType) -> Self {
+    pub fn new(response: Response<Vec<u8>>) -> Self {
         EndpointResult {
             response,
-            ty,
             inner: PhantomData,
         }
     }
@@ -280,14 +574,7 @@ impl<T: DeserializeOwned + Send + Sync> EndpointResult<T> {
     /// Parses the response into the final result type.
     #[instrument(skip(self), err)]
     pub fn parse(&self) -> Result<T, ClientError> {
-        match self.ty {
-            ResponseType::JSON => serde_json::from_slice(self.response.body()).map_err(|e| {
-                ClientError::ResponseParseError {
-                    source: e.into(),
-                    content: String::from_utf8(self.response.body().to_vec()).ok(),
-                }
-            }),
-        }
+        T::from_response(&self.response)
     }
 
     /// Returns the raw response body from the HTTP [Response].
@@ -302,14 +589,15 @@ impl<T: DeserializeOwned + Send + Sync> EndpointResult<T> {
     where
         W: Wrapper<Value = T>,
     {
-        match self.ty {
-            ResponseType::JSON => serde_json::from_slice(self.response.body()).map_err(|e| {
-                ClientError::ResponseParseError {
-                    source: e.into(),
-                    content: String::from_utf8(self.response.body().to_vec()).ok(),
-                }
-            }),
-        }
+        C::decode_response(&self.response)
+    }
+}
+
+impl<T: DeserializeOwned + Send + Sync> EndpointResult<Vec<T>, Ndjson> {
+    /// Returns an iterator which decodes the records of the NDJSON response
+    /// body one line at a time, see [crate::ndjson].
+    pub fn lines(&self) -> Lines<'_, T> {
+        Lines::new(self.response.body())
     }
 }
 
@@ -335,40 +623,157 @@ pub trait MiddleWare: Sync + Send {
     ) -> Result<(), ClientError>;
 }
 
-async fn exec(
+/// Sets the status codes accepted by the response of the [Endpoint] on the
+/// given [Request].
+fn accept<E: Endpoint>(req: &mut Request<Vec<u8>>) {
+    req.extensions_mut().insert(AcceptedStatus(accepts::<E>));
+}
+
+/// Returns `true` if the given status code is accepted by the
+/// [Endpoint::Response].
+fn accepts<E: Endpoint>(status: u16) -> bool {
+    <E::Response as FromResponse<E::ResponseCodec>>::accepts(status)
+}
+
+/// Makes the client accept responses with any status code, leaving them to be
+/// checked by [check].
+pub(crate) fn accept_any(req: &mut Request<Vec<u8>>) {
+    req.extensions_mut().insert(AcceptedStatus(|_| true));
+}
+
+/// Returns [Endpoint::error] if the status code of the given [Response] isn't
+/// accepted by the [Endpoint::Response], otherwise adds the [Variant] of the
+/// endpoint to the response.
+fn check<E: Endpoint>(
+    endpoint: &E,
+    mut resp: Response<Vec<u8>>,
+) -> Result<Response<Vec<u8>>, ClientError> {
+    if !accepts::<E>(resp.status().as_u16()) {
+        return Err(endpoint.error(&resp));
+    }
+    if let Some(v) = endpoint.variant() {
+        resp.extensions_mut().insert(Variant(v));
+    }
+    Ok(resp)
+}
+
+/// Returns [Endpoint::error] if the status code of the given streamed
+/// [Response] isn't accepted by the [Endpoint::Response]. The body is only read
+/// when the response isn't accepted.
+pub(crate) async fn check_stream<E: Endpoint>(
+    endpoint: &E,
+    resp: Response<ByteStream>,
+) -> Result<Response<ByteStream>, ClientError> {
+    if !accepts::<E>(resp.status().as_u16()) {
+        return Err(endpoint.error(&crate::client::buffer(resp).await));
+    }
+    Ok(resp)
+}
+
+fn upload_request<E: Endpoint>(endpoint: &E, base: &str) -> Result<Request<Vec<u8>>, ClientError> {
+    crate::http::build_request::<crate::codec::Bytes, E::ResponseCodec>(
+        base,
+        &endpoint.path(),
+        endpoint.method(),
+        endpoint.query()?,
+        endpoint.headers()?,
+        None,
+    )
+}
+
+/// Returns the [Retry] policy of the [Endpoint] if it applies to its method.
+fn retry_policy<E: Endpoint>(endpoint: &E) -> Option<Retry> {
+    endpoint.retry().filter(|r| r.allows(&endpoint.method()))
+}
+
+/// Logs the retry of a request which received the given result.
+fn log_retry(result: &Result<Response<Vec<u8>>, ClientError>, delay: Duration) {
+    match result {
+        Ok(resp) => warn!("Retrying in {:?} after status {}", delay, resp.status()),
+        Err(e) => warn!("Retrying in {:?} after error: {}", delay, e),
+    }
+}
+
+pub(crate) async fn exec<E: Endpoint>(
     client: &impl Client,
-    req: Request<Vec<u8>>,
+    endpoint: &E,
+    mut req: Request<Vec<u8>>,
 ) -> Result<Response<Vec<u8>>, ClientError> {
-    client.execute(req).await
+    let retry = match retry_policy(endpoint) {
+        Some(r) => r,
+        None => {
+            accept_any(&mut req);
+            return check(endpoint, client.execute(req).await?);
+        }
+    };
+
+    let mut attempt = 1;
+    loop {
+        debug!("Sending attempt {} of {}", attempt, retry.max + 1);
+        let mut next = copy_request(&req);
+        accept_any(&mut next);
+        let result = client.execute(next).await;
+        match retry.wait(attempt, &result, accepts::<E>) {
+            Some(delay) => {
+                log_retry(&result, delay);
+                tokio::time::sleep(delay).await;
+            }
+            None => return check(endpoint, result?),
+        }
+        attempt += 1;
+    }
 }
 
-async fn exec_mut(
+async fn exec_mut<E: Endpoint>(
     client: &impl Client,
-    endpoint: &impl Endpoint,
+    endpoint: &E,
     req: Request<Vec<u8>>,
     middle: &impl MiddleWare,
 ) -> Result<Response<Vec<u8>>, ClientError> {
-    let mut resp = client.execute(req).await?;
+    let mut resp = exec(client, endpoint, req).await?;
     middle.response(endpoint, &mut resp)?;
     Ok(resp)
 }
 
 #[cfg(feature = "blocking")]
-fn exec_block(
+pub(crate) fn exec_block<E: Endpoint>(
     client: &impl BlockingClient,
-    req: Request<Vec<u8>>,
+    endpoint: &E,
+    mut req: Request<Vec<u8>>,
 ) -> Result<Response<Vec<u8>>, ClientError> {
-    client.execute(req)
+    let retry = match retry_policy(endpoint) {
+        Some(r) => r,
+        None => {
+            accept_any(&mut req);
+            return check(endpoint, client.execute(req)?);
+        }
+    };
+
+    let mut attempt = 1;
+    loop {
+        debug!("Sending attempt {} of {}", attempt, retry.max + 1);
+        let mut next = copy_request(&req);
+        accept_any(&mut next);
+        let result = client.execute(next);
+        match retry.wait(attempt, &result, accepts::<E>) {
+            Some(delay) => {
+                log_retry(&result, delay);
+                std::thread::sleep(delay);
+            }
+            None => return check(endpoint, result?),
+        }
+        attempt += 1;
+    }
 }
 
 #[cfg(feature = "blocking")]
-fn exec_block_mut(
+fn exec_block_mut<E: Endpoint>(
     client: &impl BlockingClient,
-    endpoint: &impl Endpoint,
+    endpoint: &E,
     req: Request<Vec<u8>>,
     middle: &impl MiddleWare,
 ) -> Result<Response<Vec<u8>>, ClientError> {
-    let mut resp = client.execute(req)?;
+    let mut resp = exec_block(client, endpoint, req)?;
     middle.response(endpoint, &mut resp)?;
     Ok(resp)
 }
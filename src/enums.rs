@@ -1,7 +1,5 @@
 //! Contains common enums used across the crate
 
-use http::HeaderValue;
-
 /// Represents a HTTP request method
 #[derive(Clone, Debug)]
 pub enum RequestMethod {
@@ -34,23 +32,3 @@ impl Into<http::Method> for RequestMethod {
         }
     }
 }
-
-/// Represents the type of a HTTP request body
-#[derive(Clone, Debug)]
-pub enum RequestType {
-    JSON,
-}
-
-impl From<RequestType> for HeaderValue {
-    fn from(ty: RequestType) -> Self {
-        match ty {
-            RequestType::JSON => HeaderValue::from_static("application/json"),
-        }
-    }
-}
-
-/// Represents the type of a HTTP response body
-#[derive(Clone, Debug)]
-pub enum ResponseType {
-    JSON,
-}
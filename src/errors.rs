@@ -1,4 +1,7 @@
 //! Contains the common error enum used across this crate
+use std::{any::Any, fmt};
+
+use http::HeaderMap;
 use thiserror::Error;
 
 use crate::enums::RequestMethod;
@@ -6,12 +9,23 @@ use crate::enums::RequestMethod;
 /// The general error type returned by this crate
 #[derive(Error, Debug)]
 pub enum ClientError {
+    #[error("Server returned error (HTTP {status}): {error:?}")]
+    Api {
+        status: u16,
+        headers: HeaderMap,
+        error: ApiError,
+    },
     #[error("Error parsing endpoint into data: {source}")]
     DataParseError { source: anyhow::Error },
     #[error("Error building endpoint request: {source}")]
     EndpointBuildError { source: anyhow::Error },
     #[error("An error occurred in processing the request: {source}")]
     GenericError { source: anyhow::Error },
+    #[error("Error building value of header {name}: {source}")]
+    HeaderValueError {
+        source: http::header::InvalidHeaderValue,
+        name: String,
+    },
     #[error("Error sending HTTP request: {source}")]
     RequestError {
         source: anyhow::Error,
@@ -33,13 +47,22 @@ pub enum ClientError {
         source: anyhow::Error,
         content: Vec<u8>,
     },
+    #[error("Server responded with unexpected media type: {media_type}")]
+    ResponseMediaTypeError {
+        media_type: String,
+        expected: String,
+        content: Option<String>,
+    },
     #[error("Error parsing HTTP response: {source}")]
     ResponseParseError {
         source: anyhow::Error,
         content: Option<String>,
+        line: Option<usize>,
     },
     #[error("Server returned error (HTTP {code})")]
     ServerResponseError { code: u16, content: Option<String> },
+    #[error("Error reading request body: {source}")]
+    UploadError { source: anyhow::Error },
     #[error("Error building URL: {source}")]
     UrlBuildError { source: http::uri::InvalidUri },
     #[error("Error serializing URL query parameters: {source}")]
@@ -47,3 +70,63 @@ pub enum ClientError {
     #[error("Error parsing URL: {source}")]
     UrlParseError { source: url::ParseError },
 }
+
+impl ClientError {
+    /// Returns the body of a [ClientError::Api] error if it was decoded into a
+    /// `T`.
+    pub fn api_error<T: Any>(&self) -> Option<&T> {
+        match self {
+            ClientError::Api { error, .. } => error.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// The body of an unsuccessful response, decoded into the error type of an
+/// [Endpoint][crate::endpoint::Endpoint] and returned in a [ClientError::Api].
+///
+/// The body can be retrieved by its type using [ApiError::downcast_ref] or
+/// [ApiError::downcast].
+pub struct ApiError(Box<dyn ErrorBody>);
+
+impl ApiError {
+    /// Returns a new [ApiError] holding the given body.
+    pub fn new<T: Any + fmt::Debug + Send + Sync>(error: T) -> Self {
+        ApiError(Box::new(error))
+    }
+
+    /// Returns a reference to the body if it's a `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        ErrorBody::as_any(&*self.0).downcast_ref()
+    }
+
+    /// Returns the body if it's a `T`, or the [ApiError] itself otherwise.
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        if !ErrorBody::as_any(&*self.0).is::<T>() {
+            return Err(self);
+        }
+        Ok(*ErrorBody::into_any(self.0).downcast().unwrap())
+    }
+}
+
+impl fmt::Debug for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+/// The types which can be held by an [ApiError].
+trait ErrorBody: fmt::Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: Any + fmt::Debug + Send + Sync> ErrorBody for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
@@ -1,76 +1,182 @@
 //! Contains helper functions for working with HTTP requests and responses.
 
-use http::{header::CONTENT_TYPE, Request, Uri};
+use http::{
+    header::{ACCEPT, CONTENT_TYPE},
+    HeaderMap, HeaderValue, Request, Uri,
+};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS, NON_ALPHANUMERIC};
 use serde::Serialize;
-use url::Url;
+use url::{Position, Url};
 
 use crate::{
-    enums::{RequestMethod, RequestType},
+    codec::Codec,
+    enums::RequestMethod,
     errors::ClientError,
+    query::{self, QueryStyle},
 };
 
-/// Builds a request body by serializing an object using a serializer determined
-/// by the [RequestType].
+/// Builds a request body by encoding an object using the given [Codec].
 #[instrument(skip(object), err)]
-pub fn build_body(object: &impl Serialize, ty: RequestType) -> Result<Vec<u8>, ClientError> {
-    match ty {
-        RequestType::JSON => {
-            let parse_data = serde_json::to_string(object)
-                .map_err(|e| ClientError::DataParseError { source: e.into() })?;
-            Ok(match parse_data.as_str() {
-                "null" => "".as_bytes().to_vec(),
-                "{}" => "".as_bytes().to_vec(),
-                _ => parse_data.as_bytes().to_vec(),
-            })
-        }
-    }
+pub fn build_body<C: Codec>(object: &impl Serialize) -> Result<Vec<u8>, ClientError> {
+    C::encode(object)
 }
 
-/// Builds a query string by serializing an object.
+/// Builds the value of the header with the given name from an object.
+pub fn build_header(name: &str, value: &impl ToString) -> Result<HeaderValue, ClientError> {
+    HeaderValue::from_str(&value.to_string()).map_err(|e| ClientError::HeaderValueError {
+        source: e,
+        name: name.to_string(),
+    })
+}
+
+/// Builds a query string by serializing an object with the default
+/// [QueryStyle] (see [crate::query]).
 #[instrument(skip(object), err)]
 pub fn build_query(object: &impl Serialize) -> Result<String, ClientError> {
-    serde_urlencoded::to_string(object)
-        .map_err(|e| ClientError::UrlQueryParseError { source: e.into() })
+    query::to_string(object, QueryStyle::default())
 }
 
 /// Builds a [Request] using the given [Endpoint][crate::Endpoint] and base URL.
-#[instrument(skip(query, data), err)]
-pub fn build_request(
+///
+/// The `Content-Type` header is determined by the request [Codec] `Req` and the
+/// `Accept` header by the response [Codec] `Resp`. Any `headers` are added
+/// afterwards, replacing headers of the same name.
+#[instrument(skip(query, headers, data), err)]
+pub fn build_request<Req: Codec, Resp: Codec>(
     base: &str,
     path: &str,
     method: RequestMethod,
     query: Option<String>,
+    headers: Option<HeaderMap>,
     data: Option<Vec<u8>>,
-    ty: RequestType,
 ) -> Result<Request<Vec<u8>>, ClientError> {
     debug!("Building endpoint request");
     let uri = build_url(base, path, query)?;
 
+    let data = data.unwrap_or_default();
+    let content_type = Req::header(&data)?;
+
     let method_err = method.clone();
     let uri_err = uri.to_string();
-    Request::builder()
+    let mut req = Request::builder()
         .uri(uri)
         .method(method)
-        .header(CONTENT_TYPE, ty)
-        .body(data.unwrap_or_default())
+        .header(CONTENT_TYPE, content_type)
+        .header(ACCEPT, Resp::accept().as_ref())
+        .body(data)
         .map_err(|e| ClientError::RequestBuildError {
             source: e,
             method: method_err,
             url: uri_err,
-        })
+        })?;
+    if let Some(h) = headers {
+        req.headers_mut().extend(h);
+    }
+    Ok(req)
 }
 
 /// Combines the given base URL, relative path, and optional query parameters
 /// into a single [Uri].
+///
+/// The path is appended to the path of the base URL one segment at a time.
+/// Characters which aren't allowed in a path segment are percent-encoded while
+/// existing percent-encoded sequences, like those produced by
+/// [encode_segment], are kept as-is. Values which may contain a literal `%`
+/// should be passed through [escape_percent] first. Segments consisting of `.`
+/// or `..` are skipped.
 #[instrument(skip(query), err)]
 pub fn build_url(base: &str, path: &str, query: Option<String>) -> Result<Uri, ClientError> {
     let mut url = Url::parse(base).map_err(|e| ClientError::UrlParseError { source: e })?;
-    url.path_segments_mut().unwrap().extend(path.split('/'));
     if let Some(q) = query {
         url.set_query(Some(q.as_str()));
     }
 
-    url.to_string()
-        .parse::<Uri>()
-        .map_err(|e| ClientError::UrlBuildError { source: e })
+    let mut full_path = url.path().to_string();
+    for segment in path.split('/').filter(|s| !matches!(*s, "." | "..")) {
+        if full_path.len() > 1 {
+            full_path.push('/');
+        }
+        full_path.push_str(&encode_escaped(segment));
+    }
+
+    format!(
+        "{}{}{}",
+        &url[..Position::BeforePath],
+        full_path,
+        &url[Position::AfterPath..]
+    )
+    .parse::<Uri>()
+    .map_err(|e| ClientError::UrlBuildError { source: e })
+}
+
+/// Percent-encodes the given value so it can be used as a single path segment.
+///
+/// All characters besides ASCII letters, digits, `-`, `.`, `_` and `~` are
+/// encoded, including `/`. A value of `.` or `..` is encoded completely, note
+/// however that clients following the WHATWG URL standard, like
+/// [reqwest](https://docs.rs/reqwest/), still resolve it as a relative segment.
+pub fn encode_segment(value: &impl ToString) -> String {
+    let value = value.to_string();
+    match value.as_str() {
+        "." => "%2E".to_string(),
+        ".." => "%2E%2E".to_string(),
+        v => utf8_percent_encode(v, SEGMENT).to_string(),
+    }
+}
+
+/// Percent-encodes the given value so it can be used as several path segments.
+///
+/// Each part of the value separated by `/` is encoded using [encode_segment].
+pub fn encode_path(value: &impl ToString) -> String {
+    value
+        .to_string()
+        .split('/')
+        .map(|s| encode_segment(&s))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Encodes the percent signs in the given value, so that [build_url] keeps
+/// them as-is instead of treating them as percent-encoded sequences.
+///
+/// This is used for values interpolated into a path with `{self.field}`,
+/// which are otherwise left to [build_url] to encode.
+pub fn escape_percent(value: &impl ToString) -> String {
+    value.to_string().replace('%', "%25")
+}
+
+/// The characters encoded in path parameters, which are all besides the
+/// unreserved characters of RFC 3986.
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// The characters encoded in path segments, matching the `url` crate. Percent
+/// signs are handled separately by [encode_escaped].
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'\\')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Percent-encodes a path segment, keeping any existing percent-encoded
+/// sequences and encoding all other percent signs.
+fn encode_escaped(segment: &str) -> String {
+    let mut result = String::with_capacity(segment.len());
+    for (i, part) in segment.split('%').enumerate() {
+        if i > 0 {
+            let escaped = part.len() >= 2 && part.as_bytes()[..2].iter().all(u8::is_ascii_hexdigit);
+            result.push_str(if escaped { "%" } else { "%25" });
+        }
+        result.extend(utf8_percent_encode(part, PATH_SEGMENT));
+    }
+    result
 }
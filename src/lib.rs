@@ -7,10 +7,20 @@ extern crate tracing;
 pub mod blocking;
 pub mod client;
 pub mod clients;
+pub mod codec;
 pub mod endpoint;
 pub mod enums;
 pub mod errors;
 pub mod http;
+pub mod multipart;
+pub mod ndjson;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod paginate;
+pub mod query;
+pub mod retry;
+pub mod sse;
+pub mod upload;
 
 #[doc(hidden)]
 #[path = "private/mod.rs"]
@@ -20,3 +30,4 @@ pub use crate::{
     clients::reqwest::Client,
     endpoint::{Endpoint, MiddleWare, Wrapper},
 };
+pub use rustified_derive::api;
@@ -0,0 +1,185 @@
+//! Contains helpers for building `multipart/form-data` request bodies.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+use http::HeaderValue;
+
+use crate::errors::ClientError;
+
+/// A file to be uploaded as a single part of a `multipart/form-data` request.
+///
+/// Fields tagged with `#[endpoint(file)]` are expected to be of this type (or
+/// an [Option] of it). When not set, the filename falls back to the name of the
+/// part and the content type falls back to `application/octet-stream`.
+#[derive(Clone, Debug, Default)]
+pub struct File {
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+impl File {
+    /// Returns a new [File] containing the given data.
+    pub fn new(data: impl Into<Vec<u8>>) -> Self {
+        File {
+            data: data.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the filename sent along with this part.
+    pub fn filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Sets the content type sent along with this part.
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+}
+
+/// Builds a `multipart/form-data` request body.
+///
+/// A random boundary is generated when the [Form] is created, and replaced by a
+/// new one when finishing the form if any part contains it. The finished body
+/// starts with the opening delimiter and ends with the closing delimiter of
+/// this boundary, which is how the [Multipart][crate::codec::Multipart] codec
+/// finds it for the `Content-Type` header.
+#[derive(Debug)]
+pub struct Form {
+    boundary: String,
+    /// The headers and data of each part
+    parts: Vec<(String, Vec<u8>)>,
+}
+
+impl Form {
+    /// Returns a new, empty [Form] with a randomly generated boundary.
+    pub fn new() -> Self {
+        Form {
+            boundary: boundary(),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Returns the boundary used to separate parts.
+    ///
+    /// Note that the boundary may still change when the form is finished.
+    pub fn boundary(&self) -> &str {
+        self.boundary.as_str()
+    }
+
+    /// Returns the `Content-Type` header value for the body of this form.
+    pub fn content_type(&self) -> Result<HeaderValue, ClientError> {
+        HeaderValue::from_str(&format!("multipart/form-data; boundary={}", self.boundary))
+            .map_err(|e| ClientError::EndpointBuildError { source: e.into() })
+    }
+
+    /// Adds a text part to the form.
+    pub fn text(&mut self, name: &str, value: impl AsRef<str>) -> &mut Self {
+        self.part(name, None, None, value.as_ref().as_bytes())
+    }
+
+    /// Adds a file part to the form.
+    pub fn file(&mut self, name: &str, file: &File) -> &mut Self {
+        self.part(
+            name,
+            Some(file.filename.as_deref().unwrap_or(name)),
+            Some(
+                file.content_type
+                    .as_deref()
+                    .unwrap_or("application/octet-stream"),
+            ),
+            &file.data,
+        )
+    }
+
+    /// Closes the form and returns the encoded body.
+    pub fn finish(mut self) -> Vec<u8> {
+        // A boundary must not appear within any of the parts
+        while self.parts.iter().any(|(head, data)| {
+            head.contains(&self.boundary) || contains(data, self.boundary.as_bytes())
+        }) {
+            self.boundary = boundary();
+        }
+
+        let mut body = Vec::new();
+        for (head, data) in &self.parts {
+            body.extend_from_slice(format!("--{}\r\n{}", self.boundary, head).as_bytes());
+            body.extend_from_slice(data);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        body
+    }
+
+    fn part(
+        &mut self,
+        name: &str,
+        filename: Option<&str>,
+        content_type: Option<&str>,
+        data: &[u8],
+    ) -> &mut Self {
+        let mut head = format!("Content-Disposition: form-data; name=\"{}\"", escape(name));
+        if let Some(f) = filename {
+            head.push_str(format!("; filename=\"{}\"", escape(f)).as_str());
+        }
+        if let Some(c) = content_type {
+            head.push_str(format!("\r\nContent-Type: {}", c).as_str());
+        }
+        head.push_str("\r\n\r\n");
+
+        self.parts.push((head, data.to_vec()));
+        self
+    }
+}
+
+impl Default for Form {
+    fn default() -> Self {
+        Form::new()
+    }
+}
+
+/// Returns the `Content-Type` header value for a multipart body built with a
+/// [Form], using the boundary of its delimiters.
+///
+/// Fails if the body doesn't open and close with the same boundary, as bodies
+/// built by a [Form] do.
+pub fn content_type(body: &[u8]) -> Result<HeaderValue, ClientError> {
+    let boundary = body
+        .strip_prefix(b"--")
+        .and_then(|b| b.split(|c| *c == b'\r').next())
+        .map(|b| b.strip_suffix(b"--").unwrap_or(b))
+        .filter(|b| !b.is_empty())
+        .and_then(|b| std::str::from_utf8(b).ok())
+        .filter(|b| body.ends_with(format!("--{}--\r\n", b).as_bytes()))
+        .ok_or_else(|| ClientError::EndpointBuildError {
+            source: anyhow::anyhow!("Multipart body was not built by a multipart form"),
+        })?;
+
+    HeaderValue::from_str(&format!("multipart/form-data; boundary={}", boundary))
+        .map_err(|e| ClientError::EndpointBuildError { source: e.into() })
+}
+
+/// Returns a new random boundary.
+fn boundary() -> String {
+    let random = || RandomState::new().build_hasher().finish();
+    format!("rustified-{:016x}{:016x}", random(), random())
+}
+
+/// Returns `true` if the given bytes contain the pattern.
+fn contains(data: &[u8], pattern: &[u8]) -> bool {
+    data.windows(pattern.len()).any(|w| w == pattern)
+}
+
+/// Escapes a name for use in a `Content-Disposition` parameter.
+fn escape(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
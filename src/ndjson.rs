@@ -0,0 +1,459 @@
+//! Contains helpers for encoding and decoding newline-delimited JSON (NDJSON)
+//! bodies, which are used by the [Ndjson][crate::codec::Ndjson] codec.
+//!
+//! Request bodies are encoded from a sequence of records, either directly or
+//! from a struct with a single field holding the sequence (which is what the
+//! derive macro produces for an endpoint with one body field). Each record is
+//! written as a single line of JSON.
+//!
+//! Response bodies can be decoded in full into a sequence, like a `Vec<T>`,
+//! using [EndpointResult::parse][crate::endpoint::EndpointResult::parse],
+//! iterated over line by line using [EndpointResult::lines][1] or streamed
+//! using [Endpoint::exec_lines][crate::endpoint::Endpoint::exec_lines]. Blank
+//! lines are skipped and a malformed line results in a
+//! [ClientError::ResponseParseError] containing its line number.
+//!
+//! # Example
+//! ```
+//! use rustified::{clients::reqwest::Client, Endpoint};
+//! use rustified_derive::Endpoint;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Deserialize, Serialize)]
+//! struct Document {
+//!     id: u64,
+//! }
+//!
+//! #[derive(Endpoint)]
+//! #[endpoint(
+//!     path = "_bulk",
+//!     method = "POST",
+//!     request_type = "NDJSON",
+//!     response_type = "NDJSON",
+//!     response = "Vec<Document>"
+//! )]
+//! struct Bulk {
+//!     documents: Vec<Document>,
+//! }
+//!
+//! # tokio_test::block_on(async {
+//! let client = Client::default("http://myapi.com");
+//! let endpoint = Bulk {
+//!     documents: vec![Document { id: 1 }, Document { id: 2 }],
+//! };
+//! if let Ok(result) = endpoint.exec(&client).await {
+//!     for document in result.lines() {
+//!         let document = document?;
+//!     }
+//! }
+//! # Ok::<(), rustified::errors::ClientError>(())
+//! # });
+//! ```
+//!
+//! [1]: crate::endpoint::EndpointResult::lines
+
+use std::{fmt::Display, marker::PhantomData, pin::Pin};
+
+use futures_core::Stream;
+use futures_util::{stream, StreamExt};
+use serde::{
+    de::{self, DeserializeOwned, DeserializeSeed, SeqAccess, Visitor},
+    ser::{self, Impossible, Serialize, SerializeSeq, SerializeStruct, SerializeTuple},
+    Deserializer, Serializer,
+};
+
+use crate::{client::ByteStream, errors::ClientError};
+
+/// A stream of records decoded from an NDJSON response body.
+pub type LineStream<T> = Pin<Box<dyn Stream<Item = Result<T, ClientError>> + Send>>;
+
+/// Encodes the given sequence of records as NDJSON.
+pub fn to_vec<T: Serialize>(object: &T) -> Result<Vec<u8>, ClientError> {
+    let mut out = Vec::new();
+    object
+        .serialize(LinesSerializer { out: &mut out })
+        .map_err(|e| ClientError::DataParseError { source: e.into() })?;
+    Ok(out)
+}
+
+/// Decodes the given NDJSON body into a sequence of records.
+pub fn from_slice<T: DeserializeOwned>(body: &[u8]) -> Result<T, ClientError> {
+    T::deserialize(LinesDeserializer {
+        lines: numbered_lines(body),
+    })
+    .map_err(|e| match e.line {
+        Some((number, line)) => parse_error(e.source.into(), number, &line),
+        None => ClientError::ResponseParseError {
+            source: e.source.into(),
+            content: String::from_utf8(body.to_vec()).ok(),
+            line: None,
+        },
+    })
+}
+
+/// Returns a [LineStream] which decodes the records of the given NDJSON body
+/// as they're received.
+pub fn stream<T: DeserializeOwned + Send + 'static>(body: ByteStream) -> LineStream<T> {
+    let state = (body, Vec::new(), 0, false);
+    Box::pin(stream::unfold(
+        state,
+        |(mut body, mut buffer, mut number, mut done)| async move {
+            loop {
+                if let Some(i) = buffer.iter().position(|b| *b == b'\n') {
+                    let line: Vec<u8> = buffer.drain(..=i).collect();
+                    number += 1;
+                    if let Some(line) = trim(&line[..i]) {
+                        let record = decode_line(line, number);
+                        return Some((record, (body, buffer, number, done)));
+                    }
+                } else if done {
+                    if buffer.is_empty() {
+                        return None;
+                    }
+                    let line = std::mem::take(&mut buffer);
+                    number += 1;
+                    if let Some(line) = trim(&line) {
+                        let record = decode_line(line, number);
+                        return Some((record, (body, buffer, number, done)));
+                    }
+                } else {
+                    match body.next().await {
+                        Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                        Some(Err(e)) => {
+                            done = true;
+                            buffer.clear();
+                            return Some((Err(e), (body, buffer, number, done)));
+                        }
+                        None => done = true,
+                    }
+                }
+            }
+        },
+    ))
+}
+
+/// An iterator over the records of an NDJSON response body, returned by
+/// [EndpointResult::lines][crate::endpoint::EndpointResult::lines].
+pub struct Lines<'a, T> {
+    lines: NumberedLines<'a>,
+    inner: PhantomData<T>,
+}
+
+impl<'a, T> Lines<'a, T> {
+    /// Returns a new [Lines] iterating over the given body.
+    pub fn new(body: &'a [u8]) -> Self {
+        Lines {
+            lines: numbered_lines(body),
+            inner: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for Lines<'_, T> {
+    type Item = Result<T, ClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.lines
+            .next()
+            .map(|(number, line)| decode_line(line, number))
+    }
+}
+
+type NumberedLines<'a> = Box<dyn Iterator<Item = (usize, &'a [u8])> + Send + 'a>;
+
+/// Returns the non-blank lines of the given body along with their line
+/// numbers, starting at one.
+fn numbered_lines(body: &[u8]) -> NumberedLines<'_> {
+    Box::new(
+        body.split(|b| *b == b'\n')
+            .enumerate()
+            .filter_map(|(i, line)| trim(line).map(|l| (i + 1, l))),
+    )
+}
+
+/// Strips a trailing carriage return from the given line and returns it, or
+/// [None] if it's blank.
+fn trim(line: &[u8]) -> Option<&[u8]> {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    match line.iter().all(u8::is_ascii_whitespace) {
+        true => None,
+        false => Some(line),
+    }
+}
+
+fn decode_line<T: DeserializeOwned>(line: &[u8], number: usize) -> Result<T, ClientError> {
+    serde_json::from_slice(line).map_err(|e| parse_error(e.into(), number, line))
+}
+
+fn parse_error(source: anyhow::Error, number: usize, line: &[u8]) -> ClientError {
+    ClientError::ResponseParseError {
+        source,
+        content: String::from_utf8(line.to_vec()).ok(),
+        line: Some(number),
+    }
+}
+
+/// Serializes a sequence of records, or a struct with a single field holding
+/// one, into one line of JSON per record.
+struct LinesSerializer<'a> {
+    out: &'a mut Vec<u8>,
+}
+
+/// Writes each element of a sequence as a line of JSON.
+struct LinesWriter<'a> {
+    out: &'a mut Vec<u8>,
+}
+
+/// Forwards the single field of a struct to a [LinesSerializer].
+struct FieldWriter<'a> {
+    out: &'a mut Vec<u8>,
+    written: bool,
+}
+
+fn unsupported() -> serde_json::Error {
+    ser::Error::custom("NDJSON bodies can only be encoded from a sequence of records")
+}
+
+macro_rules! unsupported {
+    ($($method:ident($($ty:ty),*)),* $(,)?) => {
+        $(
+            fn $method(self, $(_: $ty),*) -> Result<Self::Ok, Self::Error> {
+                Err(unsupported())
+            }
+        )*
+    };
+}
+
+impl<'a> Serializer for LinesSerializer<'a> {
+    type Ok = ();
+    type Error = serde_json::Error;
+    type SerializeSeq = LinesWriter<'a>;
+    type SerializeTuple = LinesWriter<'a>;
+    type SerializeTupleStruct = Impossible<(), serde_json::Error>;
+    type SerializeTupleVariant = Impossible<(), serde_json::Error>;
+    type SerializeMap = Impossible<(), serde_json::Error>;
+    type SerializeStruct = FieldWriter<'a>;
+    type SerializeStructVariant = Impossible<(), serde_json::Error>;
+
+    unsupported!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    );
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(LinesWriter { out: self.out })
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(LinesWriter { out: self.out })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(unsupported())
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(FieldWriter {
+            out: self.out,
+            written: false,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(unsupported())
+    }
+}
+
+impl SerializeSeq for LinesWriter<'_> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        serde_json::to_writer(&mut *self.out, value)?;
+        self.out.push(b'\n');
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl SerializeTuple for LinesWriter<'_> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl SerializeStruct for FieldWriter<'_> {
+    type Ok = ();
+    type Error = serde_json::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        if self.written {
+            return Err(ser::Error::custom(
+                "NDJSON bodies can only be encoded from a single field",
+            ));
+        }
+        self.written = true;
+        value.serialize(LinesSerializer {
+            out: &mut *self.out,
+        })
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+/// An error from deserializing an NDJSON body along with the line it occurred
+/// on, if any.
+#[derive(Debug)]
+struct LinesError {
+    source: serde_json::Error,
+    line: Option<(usize, Vec<u8>)>,
+}
+
+impl Display for LinesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.source.fmt(f)
+    }
+}
+
+impl std::error::Error for LinesError {}
+
+impl de::Error for LinesError {
+    fn custom<T: Display>(msg: T) -> Self {
+        LinesError {
+            source: de::Error::custom(msg),
+            line: None,
+        }
+    }
+}
+
+/// Deserializes the lines of an NDJSON body as a sequence of records.
+struct LinesDeserializer<'a> {
+    lines: NumberedLines<'a>,
+}
+
+impl<'de> Deserializer<'de> for LinesDeserializer<'de> {
+    type Error = LinesError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> SeqAccess<'de> for LinesDeserializer<'de> {
+    type Error = LinesError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        let (number, line) = match self.lines.next() {
+            Some(l) => l,
+            None => return Ok(None),
+        };
+        let mut de = serde_json::Deserializer::from_slice(line);
+        seed.deserialize(&mut de)
+            .and_then(|v| de.end().map(|_| v))
+            .map(Some)
+            .map_err(|e| LinesError {
+                source: e,
+                line: Some((number, line.to_vec())),
+            })
+    }
+}
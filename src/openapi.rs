@@ -0,0 +1,412 @@
+//! Contains helpers for generating an [OpenAPI 3.1][1] document from
+//! endpoints.
+//!
+//! With the `openapi` feature enabled the derive macro implements [Describe]
+//! for each endpoint, which returns the path, method, parameters, request body
+//! and responses of its operations. These are collected into a document with
+//! [OpenApi]:
+//!
+//! ```ignore
+//! use rustified::openapi::OpenApi;
+//!
+//! let spec = OpenApi::new("GitHub", "1.0.0")
+//!     .server("https://api.github.com")
+//!     .endpoint::<GetUser>()
+//!     .endpoint::<Repos>()
+//!     .to_string();
+//! ```
+//!
+//! Schemas are taken from the [Schema] trait, which is implemented for the
+//! common types of the standard library and can be derived for custom types
+//! with `#[derive(Schema)]`. Types which don't implement it are described by an
+//! empty schema, which allows any value. The summary and description of an
+//! operation are taken from the doc comments of the endpoint.
+//!
+//! [1]: https://spec.openapis.org/oas/v3.1.0
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    marker::PhantomData,
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::{enums::RequestMethod, query::QueryStyle};
+
+pub use rustified_derive::Schema;
+
+/// A type which can be described by a [JSON Schema][1].
+///
+/// [1]: https://json-schema.org/
+pub trait Schema {
+    /// Returns the JSON Schema describing this type.
+    fn schema() -> Value;
+}
+
+macro_rules! schema {
+    ($schema:tt => $($ty:ty),*) => {
+        $(
+            impl Schema for $ty {
+                fn schema() -> Value {
+                    json!($schema)
+                }
+            }
+        )*
+    };
+}
+
+schema!({ "type": "boolean" } => bool);
+schema!({ "type": "integer" } => i8, i16, i32, i64, i128, isize);
+schema!({ "type": "integer", "minimum": 0 } => u8, u16, u32, u64, u128, usize);
+schema!({ "type": "number" } => f32, f64);
+schema!({ "type": "string" } => str, String, char);
+schema!({ "type": "string", "format": "binary" } => bytes::Bytes, crate::multipart::File);
+schema!({ "type": "null" } => ());
+schema!({} => Value);
+
+impl<T: Schema + ?Sized> Schema for &T {
+    fn schema() -> Value {
+        T::schema()
+    }
+}
+
+impl<T: Schema + ?Sized> Schema for Box<T> {
+    fn schema() -> Value {
+        T::schema()
+    }
+}
+
+impl<T: Schema> Schema for Option<T> {
+    /// Returns the schema of `T` which also allows `null`.
+    fn schema() -> Value {
+        let mut schema = T::schema();
+        if schema == json!({}) {
+            return schema;
+        }
+        match schema.get_mut("type") {
+            Some(ty @ Value::String(_)) => {
+                if *ty != "null" {
+                    *ty = json!([ty.take(), "null"]);
+                }
+            }
+            Some(Value::Array(types)) => {
+                if !types.contains(&json!("null")) {
+                    types.push(json!("null"));
+                }
+            }
+            _ => return json!({ "anyOf": [schema, { "type": "null" }] }),
+        }
+        schema
+    }
+}
+
+macro_rules! schema_seq {
+    ($($ty:ident),*) => {
+        $(
+            impl<T: Schema> Schema for $ty<T> {
+                fn schema() -> Value {
+                    json!({ "type": "array", "items": T::schema() })
+                }
+            }
+        )*
+    };
+}
+
+schema_seq!(Vec, HashSet, BTreeSet);
+
+impl<T: Schema> Schema for [T] {
+    fn schema() -> Value {
+        Vec::<T>::schema()
+    }
+}
+
+macro_rules! schema_map {
+    ($($ty:ident),*) => {
+        $(
+            impl<V: Schema> Schema for $ty<String, V> {
+                fn schema() -> Value {
+                    json!({ "type": "object", "additionalProperties": V::schema() })
+                }
+            }
+        )*
+    };
+}
+
+schema_map!(HashMap, BTreeMap);
+
+/// A type which describes the operations of an [Endpoint][crate::Endpoint].
+///
+/// Implemented by the derive macro when the `openapi` feature is enabled.
+pub trait Describe {
+    /// Returns the operations of this endpoint, one for each variant of an
+    /// enum.
+    fn operations() -> Vec<Operation>;
+}
+
+/// A single operation of an API.
+#[derive(Clone, Debug)]
+pub struct Operation {
+    /// A unique name for the operation, taken from the name of the endpoint.
+    pub id: String,
+    /// The path template, as in `/users/{name}`.
+    pub path: String,
+    pub method: RequestMethod,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub parameters: Vec<Parameter>,
+    pub request: Option<Content>,
+    pub responses: Vec<Response>,
+}
+
+/// A parameter of an [Operation].
+#[derive(Clone, Debug)]
+pub struct Parameter {
+    pub name: String,
+    pub location: Location,
+    pub required: bool,
+    pub schema: Value,
+    /// The style of a query parameter, if it's not the default.
+    pub style: Option<QueryStyle>,
+}
+
+/// Where a [Parameter] is sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Location {
+    Path,
+    Query,
+    Header,
+}
+
+/// A request or response body.
+#[derive(Clone, Debug)]
+pub struct Content {
+    pub media_type: String,
+    pub schema: Value,
+}
+
+/// A response of an [Operation].
+#[derive(Clone, Debug)]
+pub struct Response {
+    /// The status code, or [None] for the default response.
+    pub status: Option<u16>,
+    pub content: Option<Content>,
+}
+
+/// Builds an [OpenAPI 3.1][1] document from a list of endpoints.
+///
+/// Operations are grouped by their path and the document is serialized with
+/// sorted keys, which keeps it stable when endpoints are reordered. Operations
+/// using a method which OpenAPI doesn't support, like `LIST`, are left out.
+///
+/// [1]: https://spec.openapis.org/oas/v3.1.0
+#[derive(Clone, Debug)]
+pub struct OpenApi {
+    title: String,
+    version: String,
+    servers: Vec<String>,
+    operations: Vec<Operation>,
+}
+
+impl OpenApi {
+    /// Returns a new document for the API with the given title and version.
+    pub fn new(title: &str, version: &str) -> Self {
+        OpenApi {
+            title: title.to_string(),
+            version: version.to_string(),
+            servers: Vec::new(),
+            operations: Vec::new(),
+        }
+    }
+
+    /// Adds the URL of a server hosting the API.
+    pub fn server(mut self, url: &str) -> Self {
+        self.servers.push(url.to_string());
+        self
+    }
+
+    /// Adds the operations of the given endpoint.
+    pub fn endpoint<E: Describe>(mut self) -> Self {
+        self.operations.extend(E::operations());
+        self
+    }
+
+    /// Adds a single operation.
+    pub fn operation(mut self, operation: Operation) -> Self {
+        self.operations.push(operation);
+        self
+    }
+
+    /// Returns the document as a JSON value.
+    pub fn to_value(&self) -> Value {
+        let mut paths = Map::new();
+        for op in &self.operations {
+            let method = match method_key(&op.method) {
+                Some(m) => m,
+                None => continue,
+            };
+            let item = paths
+                .entry(op.path.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            item[method] = operation(op);
+        }
+
+        let mut doc = json!({
+            "openapi": "3.1.0",
+            "info": { "title": self.title, "version": self.version },
+            "paths": paths,
+        });
+        if !self.servers.is_empty() {
+            doc["servers"] = self
+                .servers
+                .iter()
+                .map(|url| json!({ "url": url }))
+                .collect();
+        }
+        doc
+    }
+}
+
+impl std::fmt::Display for OpenApi {
+    /// Formats the document as pretty printed JSON.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let doc = serde_json::to_string_pretty(&self.to_value()).map_err(|_| std::fmt::Error)?;
+        f.write_str(&doc)
+    }
+}
+
+fn method_key(method: &RequestMethod) -> Option<&'static str> {
+    match method {
+        RequestMethod::DELETE => Some("delete"),
+        RequestMethod::GET => Some("get"),
+        RequestMethod::HEAD => Some("head"),
+        RequestMethod::OPTIONS => Some("options"),
+        RequestMethod::PATCH => Some("patch"),
+        RequestMethod::POST => Some("post"),
+        RequestMethod::PUT => Some("put"),
+        RequestMethod::TRACE => Some("trace"),
+        RequestMethod::CONNECT | RequestMethod::LIST => None,
+    }
+}
+
+fn operation(op: &Operation) -> Value {
+    let mut value = json!({ "operationId": op.id });
+    if let Some(s) = &op.summary {
+        value["summary"] = json!(s);
+    }
+    if let Some(d) = &op.description {
+        value["description"] = json!(d);
+    }
+    if !op.parameters.is_empty() {
+        value["parameters"] = op.parameters.iter().map(parameter).collect();
+    }
+    if let Some(c) = &op.request {
+        value["requestBody"] = json!({ "required": true, "content": content(c) });
+    }
+
+    let mut responses = Map::new();
+    for r in &op.responses {
+        let key = r.status.map_or("default".to_string(), |s| s.to_string());
+        let mut response = json!({ "description": reason(r.status) });
+        if let Some(c) = &r.content {
+            response["content"] = content(c);
+        }
+        responses.insert(key, response);
+    }
+    value["responses"] = Value::Object(responses);
+    value
+}
+
+fn parameter(p: &Parameter) -> Value {
+    let location = match p.location {
+        Location::Path => "path",
+        Location::Query => "query",
+        Location::Header => "header",
+    };
+    let mut value = json!({
+        "name": p.name,
+        "in": location,
+        "required": p.required,
+        "schema": p.schema,
+    });
+    match p.style {
+        Some(QueryStyle::Comma) => {
+            value["style"] = json!("form");
+            value["explode"] = json!(false);
+        }
+        Some(QueryStyle::DeepObject) => {
+            value["style"] = json!("deepObject");
+            value["explode"] = json!(true);
+        }
+        _ => {}
+    }
+    value
+}
+
+fn content(c: &Content) -> Value {
+    let mut value = Map::new();
+    value.insert(c.media_type.clone(), json!({ "schema": c.schema }));
+    Value::Object(value)
+}
+
+fn reason(status: Option<u16>) -> String {
+    status
+        .and_then(|s| http::StatusCode::from_u16(s).ok())
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("Default response")
+        .to_string()
+}
+
+/// Returns the schema of an object with the given properties, each of which is
+/// given along with whether it's required.
+pub fn object(properties: Vec<(&str, Value, bool)>) -> Value {
+    let required = properties
+        .iter()
+        .filter(|(_, _, r)| *r)
+        .map(|(n, _, _)| json!(n))
+        .collect::<Vec<_>>();
+    let properties = properties
+        .into_iter()
+        .map(|(n, s, _)| (n.to_string(), s))
+        .collect::<Map<_, _>>();
+    let mut value = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        value["required"] = Value::Array(required);
+    }
+    value
+}
+
+/// Used by the derive macro for taking the schema of types which implement
+/// [Schema] and falling back to an empty schema for all other types.
+#[doc(hidden)]
+pub mod __private {
+    use super::*;
+
+    pub struct SchemaOf<T: ?Sized>(PhantomData<T>);
+
+    impl<T: ?Sized> SchemaOf<T> {
+        #[allow(clippy::new_without_default)]
+        pub fn new() -> Self {
+            SchemaOf(PhantomData)
+        }
+    }
+
+    pub trait ViaSchema {
+        fn schema(&self) -> Value;
+    }
+
+    impl<T: Schema + ?Sized> ViaSchema for &SchemaOf<T> {
+        fn schema(&self) -> Value {
+            T::schema()
+        }
+    }
+
+    pub trait ViaAny {
+        fn schema(&self) -> Value;
+    }
+
+    impl<T: ?Sized> ViaAny for SchemaOf<T> {
+        fn schema(&self) -> Value {
+            json!({})
+        }
+    }
+}
@@ -0,0 +1,585 @@
+//! Contains the [Paginated] extension of [Endpoint] for endpoints whose results
+//! are split over several pages.
+//!
+//! The derive macro implements [Paginated] when given the `paginate` parameter,
+//! which declares how the next page is requested:
+//!
+//! * `paginate(cursor = "next_cursor", param = "cursor")` reads a cursor from
+//!   the given field of the response body and sends it in the given query
+//!   parameter. The last page has no cursor.
+//! * `paginate(offset = "offset", limit = "limit")` increases the offset query
+//!   parameter by the number of items in each page.
+//! * `paginate(page = "page", limit = "per_page")` increases the page number
+//!   query parameter by one, starting from `1` when it's not set.
+//! * `paginate(link = true)` follows the URL of the `rel="next"` link in the
+//!   [RFC 8288][1] `Link` header of each response. Pagination ends when the
+//!   link points to another origin, since the headers of the request would be
+//!   sent along to it, or back to the page itself.
+//!
+//! The offset and page strategies end on an empty page, or on a page holding
+//! fewer items than the value of the optional `limit` query parameter.
+//!
+//! The items of a page are the response body itself, which is expected to be
+//! a list, or the field given with `items = "data"`. Their type is taken from
+//! a `Vec` response and otherwise needs to be given with `item = User`. Fields
+//! of nested objects are given in the form of `meta.next_cursor`.
+//!
+//! # Example
+//! ```
+//! use futures_util::TryStreamExt;
+//! use rustified::{clients::reqwest::Client, paginate::Paginated};
+//! use rustified_derive::Endpoint;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct User {
+//!     name: String,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct UserPage {
+//!     data: Vec<User>,
+//!     next_cursor: Option<String>,
+//! }
+//!
+//! #[derive(Endpoint)]
+//! #[endpoint(
+//!     path = "users",
+//!     response = "UserPage",
+//!     paginate(cursor = "next_cursor", param = "cursor", items = "data", item = User)
+//! )]
+//! struct ListUsers {}
+//!
+//! # tokio_test::block_on(async {
+//! let client = Client::default("http://myapi.com");
+//! let mut users = ListUsers {}.exec_items(&client)?;
+//! while let Some(user) = users.try_next().await? {
+//!     println!("{}", user.name);
+//! }
+//! # Ok::<(), rustified::errors::ClientError>(())
+//! # });
+//! ```
+//!
+//! [1]: https://www.rfc-editor.org/rfc/rfc8288
+
+use std::pin::Pin;
+
+use futures_core::Stream;
+use futures_util::{stream, StreamExt};
+use http::{header::LINK, HeaderMap, Method, Request, Response, Uri};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use url::form_urlencoded;
+
+#[cfg(feature = "blocking")]
+use crate::blocking::client::Client as BlockingClient;
+use crate::{
+    client::Client,
+    codec::Codec,
+    endpoint::{Endpoint, EndpointResult},
+    errors::ClientError,
+};
+
+/// A stream of the pages of a [Paginated] endpoint returned by
+/// [Paginated::exec_pages].
+pub type PageStream<'a, E> = Pin<
+    Box<
+        dyn Stream<
+                Item = Result<
+                    EndpointResult<<E as Endpoint>::Response, <E as Endpoint>::ResponseCodec>,
+                    ClientError,
+                >,
+            > + Send
+            + 'a,
+    >,
+>;
+
+/// A stream of the items of a [Paginated] endpoint returned by
+/// [Paginated::exec_items].
+pub type ItemStream<'a, T> = Pin<Box<dyn Stream<Item = Result<T, ClientError>> + Send + 'a>>;
+
+/// How the next page of a [Paginated] endpoint is requested.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// Sends the cursor found in `field` of the response body in the `param`
+    /// query parameter.
+    Cursor { field: String, param: String },
+    /// Increases the `param` query parameter by the number of items in each
+    /// page.
+    Offset { param: String },
+    /// Increases the `param` query parameter by one for each page.
+    Page { param: String },
+    /// Follows the `rel="next"` link of the `Link` header.
+    Link,
+}
+
+/// Describes how a [Paginated] endpoint requests its pages and where the items
+/// of each page are found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pagination {
+    pub strategy: Strategy,
+    /// The query parameter holding the number of items per page, which ends
+    /// the offset and page strategies on a page holding fewer items.
+    pub limit: Option<String>,
+    /// The field of the response body holding the items of a page, or [None]
+    /// if the body itself is the list of items.
+    pub items: Option<String>,
+}
+
+impl Pagination {
+    /// Returns a [Pagination] using [Strategy::Cursor].
+    pub fn cursor(field: &str, param: &str) -> Self {
+        Pagination::new(Strategy::Cursor {
+            field: field.to_string(),
+            param: param.to_string(),
+        })
+    }
+
+    /// Returns a [Pagination] using [Strategy::Offset].
+    pub fn offset(param: &str) -> Self {
+        Pagination::new(Strategy::Offset {
+            param: param.to_string(),
+        })
+    }
+
+    /// Returns a [Pagination] using [Strategy::Page].
+    pub fn page(param: &str) -> Self {
+        Pagination::new(Strategy::Page {
+            param: param.to_string(),
+        })
+    }
+
+    /// Returns a [Pagination] using [Strategy::Link].
+    pub fn link() -> Self {
+        Pagination::new(Strategy::Link)
+    }
+
+    /// Sets the query parameter holding the number of items per page.
+    pub fn limit(mut self, param: &str) -> Self {
+        self.limit = Some(param.to_string());
+        self
+    }
+
+    /// Sets the field of the response body holding the items of a page.
+    pub fn items(mut self, field: &str) -> Self {
+        self.items = Some(field.to_string());
+        self
+    }
+
+    fn new(strategy: Strategy) -> Self {
+        Pagination {
+            strategy,
+            limit: None,
+            items: None,
+        }
+    }
+
+    /// Returns the URI of the page following the given response to a request
+    /// for `uri`, or [None] if it was the last page.
+    ///
+    /// The response body is decoded using the [Codec] `C` when needed.
+    pub fn next<C: Codec>(
+        &self,
+        uri: &Uri,
+        response: &Response<Vec<u8>>,
+    ) -> Result<Option<Uri>, ClientError> {
+        match &self.strategy {
+            Strategy::Link => next_link(uri, response.headers()),
+            Strategy::Cursor { field, param } => {
+                let body = C::decode::<Value>(response.body())?;
+                let cursor = match lookup(&body, field) {
+                    None | Some(Value::Null) => return Ok(None),
+                    Some(Value::String(s)) => s.clone(),
+                    Some(v) => v.to_string(),
+                };
+
+                // Stop when the cursor doesn't change, which would repeat the
+                // same page forever
+                if cursor.is_empty() || query_value(uri, param).as_deref() == Some(&cursor) {
+                    return Ok(None);
+                }
+                set_query(uri, param, &cursor).map(Some)
+            }
+            Strategy::Offset { param } | Strategy::Page { param } => {
+                let body = C::decode::<Value>(response.body())?;
+                let count = self.item_values(&body)?.len() as u64;
+                let limit = self
+                    .limit
+                    .as_ref()
+                    .and_then(|l| query_value(uri, l))
+                    .and_then(|l| l.parse::<u64>().ok());
+                if count == 0 || limit.is_some_and(|l| count < l) {
+                    return Ok(None);
+                }
+
+                let current = query_value(uri, param).and_then(|v| v.parse::<u64>().ok());
+                let next = match &self.strategy {
+                    Strategy::Offset { .. } => current.unwrap_or(0) + count,
+                    _ => current.unwrap_or(1) + 1,
+                };
+                set_query(uri, param, &next.to_string()).map(Some)
+            }
+        }
+    }
+
+    /// Decodes the items of the given page using the [Codec] `C`.
+    pub fn page_items<T: DeserializeOwned, C: Codec>(
+        &self,
+        response: &Response<Vec<u8>>,
+    ) -> Result<Vec<T>, ClientError> {
+        if self.items.is_none() {
+            return C::decode_response(response);
+        }
+        let body = C::decode::<Value>(response.body())?;
+        let items = Value::Array(self.item_values(&body)?.to_vec());
+        serde_json::from_value(items).map_err(|e| ClientError::ResponseParseError {
+            source: e.into(),
+            content: String::from_utf8(response.body().to_vec()).ok(),
+            line: None,
+        })
+    }
+
+    /// Returns the list of items in the given body, failing if it isn't a list.
+    fn item_values<'a>(&self, body: &'a Value) -> Result<&'a [Value], ClientError> {
+        let items = match &self.items {
+            Some(field) => lookup(body, field),
+            None => Some(body),
+        };
+        match items {
+            Some(Value::Array(items)) => Ok(items),
+            _ => Err(ClientError::ResponseParseError {
+                source: anyhow::anyhow!(
+                    "Expected a list of items in {}",
+                    self.items.as_deref().unwrap_or("the response body")
+                ),
+                content: Some(body.to_string()),
+                line: None,
+            }),
+        }
+    }
+}
+
+/// An [Endpoint] whose results are split over several pages, see
+/// [crate::paginate].
+pub trait Paginated: Endpoint {
+    /// The type of the items held by each page.
+    type Item: DeserializeOwned + Send;
+
+    /// Returns how the pages of this endpoint are requested.
+    fn pagination(&self) -> Pagination;
+
+    /// Executes the Endpoint using the given [Client] and returns a stream of
+    /// its pages, each of which is requested once the previous one is
+    /// received.
+    ///
+    /// The stream ends after the last page or the first error. Response
+    /// [MiddleWare][crate::endpoint::MiddleWare] is not applied to pages.
+    #[instrument(skip(self, client), err)]
+    fn exec_pages<'a>(
+        &'a self,
+        client: &'a impl Client,
+    ) -> Result<PageStream<'a, Self>, ClientError> {
+        debug!("Executing endpoint");
+
+        let pager = Pager::new(self, client.base())?;
+        Ok(Box::pin(stream::unfold(
+            pager,
+            move |mut pager| async move {
+                let req = pager.request()?;
+                let uri = req.uri().clone();
+                let result = match crate::endpoint::exec(client, self, req).await {
+                    Ok(resp) => pager
+                        .advance::<Self::ResponseCodec>(&uri, &resp)
+                        .map(|_| EndpointResult::new(resp)),
+                    Err(e) => Err(e),
+                };
+                Some((result, pager))
+            },
+        )))
+    }
+
+    /// Executes the Endpoint using the given [Client] and returns a stream of
+    /// the items of all of its pages, see [Paginated::exec_pages].
+    #[instrument(skip(self, client), err)]
+    fn exec_items<'a>(
+        &'a self,
+        client: &'a impl Client,
+    ) -> Result<ItemStream<'a, Self::Item>, ClientError>
+    where
+        Self::Item: 'a,
+    {
+        let pagination = self.pagination();
+        let pages = self.exec_pages(client)?.map(move |page| {
+            page.and_then(|p| pagination.page_items::<Self::Item, Self::ResponseCodec>(&p.response))
+        });
+        Ok(Box::pin(pages.flat_map(|items| {
+            stream::iter(match items {
+                Ok(items) => items.into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            })
+        })))
+    }
+
+    /// Executes the Endpoint using the given [Client] and returns an iterator
+    /// over its pages, see [Paginated::exec_pages].
+    #[cfg(feature = "blocking")]
+    #[instrument(skip(self, client), err)]
+    fn exec_pages_block<'a, C: BlockingClient>(
+        &'a self,
+        client: &'a C,
+    ) -> Result<Pages<'a, Self, C>, ClientError> {
+        debug!("Executing endpoint");
+
+        Ok(Pages {
+            endpoint: self,
+            client,
+            pager: Pager::new(self, client.base())?,
+        })
+    }
+
+    /// Executes the Endpoint using the given [Client] and returns an iterator
+    /// over the items of all of its pages, see [Paginated::exec_pages].
+    #[cfg(feature = "blocking")]
+    #[instrument(skip(self, client), err)]
+    fn exec_items_block<'a, C: BlockingClient>(
+        &'a self,
+        client: &'a C,
+    ) -> Result<Items<'a, Self, C>, ClientError> {
+        Ok(Items {
+            pagination: self.pagination(),
+            pages: self.exec_pages_block(client)?,
+            items: Vec::new().into_iter(),
+        })
+    }
+}
+
+/// An iterator over the pages of a [Paginated] endpoint returned by
+/// [Paginated::exec_pages_block].
+#[cfg(feature = "blocking")]
+pub struct Pages<'a, E: Paginated, C: BlockingClient> {
+    endpoint: &'a E,
+    client: &'a C,
+    pager: Pager,
+}
+
+#[cfg(feature = "blocking")]
+impl<E: Paginated, C: BlockingClient> Iterator for Pages<'_, E, C> {
+    type Item = Result<EndpointResult<E::Response, E::ResponseCodec>, ClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let req = self.pager.request()?;
+        let uri = req.uri().clone();
+        Some(
+            crate::endpoint::exec_block(self.client, self.endpoint, req).and_then(|resp| {
+                self.pager.advance::<E::ResponseCodec>(&uri, &resp)?;
+                Ok(EndpointResult::new(resp))
+            }),
+        )
+    }
+}
+
+/// An iterator over the items of a [Paginated] endpoint returned by
+/// [Paginated::exec_items_block].
+#[cfg(feature = "blocking")]
+pub struct Items<'a, E: Paginated, C: BlockingClient> {
+    pagination: Pagination,
+    pages: Pages<'a, E, C>,
+    items: std::vec::IntoIter<E::Item>,
+}
+
+#[cfg(feature = "blocking")]
+impl<E: Paginated, C: BlockingClient> Iterator for Items<'_, E, C> {
+    type Item = Result<E::Item, ClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.items.next() {
+                return Some(Ok(item));
+            }
+            let page = match self.pages.next()? {
+                Ok(page) => page,
+                Err(e) => return Some(Err(e)),
+            };
+            match self
+                .pagination
+                .page_items::<E::Item, E::ResponseCodec>(&page.response)
+            {
+                Ok(items) => self.items = items.into_iter(),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Holds the request of the next page of a [Paginated] endpoint.
+struct Pager {
+    pagination: Pagination,
+    method: Method,
+    headers: HeaderMap,
+    body: Vec<u8>,
+    next: Option<Uri>,
+}
+
+impl Pager {
+    /// Returns a [Pager] whose first request is the request of the endpoint.
+    fn new<E: Paginated>(endpoint: &E, base: &str) -> Result<Self, ClientError> {
+        let (parts, body) = endpoint.request(base)?.into_parts();
+        Ok(Pager {
+            pagination: endpoint.pagination(),
+            method: parts.method,
+            headers: parts.headers,
+            body,
+            next: Some(parts.uri),
+        })
+    }
+
+    /// Returns the request of the next page, or [None] after the last page.
+    fn request(&mut self) -> Option<Request<Vec<u8>>> {
+        let uri = self.next.take()?;
+        debug!("Requesting page {}", uri);
+
+        let mut req = Request::new(self.body.clone());
+        *req.method_mut() = self.method.clone();
+        *req.uri_mut() = uri;
+        *req.headers_mut() = self.headers.clone();
+        Some(req)
+    }
+
+    /// Determines the request of the page following the given response.
+    fn advance<C: Codec>(
+        &mut self,
+        uri: &Uri,
+        response: &Response<Vec<u8>>,
+    ) -> Result<(), ClientError> {
+        self.next = self.pagination.next::<C>(uri, response)?;
+        Ok(())
+    }
+}
+
+/// Returns the value at the given dot-separated path of fields in a body.
+fn lookup<'a>(body: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(body, |value, field| value.get(field))
+}
+
+/// Returns the decoded value of the given query parameter of a URI.
+fn query_value(uri: &Uri, name: &str) -> Option<String> {
+    form_urlencoded::parse(uri.query()?.as_bytes())
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.into_owned())
+}
+
+/// Returns the given URI with the value of a query parameter replaced, leaving
+/// all other parameters as they are.
+fn set_query(uri: &Uri, name: &str, value: &str) -> Result<Uri, ClientError> {
+    let mut pairs = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .filter(|p| !form_urlencoded::parse(p.as_bytes()).any(|(k, _)| k == name))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    pairs.push(
+        form_urlencoded::Serializer::new(String::new())
+            .append_pair(name, value)
+            .finish(),
+    );
+
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(
+        format!("{}?{}", uri.path(), pairs.join("&"))
+            .parse()
+            .map_err(|e| ClientError::UrlBuildError { source: e })?,
+    );
+    Uri::from_parts(parts).map_err(|e| ClientError::GenericError { source: e.into() })
+}
+
+/// Returns the target of the `rel="next"` link in the given headers, resolved
+/// against the URI of the request.
+///
+/// Targets with another scheme, host or port than the request are ignored, as
+/// are targets pointing back to the request itself.
+fn next_link(uri: &Uri, headers: &HeaderMap) -> Result<Option<Uri>, ClientError> {
+    let target = headers
+        .get_all(LINK)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(links)
+        .find(|(_, rels)| {
+            rels.split_whitespace()
+                .any(|r| r.eq_ignore_ascii_case("next"))
+        })
+        .map(|(target, _)| target);
+    let target = match target {
+        Some(t) => t,
+        None => return Ok(None),
+    };
+
+    let base =
+        url::Url::parse(&uri.to_string()).map_err(|e| ClientError::UrlParseError { source: e })?;
+    let next = base
+        .join(target)
+        .map_err(|e| ClientError::UrlParseError { source: e })?;
+
+    // Stop at links to another origin, which would receive the headers of the
+    // request, and at links to the same page, which would repeat it forever
+    if next.origin() != base.origin() {
+        warn!("Not following the link to {} on another origin", next);
+        return Ok(None);
+    }
+    if next == base {
+        return Ok(None);
+    }
+    next.as_str()
+        .parse()
+        .map(Some)
+        .map_err(|e| ClientError::UrlBuildError { source: e })
+}
+
+/// Parses the links of a `Link` header value into their target and `rel`
+/// parameter, as in `<https://api.com/users?page=2>; rel="next"`.
+fn links(value: &str) -> Vec<(&str, &str)> {
+    let mut result = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('<') {
+        let end = match rest[start..].find('>') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let target = &rest[start + 1..end];
+
+        // Parameters continue until the next link
+        let params = split_unquoted(&rest[end + 1..], ',')[0];
+        let rel = split_unquoted(params, ';')
+            .into_iter()
+            .filter_map(|p| p.split_once('='))
+            .find(|(k, _)| k.trim().eq_ignore_ascii_case("rel"))
+            .map(|(_, v)| v.trim().trim_matches('"'))
+            .unwrap_or_default();
+        result.push((target, rel));
+        rest = &rest[end + 1 + params.len()..];
+    }
+    result
+}
+
+/// Splits a header value on the given separator, ignoring separators within
+/// quoted strings such as `title="a, b"`.
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut quoted, mut escaped) = (0, false, false);
+    for (i, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => {
+                parts.push(&value[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
@@ -1 +1,57 @@
+pub use ::http;
 pub use serde;
+pub use serde_json;
+
+pub use crate::{__blocking as blocking, __openapi as openapi};
+
+use crate::{endpoint::Variant, errors::ClientError};
+
+/// Returns the error for a response of an enum endpoint whose [Variant] has
+/// no response type.
+pub fn variant_error(response: &http::Response<Vec<u8>>) -> ClientError {
+    let source = match Variant::of(response) {
+        Some(Variant(v)) => anyhow::anyhow!("The {} variant has no response type", v),
+        None => anyhow::anyhow!("The response isn't tagged with the variant of the endpoint"),
+    };
+    ClientError::ResponseParseError {
+        source,
+        content: String::from_utf8(response.body().to_vec()).ok(),
+        line: None,
+    }
+}
+
+/// Expands to the given items when the `blocking` feature is enabled.
+#[cfg(feature = "blocking")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __blocking {
+    ($($item:item)*) => {
+        $($item)*
+    };
+}
+
+/// Expands to the given items when the `blocking` feature is enabled.
+#[cfg(not(feature = "blocking"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __blocking {
+    ($($item:item)*) => {};
+}
+
+/// Expands to the given items when the `openapi` feature is enabled.
+#[cfg(feature = "openapi")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __openapi {
+    ($($item:item)*) => {
+        $($item)*
+    };
+}
+
+/// Expands to the given items when the `openapi` feature is enabled.
+#[cfg(not(feature = "openapi"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __openapi {
+    ($($item:item)*) => {};
+}
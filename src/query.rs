@@ -0,0 +1,506 @@
+//! Contains the serializer used for building URL query strings.
+//!
+//! Query strings are serialized from a struct or map, each field of which is
+//! added as a parameter named after the field. Fields holding [None] or `()`
+//! are left out. How sequences and nested maps or structs are added depends on
+//! the [QueryStyle] used:
+//!
+//! | Style                     | Sequence            | Map or struct     |
+//! |---------------------------|---------------------|-------------------|
+//! | [QueryStyle::Repeat]      | `tag=a&tag=b`       | not supported     |
+//! | [QueryStyle::Comma]       | `tag=a,b`           | `filter=name,x`   |
+//! | [QueryStyle::Brackets]    | `tag[]=a&tag[]=b`   | `filter[name]=x`  |
+//! | [QueryStyle::DeepObject]  | `tag[0]=a&tag[1]=b` | `filter[name]=x`  |
+//!
+//! The style is chosen for a whole endpoint with `query(style = comma)` and for
+//! a single field with `#[endpoint(query(style = comma))]`. Fields of a
+//! manually serialized struct can be given a style with the serialization
+//! functions in this module, as in
+//! `#[serde(serialize_with = "rustified::query::comma")]`.
+
+use std::fmt::Display;
+
+use serde::{
+    ser::{self, Impossible},
+    Serialize, Serializer,
+};
+use url::form_urlencoded::byte_serialize;
+
+use crate::errors::ClientError;
+
+/// The style used for serializing sequences and maps into a query string.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QueryStyle {
+    /// Repeats the parameter for each element of a sequence. Maps and structs
+    /// can only be nested with the other styles. This is the default.
+    #[default]
+    Repeat,
+    /// Joins the elements of a sequence, or the keys and values of a map, into
+    /// a single comma separated value.
+    Comma,
+    /// Appends `[]` to the parameter for each element of a sequence and the key
+    /// in brackets for each entry of a map.
+    Brackets,
+    /// Appends the index in brackets to the parameter for each element of a
+    /// sequence and the key in brackets for each entry of a map.
+    DeepObject,
+}
+
+impl QueryStyle {
+    /// The name of the newtype struct which marks a value serialized with this
+    /// style.
+    fn marker(self) -> &'static str {
+        match self {
+            QueryStyle::Repeat => "__rustified_query_repeat",
+            QueryStyle::Comma => "__rustified_query_comma",
+            QueryStyle::Brackets => "__rustified_query_brackets",
+            QueryStyle::DeepObject => "__rustified_query_deep_object",
+        }
+    }
+
+    fn from_marker(name: &str) -> Option<Self> {
+        [
+            QueryStyle::Repeat,
+            QueryStyle::Comma,
+            QueryStyle::Brackets,
+            QueryStyle::DeepObject,
+        ]
+        .iter()
+        .copied()
+        .find(|s| s.marker() == name)
+    }
+}
+
+/// Serializes an object into a query string, using the given [QueryStyle] for
+/// all sequences and maps which aren't given a style of their own.
+#[instrument(skip(object), err)]
+pub fn to_string(object: &impl Serialize, style: QueryStyle) -> Result<String, ClientError> {
+    let mut pairs = Vec::new();
+    let result = object
+        .serialize(NodeSerializer)
+        .and_then(|node| match node {
+            Node::Map(entries) => entries
+                .into_iter()
+                .try_for_each(|(k, v)| write(&mut pairs, encode(&k), v, style)),
+            Node::Seq(items) => items
+                .into_iter()
+                .try_for_each(|item| write_pair(&mut pairs, item, style)),
+            Node::Skip => Ok(()),
+            _ => Err(QueryError::new(
+                "Query strings can only be serialized from a struct, map or sequence of pairs",
+            )),
+        });
+    result.map_err(|e| ClientError::UrlQueryParseError { source: e.into() })?;
+
+    Ok(pairs
+        .into_iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&"))
+}
+
+macro_rules! style_fn {
+    ($($(#[$doc:meta])* $name:ident => $style:ident),* $(,)?) => {
+        $(
+            $(#[$doc])*
+            pub fn $name<T: ?Sized + Serialize, S: Serializer>(
+                value: &T,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                serializer.serialize_newtype_struct(QueryStyle::$style.marker(), value)
+            }
+        )*
+    };
+}
+
+style_fn!(
+    /// Serializes a field of a query with the [QueryStyle::Repeat] style.
+    repeat => Repeat,
+    /// Serializes a field of a query with the [QueryStyle::Comma] style.
+    comma => Comma,
+    /// Serializes a field of a query with the [QueryStyle::Brackets] style.
+    brackets => Brackets,
+    /// Serializes a field of a query with the [QueryStyle::DeepObject] style.
+    deep_object => DeepObject,
+);
+
+/// Adds the parameters for a node under the given, already encoded, key.
+fn write(
+    pairs: &mut Vec<(String, String)>,
+    key: String,
+    node: Node,
+    style: QueryStyle,
+) -> Result<(), QueryError> {
+    match (node, style) {
+        (Node::Skip, _) => {}
+        (Node::Value(v), _) => pairs.push((key, encode(&v))),
+        (Node::Styled(style, node), _) => write(pairs, key, *node, style)?,
+        (node, QueryStyle::Comma) => {
+            let mut values = Vec::new();
+            join(&mut values, node)?;
+            pairs.push((key, values.join(",")));
+        }
+        (Node::Seq(items), QueryStyle::Repeat) => {
+            for item in items {
+                write(pairs, key.clone(), item, style)?;
+            }
+        }
+        (Node::Seq(items), QueryStyle::Brackets) => {
+            for item in items {
+                write(pairs, format!("{}[]", key), item, style)?;
+            }
+        }
+        (Node::Seq(items), QueryStyle::DeepObject) => {
+            for (i, item) in items.into_iter().enumerate() {
+                write(pairs, format!("{}[{}]", key, i), item, style)?;
+            }
+        }
+        (Node::Map(_), QueryStyle::Repeat) => {
+            return Err(QueryError::new(
+                "Nested maps and structs cannot be serialized with the repeat style",
+            ))
+        }
+        (Node::Map(entries), _) => {
+            for (k, v) in entries {
+                write(pairs, format!("{}[{}]", key, encode(&k)), v, style)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds a parameter from an element of a sequence of key-value pairs.
+fn write_pair(
+    pairs: &mut Vec<(String, String)>,
+    node: Node,
+    style: QueryStyle,
+) -> Result<(), QueryError> {
+    if let Node::Seq(mut pair) = node {
+        if let (2, Node::Value(_)) = (pair.len(), &pair[0]) {
+            let value = pair.pop().unwrap();
+            if let Some(Node::Value(key)) = pair.pop() {
+                return write(pairs, encode(&key), value, style);
+            }
+        }
+    }
+
+    Err(QueryError::new(
+        "Sequences can only be serialized into a query string as key-value pairs",
+    ))
+}
+
+/// Collects the encoded values of a node joined with the comma style.
+fn join(values: &mut Vec<String>, node: Node) -> Result<(), QueryError> {
+    match node {
+        Node::Skip => {}
+        Node::Value(v) => values.push(encode(&v)),
+        Node::Styled(_, node) => join(values, *node)?,
+        Node::Seq(items) => {
+            for item in items {
+                join_value(values, item)?;
+            }
+        }
+        Node::Map(entries) => {
+            for (k, v) in entries {
+                values.push(encode(&k));
+                join_value(values, v)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Collects a single value nested in a node joined with the comma style.
+fn join_value(values: &mut Vec<String>, node: Node) -> Result<(), QueryError> {
+    match node {
+        Node::Styled(_, node) => join_value(values, *node),
+        Node::Seq(_) | Node::Map(_) => Err(QueryError::new(
+            "Nested sequences and maps cannot be serialized with the comma style",
+        )),
+        node => join(values, node),
+    }
+}
+
+fn encode(value: &str) -> String {
+    byte_serialize(value.as_bytes()).collect()
+}
+
+/// An error from serializing a query string.
+#[derive(Debug)]
+struct QueryError(String);
+
+impl QueryError {
+    fn new(msg: &str) -> Self {
+        QueryError(msg.to_string())
+    }
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl ser::Error for QueryError {
+    fn custom<T: Display>(msg: T) -> Self {
+        QueryError(msg.to_string())
+    }
+}
+
+/// A serialized value, keeping the order of the entries of maps.
+enum Node {
+    /// A value which is left out of the query string, like [None].
+    Skip,
+    Value(String),
+    Seq(Vec<Node>),
+    Map(Vec<(String, Node)>),
+    /// A value which was given its own [QueryStyle].
+    Styled(QueryStyle, Box<Node>),
+}
+
+/// Serializes a value into a [Node].
+struct NodeSerializer;
+
+/// Collects the elements of a sequence, tuple or tuple struct.
+struct SeqSerializer(Vec<Node>);
+
+/// Collects the entries of a map or struct.
+struct MapSerializer {
+    entries: Vec<(String, Node)>,
+    key: Option<String>,
+}
+
+fn unsupported(kind: &str) -> QueryError {
+    QueryError(format!("Cannot serialize {} into a query string", kind))
+}
+
+macro_rules! serialize_display {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Self::Ok, Self::Error> {
+                Ok(Node::Value(v.to_string()))
+            }
+        )*
+    };
+}
+
+impl Serializer for NodeSerializer {
+    type Ok = Node;
+    type Error = QueryError;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = Impossible<Node, QueryError>;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = Impossible<Node, QueryError>;
+
+    serialize_display!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+    );
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Skip)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Skip)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Skip)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Value(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        let node = value.serialize(self)?;
+        Ok(match QueryStyle::from_marker(name) {
+            Some(style) => Node::Styled(style, Box::new(node)),
+            None => node,
+        })
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(unsupported("enum variants with data"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SeqSerializer(Vec::with_capacity(len.unwrap_or_default())))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(SeqSerializer(Vec::with_capacity(len)))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(SeqSerializer(Vec::with_capacity(len)))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(unsupported("enum variants with data"))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(MapSerializer {
+            entries: Vec::with_capacity(len.unwrap_or_default()),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(unsupported("enum variants with data"))
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Node;
+    type Error = QueryError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.0.push(value.serialize(NodeSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Seq(self.0))
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Node;
+    type Error = QueryError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Node;
+    type Error = QueryError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = Node;
+    type Error = QueryError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        match key.serialize(NodeSerializer)? {
+            Node::Value(k) => {
+                self.key = Some(k);
+                Ok(())
+            }
+            _ => Err(QueryError::new("Map keys must be strings or numbers")),
+        }
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| QueryError::new("Map value serialized without a key"))?;
+        self.entries.push((key, value.serialize(NodeSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Map(self.entries))
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer {
+    type Ok = Node;
+    type Error = QueryError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.entries
+            .push((key.to_string(), value.serialize(NodeSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Node::Map(self.entries))
+    }
+}
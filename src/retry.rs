@@ -0,0 +1,210 @@
+//! Contains the [Retry] policy which [Endpoints][crate::endpoint::Endpoint]
+//! use to retry failed requests.
+//!
+//! The derive macro sets the policy of an endpoint when given the `retry`
+//! parameter, as in `retry(max = 3, backoff = "exponential", on = [502, 503,
+//! 504])`, which is returned by
+//! [Endpoint::retry][crate::endpoint::Endpoint::retry]. Executing the endpoint
+//! then retries requests which fail with a [ClientError::RequestError] or
+//! whose response has one of the given status codes and isn't accepted by the
+//! endpoint. The following options are available:
+//!
+//! * `max`: the number of retries after the first attempt, defaults to `3`.
+//! * `backoff`: either `"constant"` or `"exponential"`, which doubles the delay
+//!   after each retry, defaults to `"exponential"`.
+//! * `delay`: the delay before the first retry in milliseconds, defaults to
+//!   `100`.
+//! * `max_delay`: the longest delay between retries in milliseconds, including
+//!   those asked for by `Retry-After`, defaults to `30000`.
+//! * `on`: the status codes which are retried, defaults to `[429, 502, 503,
+//!   504]`.
+//! * `non_idempotent`: whether requests using the `POST`, `PATCH` and
+//!   `CONNECT` methods are retried, defaults to `false`.
+//!
+//! Responses with a `Retry-After` header, given either in seconds or as an
+//! HTTP date, are retried after the delay it asks for instead of the backoff,
+//! up to `max_delay`. Only requests executed with
+//! [exec][crate::endpoint::Endpoint::exec] and
+//! [exec_block][crate::endpoint::Endpoint::exec_block], including those of
+//! endpoints with [MiddleWare][crate::endpoint::MiddleWare] applied and of
+//! [Paginated][crate::paginate::Paginated] endpoints, are retried.
+//!
+//! # Example
+//! ```
+//! use rustified::{endpoint::Endpoint, retry::Backoff};
+//! use rustified_derive::Endpoint;
+//!
+//! #[derive(Endpoint)]
+//! #[endpoint(path = "users", retry(max = 5, backoff = "constant", delay = 500))]
+//! struct ListUsers {}
+//!
+//! let retry = ListUsers {}.retry().unwrap();
+//! assert_eq!(retry.max, 5);
+//! assert_eq!(retry.backoff, Backoff::Constant);
+//! ```
+
+use std::time::{Duration, SystemTime};
+
+use http::{header::RETRY_AFTER, Request, Response};
+
+use crate::{enums::RequestMethod, errors::ClientError};
+
+/// The status codes retried by default, see [Retry::on].
+pub const RETRY_STATUS_CODES: [u16; 4] = [429, 502, 503, 504];
+
+/// How the delay between retries grows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backoff {
+    /// Waits the same delay before each retry.
+    Constant,
+    /// Doubles the delay after each retry.
+    Exponential,
+}
+
+/// A policy for retrying the failed requests of an endpoint, see
+/// [crate::retry].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Retry {
+    /// The number of retries after the first attempt.
+    pub max: u32,
+    pub backoff: Backoff,
+    /// The delay before the first retry.
+    pub delay: Duration,
+    /// The longest delay between retries, which caps both the backoff and the
+    /// `Retry-After` header.
+    pub max_delay: Duration,
+    /// The status codes which are retried.
+    pub on: Vec<u16>,
+    /// Whether requests using a non-idempotent method are retried.
+    pub non_idempotent: bool,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Retry {
+            max: 3,
+            backoff: Backoff::Exponential,
+            delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(30),
+            on: RETRY_STATUS_CODES.to_vec(),
+            non_idempotent: false,
+        }
+    }
+}
+
+impl Retry {
+    /// Returns the default [Retry] policy with the given number of retries.
+    pub fn new(max: u32) -> Self {
+        Retry {
+            max,
+            ..Default::default()
+        }
+    }
+
+    /// Sets how the delay between retries grows.
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Sets the longest delay between retries.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Sets the status codes which are retried.
+    pub fn on(mut self, codes: &[u16]) -> Self {
+        self.on = codes.to_vec();
+        self
+    }
+
+    /// Sets whether requests using a non-idempotent method are retried.
+    pub fn non_idempotent(mut self, enabled: bool) -> Self {
+        self.non_idempotent = enabled;
+        self
+    }
+
+    /// Returns `true` if requests using the given method can be retried.
+    pub fn allows(&self, method: &RequestMethod) -> bool {
+        self.non_idempotent
+            || !matches!(
+                method,
+                RequestMethod::CONNECT | RequestMethod::PATCH | RequestMethod::POST
+            )
+    }
+
+    /// Returns the delay before the given retry, counting from `1`, ignoring
+    /// any `Retry-After` header. The delay is at most [Retry::max_delay].
+    pub fn backoff_delay(&self, retry: u32) -> Duration {
+        let delay = match self.backoff {
+            Backoff::Constant => self.delay,
+            Backoff::Exponential => self
+                .delay
+                .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1))),
+        };
+        delay.min(self.max_delay)
+    }
+
+    /// Returns the delay before retrying a request which received the given
+    /// result on its last attempt, or [None] if it shouldn't be retried.
+    ///
+    /// `attempt` counts the attempts made so far, starting from `1`, and
+    /// `accepts` returns `true` for the status codes accepted by the endpoint.
+    pub(crate) fn wait(
+        &self,
+        attempt: u32,
+        result: &Result<Response<Vec<u8>>, ClientError>,
+        accepts: impl Fn(u16) -> bool,
+    ) -> Option<Duration> {
+        if attempt > self.max {
+            return None;
+        }
+        match result {
+            Err(ClientError::RequestError { .. }) => Some(self.backoff_delay(attempt)),
+            Ok(resp) => {
+                let status = resp.status().as_u16();
+                if accepts(status) || !self.on.contains(&status) {
+                    return None;
+                }
+                match retry_after(resp) {
+                    Some(delay) => Some(delay.min(self.max_delay)),
+                    None => Some(self.backoff_delay(attempt)),
+                }
+            }
+            Err(_) => None,
+        }
+    }
+}
+
+/// Returns the delay given by the `Retry-After` header of a [Response].
+fn retry_after(resp: &Response<Vec<u8>>) -> Option<Duration> {
+    let value = resp.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    match value.parse::<u64>() {
+        Ok(secs) => Some(Duration::from_secs(secs)),
+        Err(_) => {
+            let date = httpdate::parse_http_date(value).ok()?;
+            Some(
+                date.duration_since(SystemTime::now())
+                    .unwrap_or(Duration::ZERO),
+            )
+        }
+    }
+}
+
+/// Returns a copy of the given [Request] for another attempt, leaving out its
+/// extensions.
+pub(crate) fn copy_request(req: &Request<Vec<u8>>) -> Request<Vec<u8>> {
+    let mut copy = Request::new(req.body().clone());
+    *copy.method_mut() = req.method().clone();
+    *copy.uri_mut() = req.uri().clone();
+    *copy.version_mut() = req.version();
+    *copy.headers_mut() = req.headers().clone();
+    copy
+}
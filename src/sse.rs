@@ -0,0 +1,377 @@
+//! Contains types for consuming server-sent events (SSE) from endpoints which
+//! respond with a `text/event-stream` body.
+//!
+//! Events are received using
+//! [Endpoint::exec_events][crate::endpoint::Endpoint::exec_events], which
+//! returns an [EventStream]. The `data` of each [Event] can be decoded into the
+//! [Endpoint::Response][crate::endpoint::Endpoint::Response] using
+//! [Event::parse].
+//!
+//! When the server closes the connection the stream waits for the reconnection
+//! time, which defaults to three seconds and can be changed by the server using
+//! the `retry` field, and then sends the request again. The ID of the last
+//! event received is sent along in the `Last-Event-ID` header so the server can
+//! resume where it left off. The stream ends when the server responds with
+//! `204 No Content`, and ends with an error when the request fails or the
+//! server responds with an unsuccessful status code or another media type.
+//! Unsuccessful responses are returned as the
+//! [Endpoint::error][crate::endpoint::Endpoint::error] of the endpoint.
+//!
+//! # Example
+//! ```
+//! use futures_util::TryStreamExt;
+//! use rustified::{clients::reqwest::Client, Endpoint};
+//! use rustified_derive::Endpoint;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Token {
+//!     text: String,
+//! }
+//!
+//! #[derive(Endpoint)]
+//! #[endpoint(path = "completions", response = "Token")]
+//! struct Completions {}
+//!
+//! # tokio_test::block_on(async {
+//! let client = Client::default("http://myapi.com");
+//! let endpoint = Completions {};
+//! let mut events = endpoint.exec_events(&client)?;
+//! while let Some(event) = events.try_next().await? {
+//!     let token = event.parse()?;
+//! }
+//! # Ok::<(), rustified::errors::ClientError>(())
+//! # });
+//! ```
+
+use std::{fmt, marker::PhantomData, pin::Pin, time::Duration};
+
+use futures_core::Stream;
+use futures_util::{stream, StreamExt};
+use http::{
+    header::{HeaderName, ACCEPT, CACHE_CONTROL, CONTENT_TYPE},
+    HeaderMap, HeaderValue, Method, Request, StatusCode, Uri,
+};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    client::{ByteStream, Client},
+    codec::{Codec, Json},
+    endpoint::Endpoint,
+    errors::ClientError,
+};
+
+/// The media type of server-sent event streams.
+pub const CONTENT_TYPE_EVENT_STREAM: &str = "text/event-stream";
+
+/// The time waited before reconnecting when the server doesn't specify one.
+pub const DEFAULT_RETRY: Duration = Duration::from_secs(3);
+
+const LAST_EVENT_ID: HeaderName = HeaderName::from_static("last-event-id");
+
+/// A stream of [Events][Event] returned by
+/// [Endpoint::exec_events][crate::endpoint::Endpoint::exec_events].
+pub type EventStream<'a, T, C = Json> =
+    Pin<Box<dyn Stream<Item = Result<Event<T, C>, ClientError>> + Send + 'a>>;
+
+/// A single server-sent event.
+///
+/// The `data` of the event can be decoded into `T` using the [Codec] `C` by
+/// calling `parse()`.
+pub struct Event<T, C = Json> {
+    /// The type of the event, which is `message` unless the server specified
+    /// another one.
+    pub event: String,
+    /// The data of the event, with the lines of multi-line data joined by `\n`.
+    pub data: String,
+    /// The last event ID set by the server, if any.
+    pub id: Option<String>,
+    /// The reconnection time set by the server along with this event, if any.
+    pub retry: Option<Duration>,
+    inner: PhantomData<fn() -> (T, C)>,
+}
+
+impl<T: DeserializeOwned, C: Codec> Event<T, C> {
+    /// Returns a new [Event].
+    pub fn new(event: String, data: String, id: Option<String>, retry: Option<Duration>) -> Self {
+        Event {
+            event,
+            data,
+            id,
+            retry,
+            inner: PhantomData,
+        }
+    }
+
+    /// Parses the data of the event into the final result type.
+    #[instrument(skip(self), err)]
+    pub fn parse(&self) -> Result<T, ClientError> {
+        C::decode(self.data.as_bytes())
+    }
+}
+
+impl<T, C> fmt::Debug for Event<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Event")
+            .field("event", &self.event)
+            .field("data", &self.data)
+            .field("id", &self.id)
+            .field("retry", &self.retry)
+            .finish()
+    }
+}
+
+/// Returns an [EventStream] which sends the given [Request] of the given
+/// [Endpoint] using the given [Client] and reconnects as needed.
+pub fn stream<'a, E>(
+    endpoint: &'a E,
+    client: &'a impl Client,
+    req: Request<Vec<u8>>,
+) -> EventStream<'a, E::Response, E::ResponseCodec>
+where
+    E: Endpoint,
+    E::Response: DeserializeOwned + 'a,
+    E::ResponseCodec: 'a,
+{
+    let (parts, body) = req.into_parts();
+    let mut headers = parts.headers;
+    headers.insert(ACCEPT, HeaderValue::from_static(CONTENT_TYPE_EVENT_STREAM));
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+
+    let state = State {
+        endpoint,
+        client,
+        method: parts.method,
+        uri: parts.uri,
+        headers,
+        body,
+        response: None,
+        parser: Parser::default(),
+        connected: false,
+        finished: false,
+    };
+    Box::pin(stream::unfold(state, |mut state| async move {
+        let event = state.next().await?;
+        Some((
+            event.map(|e| Event::new(e.event, e.data, e.id, e.retry)),
+            state,
+        ))
+    }))
+}
+
+/// The state of an [EventStream] between events.
+struct State<'a, E: Endpoint, C: Client> {
+    endpoint: &'a E,
+    client: &'a C,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Vec<u8>,
+    response: Option<ByteStream>,
+    parser: Parser,
+    connected: bool,
+    finished: bool,
+}
+
+impl<E: Endpoint, C: Client> State<'_, E, C> {
+    /// Returns the next event, connecting to the server whenever there's no
+    /// open response.
+    async fn next(&mut self) -> Option<Result<RawEvent, ClientError>> {
+        loop {
+            if self.finished {
+                return None;
+            }
+            if let Some(event) = self.parser.next_event() {
+                return Some(Ok(event));
+            }
+
+            let response = match &mut self.response {
+                Some(r) => r,
+                None => {
+                    if let Err(e) = self.connect().await {
+                        self.finished = true;
+                        return Some(Err(e));
+                    }
+                    continue;
+                }
+            };
+            match response.next().await {
+                Some(Ok(chunk)) => self.parser.feed(&chunk),
+                Some(Err(e)) => {
+                    debug!("Event stream interrupted: {}", e);
+                    self.response = None;
+                }
+                None => {
+                    debug!("Event stream closed by server");
+                    self.response = None;
+                }
+            }
+        }
+    }
+
+    /// Sends the request, waiting for the reconnection time first if this is
+    /// not the first connection.
+    async fn connect(&mut self) -> Result<(), ClientError> {
+        if self.connected {
+            debug!("Reconnecting in {:?}", self.parser.retry);
+            tokio::time::sleep(self.parser.retry).await;
+        }
+        self.connected = true;
+        self.parser.reset();
+
+        let mut req = Request::new(self.body.clone());
+        *req.method_mut() = self.method.clone();
+        *req.uri_mut() = self.uri.clone();
+        *req.headers_mut() = self.headers.clone();
+        if let Some(id) = &self.parser.last_id {
+            let value = HeaderValue::from_str(id)
+                .map_err(|e| ClientError::EndpointBuildError { source: e.into() })?;
+            req.headers_mut().insert(LAST_EVENT_ID, value);
+        }
+
+        crate::endpoint::accept_any(&mut req);
+        let response = self.client.execute_stream(req).await?;
+        let response = crate::endpoint::check_stream(self.endpoint, response).await?;
+        if response.status() == StatusCode::NO_CONTENT {
+            debug!("Event stream ended by server");
+            self.finished = true;
+            return Ok(());
+        }
+        if let Some(media_type) = response.headers().get(CONTENT_TYPE) {
+            let media_type = String::from_utf8_lossy(media_type.as_bytes()).to_string();
+            let essence = media_type.split(';').next().unwrap_or_default().trim();
+            if !essence.eq_ignore_ascii_case(CONTENT_TYPE_EVENT_STREAM) {
+                return Err(ClientError::ResponseMediaTypeError {
+                    media_type,
+                    expected: CONTENT_TYPE_EVENT_STREAM.to_string(),
+                    content: None,
+                });
+            }
+        }
+        self.response = Some(response.into_body());
+        Ok(())
+    }
+}
+
+/// An event as it's parsed from the stream.
+struct RawEvent {
+    event: String,
+    data: String,
+    id: Option<String>,
+    retry: Option<Duration>,
+}
+
+/// Incrementally parses an event stream as described by the
+/// [HTML specification](https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation).
+struct Parser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Option<String>,
+    event_retry: Option<Duration>,
+    last_id: Option<String>,
+    retry: Duration,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Parser {
+            buffer: Vec::new(),
+            event: None,
+            data: None,
+            event_retry: None,
+            last_id: None,
+            retry: DEFAULT_RETRY,
+        }
+    }
+}
+
+impl Parser {
+    /// Adds a chunk of the stream to the parser.
+    fn feed(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Discards any incomplete event when the connection is reset. The last
+    /// event ID and reconnection time are kept.
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.event = None;
+        self.data = None;
+        self.event_retry = None;
+    }
+
+    /// Processes complete lines from the buffer until an event is dispatched.
+    fn next_event(&mut self) -> Option<RawEvent> {
+        while let Some(line) = self.next_line() {
+            if line.is_empty() {
+                if let Some(event) = self.dispatch() {
+                    return Some(event);
+                }
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = match line.split_once(':') {
+                Some((f, v)) => (f, v.strip_prefix(' ').unwrap_or(v)),
+                None => (line.as_str(), ""),
+            };
+            match field {
+                "event" => self.event = Some(value.to_string()),
+                "data" => {
+                    let data = self.data.get_or_insert_with(String::new);
+                    data.push_str(value);
+                    data.push('\n');
+                }
+                "id" if !value.contains('\0') => {
+                    self.last_id = match value {
+                        "" => None,
+                        _ => Some(value.to_string()),
+                    }
+                }
+                "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                    if let Ok(ms) = value.parse() {
+                        self.retry = Duration::from_millis(ms);
+                        self.event_retry = Some(self.retry);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Removes and returns the next complete line from the buffer. Lines end
+    /// with `\r\n`, `\n` or `\r`.
+    fn next_line(&mut self) -> Option<String> {
+        let i = self
+            .buffer
+            .iter()
+            .position(|b| *b == b'\n' || *b == b'\r')?;
+        let end = match self.buffer[i] {
+            b'\r' if i + 1 == self.buffer.len() => return None,
+            b'\r' if self.buffer[i + 1] == b'\n' => i + 2,
+            _ => i + 1,
+        };
+        let line: Vec<u8> = self.buffer.drain(..end).take(i).collect();
+        Some(String::from_utf8_lossy(&line).into_owned())
+    }
+
+    /// Returns the event built from the fields seen since the last blank line,
+    /// if it has any data.
+    fn dispatch(&mut self) -> Option<RawEvent> {
+        let event = self.event.take();
+        let retry = self.event_retry.take();
+        let mut data = self.data.take()?;
+        data.pop();
+        Some(RawEvent {
+            event: event
+                .filter(|e| !e.is_empty())
+                .unwrap_or_else(|| "message".to_string()),
+            data,
+            id: self.last_id.clone(),
+            retry,
+        })
+    }
+}
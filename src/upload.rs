@@ -0,0 +1,137 @@
+//! Contains the [Upload] type for sending request bodies without buffering
+//! them in memory.
+
+use std::{io, path::PathBuf, pin::Pin};
+
+use bytes::Bytes;
+use futures_core::Stream;
+use http::{header::CONTENT_TYPE, HeaderValue, Request};
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+
+use crate::errors::ClientError;
+
+/// A stream of chunks making up an [Upload].
+pub type UploadStream = Pin<Box<dyn Stream<Item = Result<Bytes, io::Error>> + Send>>;
+
+/// A request body which is streamed to the server instead of being held in
+/// memory, used with [Endpoint::exec_upload][crate::Endpoint::exec_upload].
+///
+/// Uploads with a known length are sent with a `Content-Length` header while
+/// all others use chunked transfer encoding. The length of a file is always
+/// known. Uploads are sent as `application/octet-stream` unless another
+/// content type is given.
+///
+/// # Example
+/// ```
+/// use rustified::{clients::reqwest::Client, upload::Upload, Endpoint};
+/// use rustified_derive::Endpoint;
+///
+/// #[derive(Endpoint)]
+/// #[endpoint(path = "artifacts/{self.name}", method = "PUT")]
+/// struct PutArtifact {
+///     #[endpoint(skip)]
+///     name: String,
+/// }
+///
+/// # tokio_test::block_on(async {
+/// let client = Client::default("http://myapi.com");
+/// let endpoint = PutArtifact {
+///     name: "build.tar".to_string(),
+/// };
+/// let result = endpoint
+///     .exec_upload(&client, Upload::file("target/build.tar"))
+///     .await;
+/// # })
+/// ```
+pub struct Upload {
+    source: Source,
+    length: Option<u64>,
+    content_type: Option<String>,
+}
+
+enum Source {
+    File(PathBuf),
+    Stream(UploadStream),
+}
+
+impl Upload {
+    /// Returns a new [Upload] which reads the file at the given path. The file
+    /// is only opened when the upload is sent.
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Upload::new(Source::File(path.into()))
+    }
+
+    /// Returns a new [Upload] which reads from the given [AsyncRead].
+    pub fn reader(reader: impl AsyncRead + Send + 'static) -> Self {
+        Upload::stream(ReaderStream::new(reader))
+    }
+
+    /// Returns a new [Upload] which sends the chunks of the given [Stream].
+    pub fn stream(stream: impl Stream<Item = Result<Bytes, io::Error>> + Send + 'static) -> Self {
+        Upload::new(Source::Stream(Box::pin(stream)))
+    }
+
+    /// Sets the length of the upload, which is sent as the `Content-Length`
+    /// header. The source must produce exactly this many bytes.
+    pub fn length(mut self, length: u64) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    /// Sets the content type of the upload.
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// Sets the `Content-Type` header of the given [Request] if a content type
+    /// was given for this upload.
+    pub fn apply(&self, req: &mut Request<Vec<u8>>) -> Result<(), ClientError> {
+        set_content_type(req, self.content_type.as_deref())
+    }
+
+    /// Opens the upload and returns its length, if known, along with the
+    /// stream of chunks to send.
+    pub async fn open(self) -> Result<(Option<u64>, UploadStream), ClientError> {
+        match self.source {
+            Source::File(path) => {
+                let file = tokio::fs::File::open(&path)
+                    .await
+                    .map_err(|e| ClientError::UploadError { source: e.into() })?;
+                let length = match self.length {
+                    Some(l) => l,
+                    None => file
+                        .metadata()
+                        .await
+                        .map_err(|e| ClientError::UploadError { source: e.into() })?
+                        .len(),
+                };
+                Ok((Some(length), Box::pin(ReaderStream::new(file))))
+            }
+            Source::Stream(stream) => Ok((self.length, stream)),
+        }
+    }
+
+    fn new(source: Source) -> Self {
+        Upload {
+            source,
+            length: None,
+            content_type: None,
+        }
+    }
+}
+
+/// Sets the `Content-Type` header of the given [Request] if a content type is
+/// given.
+pub(crate) fn set_content_type(
+    req: &mut Request<Vec<u8>>,
+    content_type: Option<&str>,
+) -> Result<(), ClientError> {
+    if let Some(c) = content_type {
+        let value = HeaderValue::from_str(c)
+            .map_err(|e| ClientError::EndpointBuildError { source: e.into() })?;
+        req.headers_mut().insert(CONTENT_TYPE, value);
+    }
+    Ok(())
+}
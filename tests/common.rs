@@ -15,6 +15,7 @@ pub struct TestServer {
 }
 
 #[cfg(feature = "blocking")]
+#[allow(dead_code)]
 pub struct TestServerBlocking {
     pub server: MockServer,
     pub client: ReqwestBlocking,
@@ -95,6 +96,7 @@ impl MiddleWare for Middle {
             serde_json::from_slice(&resp_body).map_err(|e| ClientError::ResponseParseError {
                 source: e.into(),
                 content: String::from_utf8(resp_body.to_vec()).ok(),
+                line: None,
             })?;
         let data = wrapper.result.to_string();
         *resp.body_mut() = data.as_bytes().to_vec();
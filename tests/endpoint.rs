@@ -1,11 +1,29 @@
 mod common;
 
-use std::{fmt::Debug, marker::PhantomData};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
 
+use bytes::Bytes;
+#[cfg(feature = "blocking")]
+use common::TestServerBlocking;
 use common::{Middle, TestGenericWrapper, TestResponse, TestServer};
 use derive_builder::Builder;
+use futures_util::{stream, StreamExt, TryStreamExt};
 use httpmock::prelude::*;
-use rustified::endpoint::Endpoint;
+use rustified::{
+    codec::{Codec, Json},
+    endpoint::Endpoint,
+    enums::RequestMethod,
+    errors::ClientError,
+    multipart::File,
+    paginate::Paginated,
+    retry::{Backoff, Retry},
+    upload::Upload,
+};
 use rustified_derive::Endpoint;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::json;
@@ -76,6 +94,120 @@ async fn test_query() {
     assert!(r.is_ok());
 }
 
+#[test(tokio::test)]
+async fn test_query_styles() {
+    #[derive(Serialize)]
+    struct Range {
+        min: u32,
+        max: u32,
+    }
+
+    #[derive(Serialize)]
+    struct Filter {
+        name: String,
+        age: Range,
+    }
+
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path")]
+    struct Test {
+        #[endpoint(query)]
+        tag: Vec<String>,
+        #[endpoint(query(style = "comma"))]
+        id: Vec<u32>,
+        #[endpoint(query(style = brackets))]
+        sort: Vec<String>,
+        #[endpoint(query(style = "deepObject"))]
+        filter: Filter,
+        #[endpoint(query(style = "comma"))]
+        page: Option<BTreeMap<String, u32>>,
+    }
+
+    let t = TestServer::default();
+    let e = Test {
+        tag: vec!["a".to_string(), "b c".to_string()],
+        id: vec![1, 2],
+        sort: vec!["name".to_string()],
+        filter: Filter {
+            name: "x".to_string(),
+            age: Range { min: 18, max: 30 },
+        },
+        page: Some(BTreeMap::from([("size".to_string(), 10)])),
+    };
+    let m = t.server.mock(|when, then| {
+        when.method(GET)
+            .path("/test/path")
+            .query_param("tag", "b c")
+            .query_param("id", "1,2")
+            .query_param("sort[]", "name")
+            .query_param("filter[age][max]", "30");
+        then.status(200);
+    });
+    let r = e.exec(&t.client).await;
+
+    m.assert();
+    assert!(r.is_ok());
+    assert_eq!(
+        e.query().unwrap().unwrap(),
+        "tag=a&tag=b+c&id=1,2&sort[]=name&filter[name]=x&filter[age][min]=18\
+         &filter[age][max]=30&page=size,10"
+    );
+}
+
+#[test(tokio::test)]
+async fn test_query_endpoint_style() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", query(style = deepObject))]
+    struct Test {
+        #[endpoint(query)]
+        tag: Vec<String>,
+        #[endpoint(query)]
+        filter: HashMap<String, String>,
+        #[endpoint(query(style = "repeat"))]
+        id: Vec<u32>,
+    }
+
+    let e = Test {
+        tag: vec!["a".to_string(), "b".to_string()],
+        filter: HashMap::from([("name".to_string(), "x".to_string())]),
+        id: vec![1, 2],
+    };
+
+    assert_eq!(
+        e.query().unwrap().unwrap(),
+        "tag[0]=a&tag[1]=b&filter[name]=x&id=1&id=2"
+    );
+}
+
+#[test(tokio::test)]
+async fn test_query_invalid() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", query(style = "comma"))]
+    struct Test {
+        #[endpoint(query)]
+        id: Vec<Vec<u32>>,
+    }
+
+    let e = Test { id: vec![vec![1]] };
+    let r = e.request("http://localhost");
+
+    assert!(matches!(r, Err(ClientError::UrlQueryParseError { .. })));
+
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path")]
+    struct TestNested {
+        #[endpoint(query)]
+        filter: HashMap<String, String>,
+    }
+
+    let e = TestNested {
+        filter: HashMap::from([("name".to_string(), "x".to_string())]),
+    };
+    let r = e.request("http://localhost");
+
+    assert!(matches!(r, Err(ClientError::UrlQueryParseError { .. })));
+}
+
 #[test(tokio::test)]
 async fn test_path_with_format() {
     #[derive(Endpoint)]
@@ -124,6 +256,208 @@ async fn test_data() {
     assert!(r.is_ok());
 }
 
+#[test(tokio::test)]
+async fn test_form_data() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", method = "POST", request_type = "FORM")]
+    struct Test {
+        grant_type: String,
+        scope: Option<String>,
+        #[serde(rename = "client-id")]
+        client_id: u64,
+    }
+
+    let t = TestServer::default();
+    let e = Test {
+        grant_type: "client_credentials".to_string(),
+        scope: None,
+        client_id: 42,
+    };
+    let m = t.server.mock(|when, then| {
+        when.method(POST)
+            .path("/test/path")
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body("grant_type=client_credentials&client-id=42");
+        then.status(200);
+    });
+    let r = e.exec(&t.client).await;
+
+    m.assert();
+    assert!(r.is_ok());
+}
+
+#[test]
+fn test_form_data_nested() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", method = "POST", request_type = "FORM")]
+    struct Test {
+        tags: Vec<String>,
+    }
+
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", method = "POST", request_type = "FORM")]
+    struct TestInvalid {
+        #[serde(serialize_with = "fail")]
+        name: String,
+    }
+
+    fn fail<S: serde::Serializer>(_: &String, _: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("invalid name"))
+    }
+
+    let e = Test {
+        tags: vec!["a".to_string()],
+    };
+    let r = e.body();
+
+    assert!(matches!(
+        r,
+        Err(ClientError::DataParseError { source }) if source.to_string().contains("nested values")
+    ));
+
+    let e = TestInvalid {
+        name: "test".to_string(),
+    };
+    let r = e.body();
+
+    assert!(matches!(
+        r,
+        Err(ClientError::DataParseError { source }) if source.to_string() == "invalid name"
+    ));
+}
+
+#[test(tokio::test)]
+async fn test_multipart() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", method = "POST")]
+    struct Test {
+        #[endpoint(part)]
+        name: String,
+        #[endpoint(part)]
+        age: Option<u8>,
+        #[endpoint(file)]
+        avatar: File,
+        #[endpoint(file)]
+        banner: Option<File>,
+    }
+
+    let t = TestServer::default();
+    let e = Test {
+        name: "test".to_string(),
+        age: None,
+        avatar: File::new("somebits")
+            .filename("avatar.png")
+            .content_type("image/png"),
+        banner: None,
+    };
+    let m = t.server.mock(|when, then| {
+        when.method(POST)
+            .path("/test/path")
+            .header_exists("Content-Type")
+            .body_contains("Content-Disposition: form-data; name=\"name\"\r\n\r\ntest\r\n")
+            .body_contains(
+                "Content-Disposition: form-data; name=\"avatar\"; \
+                 filename=\"avatar.png\"\r\nContent-Type: image/png\r\n\r\nsomebits\r\n",
+            )
+            .matches(|req| {
+                let body = String::from_utf8(req.body.clone().unwrap()).unwrap();
+                !body.contains("name=\"age\"") && !body.contains("name=\"banner\"")
+            });
+        then.status(200);
+    });
+    let r = e.exec(&t.client).await;
+
+    m.assert();
+    assert!(r.is_ok());
+}
+
+#[test]
+fn test_multipart_boundary() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", method = "POST", request_type = "MULTIPART")]
+    struct Test {
+        name: String,
+    }
+
+    let e = Test {
+        name: "test".to_string(),
+    };
+    let r = e.request("http://localhost").unwrap();
+    let content_type = r.headers()["Content-Type"].to_str().unwrap();
+    let boundary = content_type
+        .strip_prefix("multipart/form-data; boundary=")
+        .unwrap();
+    let body = String::from_utf8(r.body().clone()).unwrap();
+
+    assert!(body.starts_with(format!("--{}\r\n", boundary).as_str()));
+    assert!(body.contains("name=\"name\"\r\n\r\ntest\r\n"));
+    assert!(body.ends_with(format!("--{}--\r\n", boundary).as_str()));
+
+    // A part containing the boundary causes a new one to be generated
+    let mut form = rustified::multipart::Form::new();
+    let initial = form.boundary().to_string();
+    form.text("data", format!("--{}", initial));
+    let body = form.finish();
+    let content_type = rustified::multipart::content_type(&body).unwrap();
+    let boundary = content_type
+        .to_str()
+        .unwrap()
+        .strip_prefix("multipart/form-data; boundary=")
+        .unwrap();
+
+    assert_ne!(boundary, initial);
+    assert!(body.starts_with(format!("--{}\r\n", boundary).as_bytes()));
+    assert!(rustified::multipart::content_type(b"--a\r\n\r\ndata\r\n--b--\r\n").is_err());
+}
+
+#[test(tokio::test)]
+async fn test_codec() {
+    struct VendorJson;
+
+    impl Codec for VendorJson {
+        fn content_type() -> &'static str {
+            "application/vnd.test+json"
+        }
+
+        fn encode<T: Serialize>(object: &T) -> Result<Vec<u8>, ClientError> {
+            Json::encode(object)
+        }
+
+        fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, ClientError> {
+            Json::decode(body)
+        }
+    }
+
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        method = "POST",
+        response = "TestResponse",
+        request_codec = "VendorJson",
+        response_codec = "VendorJson"
+    )]
+    struct Test {
+        name: String,
+    }
+
+    let t = TestServer::default();
+    let e = Test {
+        name: "test".to_string(),
+    };
+    let m = t.server.mock(|when, then| {
+        when.method(POST)
+            .path("/test/path")
+            .header("Content-Type", "application/vnd.test+json")
+            .json_body(json!({ "name": "test" }));
+        then.status(200).json_body(json!({"age": 30}));
+    });
+    let r = e.exec(&t.client).await;
+
+    m.assert();
+    assert!(r.is_ok());
+    assert_eq!(r.unwrap().parse().unwrap().age, 30);
+}
+
 #[test(tokio::test)]
 async fn test_raw_data() {
     #[derive(Endpoint)]
@@ -176,29 +510,1965 @@ async fn test_result() {
     assert_eq!(r.unwrap().parse().unwrap().age, 30);
 }
 
+#[cfg(feature = "xml")]
 #[test(tokio::test)]
-async fn test_builder() {
-    #[derive(Builder, Endpoint)]
-    #[endpoint(path = "test/path", method = "POST", builder = "true")]
-    #[builder(setter(into))]
+async fn test_xml() {
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        method = "POST",
+        response = "TestResponse",
+        request_type = "XML",
+        response_type = "XML"
+    )]
+    #[serde(rename = "user")]
     struct Test {
         name: String,
+        nickname: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct TestResponse {
+        age: u8,
     }
 
     let t = TestServer::default();
+    let e = Test {
+        name: "test".to_string(),
+        nickname: None,
+    };
     let m = t.server.mock(|when, then| {
-        when.method(POST).path("/test/path");
-        then.status(200);
+        when.method(POST)
+            .path("/test/path")
+            .header("Content-Type", "application/xml")
+            .body("<user><name>test</name></user>");
+        then.status(200).body("<response><age>30</age></response>");
     });
-    let r = Test::builder()
-        .name("test")
-        .build()
-        .unwrap()
-        .exec(&t.client)
-        .await;
+    let r = e.exec(&t.client).await;
+
+    m.assert();
+    assert!(r.is_ok());
+    assert_eq!(r.unwrap().parse().unwrap().age, 30);
+}
+
+#[cfg(feature = "xml")]
+#[test(tokio::test)]
+async fn test_xml_parse_error() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", response = "TestResponse", response_type = "XML")]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(200)
+            .body("<response><name>test</name></response>");
+    });
+    let r = e.exec(&t.client).await.unwrap().parse();
+
+    m.assert();
+    match r {
+        Err(ClientError::ResponseParseError { content, .. }) => assert_eq!(
+            content.as_deref(),
+            Some("<response><name>test</name></response>")
+        ),
+        _ => panic!("expected a parse error"),
+    }
+}
+
+#[cfg(feature = "msgpack")]
+#[test(tokio::test)]
+async fn test_msgpack() {
+    use rustified::codec::MessagePack;
+    use serde_json::Value;
+
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        method = "POST",
+        response = "TestResponse",
+        request_type = "MSGPACK",
+        response_type = "MSGPACK"
+    )]
+    struct Test {
+        name: String,
+        nickname: Option<String>,
+    }
+
+    let t = TestServer::default();
+    let e = Test {
+        name: "test".to_string(),
+        nickname: None,
+    };
+    let m = t.server.mock(|when, then| {
+        when.method(POST)
+            .path("/test/path")
+            .header("Content-Type", "application/msgpack")
+            .header("Accept", "application/msgpack")
+            .matches(|req| {
+                let body = req.body.clone().unwrap_or_default();
+                MessagePack::decode::<Value>(&body).ok() == Some(json!({ "name": "test" }))
+            });
+        then.status(200)
+            .body(MessagePack::encode(&json!({ "age": 30 })).unwrap());
+    });
+    let r = e.exec(&t.client).await;
 
     m.assert();
     assert!(r.is_ok());
+    assert_eq!(r.unwrap().parse().unwrap().age, 30);
+}
+
+#[cfg(feature = "cbor")]
+#[test(tokio::test)]
+async fn test_cbor() {
+    use rustified::codec::Cbor;
+    use serde_json::Value;
+
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        method = "POST",
+        response = "TestResponse",
+        request_type = "CBOR",
+        response_type = "CBOR"
+    )]
+    struct Test {
+        name: String,
+        nickname: Option<String>,
+    }
+
+    let t = TestServer::default();
+    let e = Test {
+        name: "test".to_string(),
+        nickname: None,
+    };
+    let m = t.server.mock(|when, then| {
+        when.method(POST)
+            .path("/test/path")
+            .header("Content-Type", "application/cbor")
+            .header("Accept", "application/cbor")
+            .matches(|req| {
+                let body = req.body.clone().unwrap_or_default();
+                Cbor::decode::<Value>(&body).ok() == Some(json!({ "name": "test" }))
+            });
+        then.status(200)
+            .body(Cbor::encode(&json!({ "age": 30 })).unwrap());
+    });
+    let r = e.exec(&t.client).await;
+
+    m.assert();
+    assert!(r.is_ok());
+    assert_eq!(r.unwrap().parse().unwrap().age, 30);
+}
+
+#[test(tokio::test)]
+async fn test_text_response() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", response = "String", response_type = "TEXT")]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(GET)
+            .path("/test/path")
+            .header("Accept", "text/*");
+        then.status(200).body("-----BEGIN CERTIFICATE-----");
+    });
+    let r = e.exec(&t.client).await;
+
+    m.assert();
+    assert!(r.is_ok());
+    assert_eq!(r.unwrap().parse().unwrap(), "-----BEGIN CERTIFICATE-----");
+}
+
+#[test(tokio::test)]
+async fn test_text_response_invalid() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", response = "String", response_type = "TEXT")]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(200).body([0xffu8, 0xfe]);
+    });
+    let r = e.exec(&t.client).await.unwrap().parse();
+
+    m.assert();
+    match r {
+        Err(ClientError::ResponseConversionError { content, .. }) => {
+            assert_eq!(content, vec![0xff, 0xfe])
+        }
+        _ => panic!("expected a conversion error"),
+    }
+}
+
+#[test(tokio::test)]
+async fn test_bytes_response() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", response = "Vec<u8>", response_type = "BYTES")]
+    struct Test {}
+
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", response = "bytes::Bytes", response_type = "BYTES")]
+    struct TestBytes {}
+
+    let t = TestServer::default();
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path").header("Accept", "*/*");
+        then.status(200).body([0xffu8, 0x00, 0x01]);
+    });
+    let r = Test {}.exec(&t.client).await.unwrap().parse();
+    let r_bytes = TestBytes {}.exec(&t.client).await.unwrap().parse();
+
+    m.assert_hits(2);
+    assert_eq!(r.unwrap(), vec![0xff, 0x00, 0x01]);
+    assert_eq!(
+        r_bytes.unwrap(),
+        bytes::Bytes::from_static(&[0xff, 0x00, 0x01])
+    );
+}
+
+#[test(tokio::test)]
+async fn test_negotiate() {
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/{self.kind}",
+        response = "String",
+        response_type = "JSON | TEXT"
+    )]
+    struct Test {
+        #[endpoint(skip)]
+        kind: String,
+    }
+
+    let t = TestServer::default();
+    let m_json = t.server.mock(|when, then| {
+        when.method(GET)
+            .path("/test/json")
+            .header("Accept", "application/json, text/*");
+        then.status(200)
+            .header("Content-Type", "application/json")
+            .body("\"test\"");
+    });
+    let m_text = t.server.mock(|when, then| {
+        when.method(GET)
+            .path("/test/text")
+            .header("Accept", "application/json, text/*");
+        then.status(200)
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .body("test");
+    });
+    let kind = |k: &str| Test {
+        kind: k.to_string(),
+    };
+    let r_json = kind("json").exec(&t.client).await.unwrap().parse();
+    let r_text = kind("text").exec(&t.client).await.unwrap().parse();
+
+    m_json.assert();
+    m_text.assert();
+    assert_eq!(r_json.unwrap(), "test");
+    assert_eq!(r_text.unwrap(), "test");
+}
+
+#[test(tokio::test)]
+async fn test_negotiate_mismatch() {
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        response = "TestResponse",
+        response_type = "JSON | TEXT"
+    )]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(200)
+            .header("Content-Type", "application/xml")
+            .body("<age>30</age>");
+    });
+    let r = e.exec(&t.client).await.unwrap().parse();
+
+    m.assert();
+    match r {
+        Err(ClientError::ResponseMediaTypeError { media_type, .. }) => {
+            assert_eq!(media_type, "application/xml")
+        }
+        _ => panic!("expected a media type error"),
+    }
+}
+
+#[test(tokio::test)]
+async fn test_media_type_mislabelled() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", response = "TestResponse")]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(200)
+            .header("Content-Type", "text/plain")
+            .body(r#"{"age":3}"#);
+    });
+    let r = e.exec(&t.client).await.unwrap().parse();
+
+    m.assert();
+    assert_eq!(r.unwrap().age, 3);
+}
+
+#[test(tokio::test)]
+async fn test_stream() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path")]
+    struct Test {}
+
+    let data = "a".repeat(1 << 20);
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(200)
+            .header("X-Test", "test")
+            .body(data.as_str());
+    });
+    let r = e.exec_stream(&t.client).await.unwrap();
+    assert_eq!(r.status(), 200);
+    assert_eq!(r.headers()["X-Test"], "test");
+    let body = r
+        .into_body()
+        .try_fold(Vec::new(), |mut acc, chunk| async move {
+            acc.extend_from_slice(&chunk);
+            Ok(acc)
+        })
+        .await
+        .unwrap();
+
+    m.assert();
+    assert_eq!(body, data.as_bytes());
+}
+
+#[test(tokio::test)]
+async fn test_stream_error() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path")]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(404).body("not found");
+    });
+    let r = e.exec_stream(&t.client).await;
+
+    m.assert();
+    match r {
+        Err(ClientError::ServerResponseError { code, content }) => {
+            assert_eq!(code, 404);
+            assert_eq!(content.as_deref(), Some("not found"));
+        }
+        _ => panic!("expected a server response error"),
+    }
+}
+
+#[test(tokio::test)]
+async fn test_upload() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", method = "PUT")]
+    struct Test {}
+
+    let chunks = vec![Ok(Bytes::from("hello ")), Ok(Bytes::from("world"))];
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(PUT)
+            .path("/test/path")
+            .header("Content-Type", "text/plain")
+            .header("Transfer-Encoding", "chunked")
+            .body("hello world");
+        then.status(200);
+    });
+    let r = e
+        .exec_upload(
+            &t.client,
+            Upload::stream(stream::iter(chunks)).content_type("text/plain"),
+        )
+        .await;
+
+    m.assert();
+    assert!(r.is_ok());
+}
+
+#[test(tokio::test)]
+async fn test_upload_file() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", method = "PUT")]
+    struct Test {}
+
+    let data = "a".repeat(1 << 20);
+    let path = std::env::temp_dir().join("rustified_test_upload_file");
+    std::fs::write(&path, &data).unwrap();
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(PUT)
+            .path("/test/path")
+            .header("Content-Type", "application/octet-stream")
+            .header("Content-Length", (1 << 20).to_string())
+            .body(data.as_str());
+        then.status(200);
+    });
+    let r = e.exec_upload(&t.client, Upload::file(&path)).await;
+    std::fs::remove_file(&path).unwrap();
+
+    m.assert();
+    assert!(r.is_ok());
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_upload_blocking() {
+    use rustified::blocking::upload::Upload;
+
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", method = "PUT")]
+    struct Test {}
+
+    let data = "a".repeat(1 << 16);
+    let path = std::env::temp_dir().join("rustified_test_upload_blocking");
+    std::fs::write(&path, &data).unwrap();
+
+    let t = TestServerBlocking::default();
+    let e = Test {};
+    let m1 = t.server.mock(|when, then| {
+        when.method(PUT)
+            .path("/test/path")
+            .header("Content-Type", "text/plain")
+            .header("Transfer-Encoding", "chunked")
+            .body("hello world");
+        then.status(200);
+    });
+    let m2 = t.server.mock(|when, then| {
+        when.method(PUT)
+            .path("/test/path")
+            .header("Content-Type", "application/octet-stream")
+            .header("Content-Length", (1 << 16).to_string())
+            .body(data.as_str());
+        then.status(200);
+    });
+    let r1 = e.exec_upload_block(
+        &t.client,
+        Upload::reader(std::io::Cursor::new("hello world")).content_type("text/plain"),
+    );
+    let r2 = e.exec_upload_block(&t.client, Upload::file(&path));
+    let r3 = e.exec_upload_block(&t.client, Upload::file("does/not/exist"));
+    std::fs::remove_file(&path).unwrap();
+
+    m1.assert();
+    m2.assert();
+    assert!(r1.is_ok());
+    assert!(r2.is_ok());
+    assert!(matches!(r3, Err(ClientError::UploadError { .. })));
+}
+
+#[test(tokio::test)]
+async fn test_upload_missing_file() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", method = "PUT")]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let r = e
+        .exec_upload(&t.client, Upload::file("does/not/exist"))
+        .await;
+
+    assert!(matches!(r, Err(ClientError::UploadError { .. })));
+}
+
+#[test(tokio::test)]
+async fn test_ndjson() {
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Record {
+        id: u64,
+    }
+
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        method = "POST",
+        request_type = "NDJSON",
+        response_type = "NDJSON",
+        response = "Vec<Record>"
+    )]
+    struct Test {
+        records: Vec<Record>,
+    }
+
+    let t = TestServer::default();
+    let e = Test {
+        records: vec![Record { id: 1 }, Record { id: 2 }],
+    };
+    let m = t.server.mock(|when, then| {
+        when.method(POST)
+            .path("/test/path")
+            .header("Content-Type", "application/x-ndjson")
+            .header("Accept", "application/x-ndjson")
+            .body("{\"id\":1}\n{\"id\":2}\n");
+        then.status(200).body("{\"id\":3}\r\n\n{\"id\":4}");
+    });
+    let r = e.exec(&t.client).await.unwrap();
+
+    m.assert();
+    assert_eq!(r.parse().unwrap(), vec![Record { id: 3 }, Record { id: 4 }]);
+    assert_eq!(
+        r.lines().collect::<Result<Vec<_>, _>>().unwrap(),
+        vec![Record { id: 3 }, Record { id: 4 }]
+    );
+}
+
+#[test(tokio::test)]
+async fn test_ndjson_invalid_line() {
+    #[derive(Debug, Deserialize)]
+    struct Record {
+        #[allow(dead_code)]
+        id: u64,
+    }
+
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", response_type = "NDJSON", response = "Vec<Record>")]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(200)
+            .body("{\"id\":1}\n\n{\"id\":\"two\"}\n{\"id\":3}\n");
+    });
+    let r = e.exec(&t.client).await.unwrap();
+
+    m.assert();
+    match r.parse() {
+        Err(ClientError::ResponseParseError { content, line, .. }) => {
+            assert_eq!(line, Some(3));
+            assert_eq!(content.as_deref(), Some("{\"id\":\"two\"}"));
+        }
+        _ => panic!("expected a response parse error"),
+    }
+    let lines = r
+        .lines()
+        .map(|l| l.map_err(|e| e.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].is_ok() && lines[1].is_err() && lines[2].is_ok());
+}
+
+#[test(tokio::test)]
+async fn test_ndjson_stream() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Record {
+        id: u64,
+    }
+
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", response_type = "NDJSON", response = "Vec<Record>")]
+    struct Test {}
+
+    let data = (0..10000)
+        .map(|i| format!("{{\"id\":{}}}\n", i))
+        .collect::<String>();
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(200).body(data.as_str());
+    });
+    let r: Vec<Record> = e
+        .exec_lines(&t.client)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    m.assert();
+    assert_eq!(r.len(), 10000);
+    assert_eq!(r[9999], Record { id: 9999 });
+}
+
+#[test(tokio::test)]
+async fn test_events() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Token {
+        text: String,
+    }
+
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", response = "Token")]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m2 = t.server.mock(|when, then| {
+        when.method(GET)
+            .path("/test/path")
+            .header("Last-Event-ID", "2");
+        then.status(204);
+    });
+    let m1 = t.server.mock(|when, then| {
+        when.method(GET)
+            .path("/test/path")
+            .header("Accept", "text/event-stream");
+        then.status(200)
+            .header("Content-Type", "text/event-stream")
+            .body(concat!(
+                ": comment\nretry: 10\n\n",
+                "event: token\nid: 1\ndata: {\"text\":\"hello\"}\n\n",
+                "data: {\"text\":\r\ndata: \"world\"}\r\nid: 2\r\n\r\n",
+                "data: incomplete",
+            ));
+    });
+    let r: Vec<_> = e
+        .exec_events(&t.client)
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    m1.assert();
+    m2.assert();
+    assert_eq!(r.len(), 2);
+    assert_eq!(r[0].event, "token");
+    assert_eq!(r[0].id.as_deref(), Some("1"));
+    assert_eq!(r[0].parse().unwrap().text, "hello");
+    assert_eq!(r[1].event, "message");
+    assert_eq!(r[1].data, "{\"text\":\n\"world\"}");
+    assert_eq!(r[1].id.as_deref(), Some("2"));
+    assert_eq!(r[1].parse().unwrap().text, "world");
+}
+
+#[test(tokio::test)]
+async fn test_events_error() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path")]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(200)
+            .header("Content-Type", "application/json")
+            .body("{}");
+    });
+    let r: Vec<_> = e.exec_events(&t.client).unwrap().collect().await;
+
+    m.assert();
+    assert_eq!(r.len(), 1);
+    assert!(matches!(
+        r[0],
+        Err(ClientError::ResponseMediaTypeError { .. })
+    ));
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct PageUser {
+    id: u64,
+}
+
+#[test(tokio::test)]
+async fn test_paginate_cursor() {
+    #[derive(Deserialize)]
+    struct Page {
+        #[allow(dead_code)]
+        data: Vec<PageUser>,
+    }
+
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        response = "Page",
+        paginate(cursor = "meta.next", param = "cursor", items = "data", item = PageUser)
+    )]
+    struct Test {
+        #[endpoint(query)]
+        limit: u64,
+    }
+
+    let t = TestServer::default();
+    let e = Test { limit: 2 };
+    let m2 = t.server.mock(|when, then| {
+        when.method(GET)
+            .path("/test/path")
+            .query_param("limit", "2")
+            .query_param("cursor", "a b");
+        then.status(200)
+            .json_body(json!({ "data": [{ "id": 3 }], "meta": { "next": null } }));
+    });
+    let m1 = t.server.mock(|when, then| {
+        when.method(GET)
+            .path("/test/path")
+            .query_param("limit", "2");
+        then.status(200)
+            .json_body(json!({ "data": [{ "id": 1 }, { "id": 2 }], "meta": { "next": "a b" } }));
+    });
+    let r: Vec<_> = e
+        .exec_items(&t.client)
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    m1.assert();
+    m2.assert();
+    assert_eq!(
+        r,
+        vec![PageUser { id: 1 }, PageUser { id: 2 }, PageUser { id: 3 }]
+    );
+}
+
+#[test(tokio::test)]
+async fn test_paginate_offset() {
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        response = "Vec<PageUser>",
+        paginate(offset = "offset", limit = "limit")
+    )]
+    struct Test {
+        #[endpoint(query)]
+        limit: u64,
+    }
+
+    let t = TestServer::default();
+    let e = Test { limit: 2 };
+    let m2 = t.server.mock(|when, then| {
+        when.method(GET)
+            .path("/test/path")
+            .query_param("offset", "2");
+        then.status(200).json_body(json!([{ "id": 3 }]));
+    });
+    let m1 = t.server.mock(|when, then| {
+        when.method(GET)
+            .path("/test/path")
+            .query_param("limit", "2");
+        then.status(200)
+            .json_body(json!([{ "id": 1 }, { "id": 2 }]));
+    });
+    let r: Vec<_> = e
+        .exec_pages(&t.client)
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    m1.assert();
+    m2.assert();
+    assert_eq!(r.len(), 2);
+    assert_eq!(r[0].parse().unwrap().len(), 2);
+    assert_eq!(r[1].parse().unwrap(), vec![PageUser { id: 3 }]);
+}
+
+#[test(tokio::test)]
+async fn test_paginate_page() {
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        response = "Vec<PageUser>",
+        paginate(page = "page")
+    )]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m3 = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path").query_param("page", "3");
+        then.status(200).json_body(json!([]));
+    });
+    let m2 = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path").query_param("page", "2");
+        then.status(200).json_body(json!([{ "id": 2 }]));
+    });
+    let m1 = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(200).json_body(json!([{ "id": 1 }]));
+    });
+    let r: Vec<_> = e
+        .exec_items(&t.client)
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    m1.assert();
+    m2.assert();
+    m3.assert();
+    assert_eq!(r, vec![PageUser { id: 1 }, PageUser { id: 2 }]);
+}
+
+#[test(tokio::test)]
+async fn test_paginate_link() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", response = "Vec<PageUser>", paginate(link = true))]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m2 = t.server.mock(|when, then| {
+        when.method(GET)
+            .path("/test/other")
+            .query_param("after", "1");
+        then.status(200)
+            .header("Link", "</test/path>; rel=\"first\"")
+            .json_body(json!([{ "id": 2 }]));
+    });
+    let m1 = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(200)
+            .header(
+                "Link",
+                "</test/path>; title=\"a, <b>; rel=next\"; rel=\"first\", \
+                 <other?after=1>; rel=\"next last\"",
+            )
+            .json_body(json!([{ "id": 1 }]));
+    });
+    let r: Vec<_> = e
+        .exec_items(&t.client)
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    m1.assert();
+    m2.assert();
+    assert_eq!(r, vec![PageUser { id: 1 }, PageUser { id: 2 }]);
+}
+
+#[test(tokio::test)]
+async fn test_paginate_link_end() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", response = "Vec<PageUser>", paginate(link = true))]
+    struct Test {}
+
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/other", response = "Vec<PageUser>", paginate(link = true))]
+    struct TestSelf {}
+
+    let t = TestServer::default();
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(200)
+            .header("Link", "<http://example.com/test/path>; rel=\"next\"")
+            .json_body(json!([{ "id": 1 }]));
+    });
+    let s = t.server.mock(|when, then| {
+        when.method(GET).path("/test/other");
+        then.status(200)
+            .header("Link", "</test/other>; rel=\"next\"")
+            .json_body(json!([{ "id": 2 }]));
+    });
+    let r: Vec<_> = Test {}
+        .exec_items(&t.client)
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    m.assert_hits(1);
+    assert_eq!(r, vec![PageUser { id: 1 }]);
+
+    let r: Vec<_> = TestSelf {}
+        .exec_items(&t.client)
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+
+    s.assert_hits(1);
+    assert_eq!(r, vec![PageUser { id: 2 }]);
+}
+
+#[test(tokio::test)]
+async fn test_paginate_error() {
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        response = "Vec<PageUser>",
+        paginate(page = "page")
+    )]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m2 = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path").query_param("page", "2");
+        then.status(500);
+    });
+    let m1 = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(200).json_body(json!([{ "id": 1 }]));
+    });
+    let r: Vec<_> = e.exec_items(&t.client).unwrap().collect().await;
+
+    m1.assert();
+    m2.assert();
+    assert_eq!(r.len(), 2);
+    assert_eq!(r[0].as_ref().unwrap(), &PageUser { id: 1 });
+    assert!(matches!(
+        r[1],
+        Err(ClientError::ServerResponseError { code: 500, .. })
+    ));
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_paginate_blocking() {
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        response = "Vec<PageUser>",
+        paginate(offset = "offset", limit = "limit")
+    )]
+    struct Test {
+        #[endpoint(query)]
+        limit: u64,
+    }
+
+    let t = TestServerBlocking::default();
+    let e = Test { limit: 1 };
+    let m3 = t.server.mock(|when, then| {
+        when.method(GET)
+            .path("/test/path")
+            .query_param("offset", "2");
+        then.status(200).json_body(json!([]));
+    });
+    let m2 = t.server.mock(|when, then| {
+        when.method(GET)
+            .path("/test/path")
+            .query_param("offset", "1");
+        then.status(200).json_body(json!([{ "id": 2 }]));
+    });
+    let m1 = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(200).json_body(json!([{ "id": 1 }]));
+    });
+    let r = e
+        .exec_items_block(&t.client)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let pages = e.exec_pages_block(&t.client).unwrap().count();
+
+    m1.assert_hits(2);
+    m2.assert_hits(2);
+    m3.assert_hits(2);
+    assert_eq!(r, vec![PageUser { id: 1 }, PageUser { id: 2 }]);
+    assert_eq!(pages, 3);
+}
+
+#[test(tokio::test)]
+async fn test_retry() {
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        retry(max = 2, backoff = "constant", delay = 1, on = [503])
+    )]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(503);
+    });
+    let r = e.exec(&t.client).await;
+
+    m.assert_hits(3);
+    assert!(matches!(
+        r,
+        Err(ClientError::ServerResponseError { code: 503, .. })
+    ));
+
+    let r = e.with_middleware(&Middle {}).exec(&t.client).await;
+
+    m.assert_hits(6);
+    assert!(matches!(
+        r,
+        Err(ClientError::ServerResponseError { code: 503, .. })
+    ));
+}
+
+#[test(tokio::test)]
+async fn test_retry_after() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", retry(max = 1, delay = 60000))]
+    struct Test {}
+
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/other", retry(max = 1, max_delay = 1))]
+    struct TestCapped {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(429).header("Retry-After", "0");
+    });
+    let c = t.server.mock(|when, then| {
+        when.method(GET).path("/test/other");
+        then.status(429).header("Retry-After", "3600");
+    });
+    let start = Instant::now();
+    let r = e.exec(&t.client).await;
+
+    m.assert_hits(2);
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert!(matches!(
+        r,
+        Err(ClientError::ServerResponseError { code: 429, .. })
+    ));
+
+    let start = Instant::now();
+    let r = TestCapped {}.exec(&t.client).await;
+
+    c.assert_hits(2);
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert!(r.is_err());
+}
+
+#[test(tokio::test)]
+async fn test_retry_skipped() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", method = "POST", retry(delay = 1))]
+    struct Test {}
+
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        method = "POST",
+        retry(max = 1, delay = 1, non_idempotent = true)
+    )]
+    struct TestForced {}
+
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/other",
+        responses(200 = "()", 503 = "()"),
+        retry(delay = 1, on = [503])
+    )]
+    struct TestAccepted {}
+
+    let t = TestServer::default();
+    let m = t.server.mock(|when, then| {
+        when.method(POST).path("/test/path");
+        then.status(503);
+    });
+    let a = t.server.mock(|when, then| {
+        when.method(GET).path("/test/other");
+        then.status(503).body("null");
+    });
+    let r = Test {}.exec(&t.client).await;
+    m.assert_hits(1);
+    assert!(r.is_err());
+
+    let r = TestForced {}.exec(&t.client).await;
+    m.assert_hits(3);
+    assert!(r.is_err());
+
+    let r = TestAccepted {}.exec(&t.client).await;
+    a.assert_hits(1);
+    assert!(r.is_ok());
+}
+
+#[test(tokio::test)]
+async fn test_retry_request_error() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use rustified::{client::Client, clients::reqwest::Client as Reqwest};
+
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", retry(max = 2, delay = 1))]
+    struct Test {}
+
+    struct Counting {
+        client: Reqwest,
+        attempts: AtomicU32,
+    }
+
+    #[async_trait::async_trait]
+    impl Client for Counting {
+        async fn send(
+            &self,
+            req: http::Request<Vec<u8>>,
+        ) -> Result<http::Response<Vec<u8>>, ClientError> {
+            self.attempts.fetch_add(1, Ordering::SeqCst);
+            self.client.send(req).await
+        }
+
+        fn base(&self) -> &str {
+            self.client.base()
+        }
+    }
+
+    // Nothing listens on the port once the listener is dropped
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let client = Counting {
+        client: Reqwest::default(&format!("http://127.0.0.1:{}", port)),
+        attempts: AtomicU32::new(0),
+    };
+    let r = Test {}.exec(&client).await;
+
+    assert_eq!(client.attempts.load(Ordering::SeqCst), 3);
+    assert!(matches!(r, Err(ClientError::RequestError { .. })));
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_retry_blocking() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", retry(max = 1, delay = 1))]
+    struct Test {}
+
+    let t = TestServerBlocking::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(502);
+    });
+    let r = e.exec_block(&t.client);
+
+    m.assert_hits(2);
+    assert!(matches!(
+        r,
+        Err(ClientError::ServerResponseError { code: 502, .. })
+    ));
+}
+
+#[test]
+fn test_retry_policy() {
+    let retry = Retry::new(4).delay(Duration::from_millis(10));
+    assert_eq!(retry.backoff_delay(1), Duration::from_millis(10));
+    assert_eq!(retry.backoff_delay(3), Duration::from_millis(40));
+    assert_eq!(
+        retry.clone().backoff(Backoff::Constant).backoff_delay(3),
+        Duration::from_millis(10)
+    );
+    assert_eq!(retry.backoff_delay(u32::MAX), Duration::from_secs(30));
+    assert_eq!(
+        retry
+            .clone()
+            .max_delay(Duration::from_millis(25))
+            .backoff_delay(3),
+        Duration::from_millis(25)
+    );
+    assert_eq!(retry.on, vec![429, 502, 503, 504]);
+    assert!(retry.allows(&RequestMethod::PUT));
+    assert!(!retry.allows(&RequestMethod::POST));
+    assert!(retry.non_idempotent(true).allows(&RequestMethod::POST));
+}
+
+#[test(tokio::test)]
+async fn test_header() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", method = "POST")]
+    struct Test {
+        #[endpoint(header)]
+        x_request_id: u64,
+        #[endpoint(header = "X-Custom-Name")]
+        custom: String,
+        #[endpoint(header = "Accept")]
+        accept: Option<String>,
+        #[endpoint(header)]
+        x_missing: Option<String>,
+        name: String,
+    }
+
+    let t = TestServer::default();
+    let e = Test {
+        x_request_id: 42,
+        custom: "custom".to_string(),
+        accept: Some("application/vnd.test+json".to_string()),
+        x_missing: None,
+        name: "test".to_string(),
+    };
+    let m = t.server.mock(|when, then| {
+        when.method(POST)
+            .path("/test/path")
+            .header("X-Request-Id", "42")
+            .header("X-Custom-Name", "custom")
+            .header("Accept", "application/vnd.test+json")
+            .header_exists("Content-Type")
+            .json_body(json!({ "name": "test" }));
+        then.status(200);
+    });
+    let r = e.exec(&t.client).await;
+
+    m.assert();
+    assert!(r.is_ok());
+    assert!(!e.headers().unwrap().unwrap().contains_key("x-missing"));
+}
+
+#[test(tokio::test)]
+async fn test_static_headers() {
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        headers("Accept: application/vnd.github+json", "X-Api-Version: 2022-11-28")
+    )]
+    struct Test {
+        #[endpoint(header = "X-Api-Version")]
+        version: Option<String>,
+    }
+
+    let t = TestServer::default();
+    let m = t.server.mock(|when, then| {
+        when.method(GET)
+            .path("/test/path")
+            .header("Accept", "application/vnd.github+json")
+            .header("X-Api-Version", "2022-11-28");
+        then.status(200);
+    });
+    let r = Test { version: None }.exec(&t.client).await;
+
+    m.assert();
+    assert!(r.is_ok());
+
+    let e = Test {
+        version: Some("2023-01-01".to_string()),
+    };
+    let h = e.headers().unwrap().unwrap();
+    let versions = h.get_all("x-api-version").iter().collect::<Vec<_>>();
+    assert_eq!(versions, ["2022-11-28", "2023-01-01"]);
+}
+
+#[test(tokio::test)]
+async fn test_header_invalid() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path")]
+    struct Test {
+        #[endpoint(header)]
+        x_name: String,
+    }
+
+    let e = Test {
+        x_name: "bad\nvalue".to_string(),
+    };
+    let r = e.request("http://localhost");
+
+    match r {
+        Err(ClientError::HeaderValueError { name, .. }) => assert_eq!(name, "x-name"),
+        _ => panic!("expected a header value error"),
+    }
+}
+
+#[test(tokio::test)]
+async fn test_path_field() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "users/{name}/files/{self.kind}", method = "POST")]
+    struct Test {
+        #[endpoint(path)]
+        name: String,
+        #[endpoint(skip)]
+        kind: String,
+        data: String,
+    }
+
+    let t = TestServer::default();
+    let e = Test {
+        name: "a/b?c#d e%".to_string(),
+        kind: "50%2F".to_string(),
+        data: "test".to_string(),
+    };
+    let m = t.server.mock(|when, then| {
+        when.method(POST)
+            .path("/users/a%2Fb%3Fc%23d%20e%25/files/50%252F")
+            .json_body(json!({ "data": "test" }));
+        then.status(200);
+    });
+    let r = e.exec(&t.client).await;
+
+    m.assert();
+    assert!(r.is_ok());
+}
+
+#[test(tokio::test)]
+async fn test_path_field_slashes() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "secret/data/{path}")]
+    struct Test {
+        #[endpoint(path(slashes))]
+        path: String,
+    }
+
+    let t = TestServer::default();
+    let e = Test {
+        path: "apps/my app/a?b".to_string(),
+    };
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/secret/data/apps/my%20app/a%3Fb");
+        then.status(200);
+    });
+    let r = e.exec(&t.client).await;
+
+    m.assert();
+    assert!(r.is_ok());
+}
+
+#[test(tokio::test)]
+async fn test_path_untagged() {
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path/{self.name}/{{literal}}/{id}", method = "POST")]
+    struct Test {
+        name: String,
+        id: u64,
+        age: u8,
+    }
+
+    let t = TestServer::default();
+    let e = Test {
+        name: "test".to_string(),
+        id: 1,
+        age: 30,
+    };
+    let m = t.server.mock(|when, then| {
+        when.method(POST)
+            .path("/test/path/test/%7Bliteral%7D/1")
+            .json_body(json!({ "age": 30 }));
+        then.status(200);
+    });
+    let r = e.exec(&t.client).await;
+
+    m.assert();
+    assert!(r.is_ok());
+}
+
+#[test(tokio::test)]
+async fn test_enum() {
+    #[derive(Endpoint)]
+    #[endpoint(response = "TestResponse")]
+    enum Users {
+        #[endpoint(path = "users/{id}")]
+        Get { id: u64 },
+        #[endpoint(path = "users/{id}", method = "PUT")]
+        Update {
+            id: u64,
+            #[endpoint(query)]
+            force: bool,
+            name: String,
+        },
+        #[endpoint(path = "users", method = "DELETE")]
+        Clear,
+    }
+
+    let t = TestServer::default();
+    let get = t.server.mock(|when, then| {
+        when.method(GET).path("/users/1");
+        then.status(200).json_body(json!({ "age": 30 }));
+    });
+    let update = t.server.mock(|when, then| {
+        when.method(PUT)
+            .path("/users/2")
+            .query_param("force", "true")
+            .json_body(json!({ "name": "test" }));
+        then.status(200).json_body(json!({ "age": 31 }));
+    });
+    let clear = t.server.mock(|when, then| {
+        when.method(DELETE).path("/users");
+        then.status(200).json_body(json!({ "age": 0 }));
+    });
+
+    let r = Users::Get { id: 1 }.exec(&t.client).await;
+    assert_eq!(r.unwrap().parse().unwrap().age, 30);
+    let e = Users::Update {
+        id: 2,
+        force: true,
+        name: "test".to_string(),
+    };
+    let r = e.exec(&t.client).await;
+    assert_eq!(r.unwrap().parse().unwrap().age, 31);
+    let r = Users::Clear.exec(&t.client).await;
+    assert_eq!(r.unwrap().parse().unwrap().age, 0);
+
+    get.assert();
+    update.assert();
+    clear.assert();
+}
+
+#[test(tokio::test)]
+async fn test_enum_responses() {
+    #[derive(Endpoint)]
+    enum Users {
+        #[endpoint(path = "users/{id}", response = "TestResponse")]
+        Get { id: u64 },
+        #[endpoint(path = "users", response = "Vec<TestResponse>")]
+        List {
+            #[endpoint(header = "X-Page")]
+            page: u64,
+        },
+        #[endpoint(path = "users", method = "POST", response = "TestResponse")]
+        Create { age: u8 },
+        #[endpoint(path = "users/{id}", method = "DELETE")]
+        Delete { id: u64 },
+    }
+
+    let t = TestServer::default();
+    let get = t.server.mock(|when, then| {
+        when.method(GET).path("/users/1");
+        then.status(200).json_body(json!({ "age": 30 }));
+    });
+    let list = t.server.mock(|when, then| {
+        when.method(GET).path("/users").header("x-page", "2");
+        then.status(200)
+            .json_body(json!([{ "age": 30 }, { "age": 31 }]));
+    });
+    let create = t.server.mock(|when, then| {
+        when.method(POST)
+            .path("/users")
+            .json_body(json!({ "age": 32 }));
+        then.status(201).json_body(json!({ "age": 32 }));
+    });
+    let delete = t.server.mock(|when, then| {
+        when.method(DELETE).path("/users/1");
+        then.status(204);
+    });
+
+    let r = Users::Get { id: 1 }.exec(&t.client).await;
+    match r.unwrap().parse().unwrap() {
+        UsersResponse::Get(u) => assert_eq!(u.age, 30),
+        _ => panic!("expected the response of Get"),
+    }
+    let r = Users::List { page: 2 }.exec(&t.client).await;
+    match r.unwrap().parse().unwrap() {
+        UsersResponse::List(u) => assert_eq!(u.len(), 2),
+        _ => panic!("expected the response of List"),
+    }
+    let r = Users::Create { age: 32 }.exec(&t.client).await;
+    match r.unwrap().parse().unwrap() {
+        UsersResponse::Create(u) => assert_eq!(u.age, 32),
+        _ => panic!("expected the response of Create"),
+    }
+    let r = Users::Delete { id: 1 }.exec(&t.client).await;
+    assert!(matches!(
+        r.unwrap().parse(),
+        Err(ClientError::ResponseParseError { .. })
+    ));
+
+    get.assert();
+    list.assert();
+    create.assert();
+    delete.assert();
+}
+
+#[test(tokio::test)]
+async fn test_responses() {
+    #[derive(Deserialize)]
+    struct NotFound {
+        message: String,
+    }
+
+    #[derive(Endpoint)]
+    #[endpoint(path = "users/{id}", responses(200 = "TestResponse", 404 = "NotFound"))]
+    struct GetUser {
+        id: u64,
+    }
+
+    let t = TestServer::default();
+    let found = t.server.mock(|when, then| {
+        when.method(GET).path("/users/1");
+        then.status(200).json_body(json!({ "age": 30 }));
+    });
+    let missing = t.server.mock(|when, then| {
+        when.method(GET).path("/users/2");
+        then.status(404).json_body(json!({ "message": "missing" }));
+    });
+    let failed = t.server.mock(|when, then| {
+        when.method(GET).path("/users/3");
+        then.status(500).body("failed");
+    });
+
+    let r = GetUser { id: 1 }.exec(&t.client).await;
+    match r.unwrap().parse().unwrap() {
+        GetUserResponse::Ok(u) => assert_eq!(u.age, 30),
+        GetUserResponse::NotFound(_) => panic!("expected a user"),
+    }
+    let r = GetUser { id: 2 }.exec(&t.client).await;
+    match r.unwrap().parse().unwrap() {
+        GetUserResponse::NotFound(e) => assert_eq!(e.message, "missing"),
+        GetUserResponse::Ok(_) => panic!("expected an error"),
+    }
+    let r = GetUser { id: 3 }.exec(&t.client).await;
+    assert!(matches!(
+        r,
+        Err(ClientError::ServerResponseError { code: 500, .. })
+    ));
+
+    found.assert();
+    missing.assert();
+    failed.assert();
+}
+
+#[test(tokio::test)]
+async fn test_responses_default() {
+    #[derive(Deserialize)]
+    struct ApiError {
+        message: String,
+    }
+
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "users",
+        method = "POST",
+        responses(201 = "TestResponse", default = "ApiError")
+    )]
+    struct CreateUser {
+        age: u8,
+    }
+
+    let t = TestServer::default();
+    let m = t.server.mock(|when, then| {
+        when.method(POST).path("/users");
+        then.status(409).json_body(json!({ "message": "exists" }));
+    });
+    let r = CreateUser { age: 30 }.exec(&t.client).await;
+
+    m.assert();
+    match r.unwrap().parse().unwrap() {
+        CreateUserResponse::Default(status, e) => {
+            assert_eq!(status, 409);
+            assert_eq!(e.message, "exists");
+        }
+        CreateUserResponse::Created(u) => panic!("expected an error, got {}", u.age),
+    }
+}
+
+#[test(tokio::test)]
+async fn test_error() {
+    #[derive(Debug, Deserialize)]
+    struct ApiError {
+        code: String,
+    }
+
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path/{name}", error = "ApiError")]
+    struct Test {
+        name: String,
+    }
+
+    let t = TestServer::default();
+    let quota = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path/quota");
+        then.status(429)
+            .header("retry-after", "10")
+            .json_body(json!({ "code": "quota_exceeded" }));
+    });
+    let invalid = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path/invalid");
+        then.status(500).body("failed");
+    });
+
+    let r = Test {
+        name: "quota".to_string(),
+    }
+    .exec(&t.client)
+    .await;
+    let err = r.err().unwrap();
+    assert_eq!(err.api_error::<ApiError>().unwrap().code, "quota_exceeded");
+    match err {
+        ClientError::Api {
+            status, headers, ..
+        } => {
+            assert_eq!(status, 429);
+            assert_eq!(headers["retry-after"], "10");
+        }
+        e => panic!("unexpected error: {}", e),
+    }
+
+    let r = Test {
+        name: "invalid".to_string(),
+    }
+    .exec(&t.client)
+    .await;
+    assert!(matches!(
+        r,
+        Err(ClientError::ServerResponseError { code: 500, .. })
+    ));
+
+    quota.assert();
+    invalid.assert();
+}
+
+#[test(tokio::test)]
+async fn test_error_stream() {
+    #[derive(Debug, Deserialize)]
+    struct ApiError {
+        code: String,
+    }
+
+    #[derive(Endpoint)]
+    #[endpoint(path = "test/path", error = "ApiError")]
+    struct Test {}
+
+    let t = TestServer::default();
+    let e = Test {};
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/test/path");
+        then.status(403).json_body(json!({ "code": "forbidden" }));
+    });
+
+    let r = e.exec_stream(&t.client).await;
+    let err = r.err().unwrap();
+    assert_eq!(err.api_error::<ApiError>().unwrap().code, "forbidden");
+    let r: Vec<_> = e.exec_events(&t.client).unwrap().collect().await;
+    assert_eq!(r.len(), 1);
+    let err = r.into_iter().next().unwrap().err().unwrap();
+    assert_eq!(err.api_error::<ApiError>().unwrap().code, "forbidden");
+
+    m.assert_hits(2);
+}
+
+#[derive(Endpoint)]
+#[endpoint(path = "users/{name}", response = "TestResponse")]
+struct GetUser {
+    #[endpoint(path)]
+    name: String,
+}
+
+#[derive(Endpoint)]
+enum Users {
+    #[endpoint(path = "users/{name}", method = "DELETE")]
+    Delete {
+        #[endpoint(path)]
+        name: String,
+    },
+}
+
+/// A client for the test API.
+#[rustified::api]
+trait TestApi {
+    /// Returns the user with the given name.
+    fn get_user(&self, name: String) -> GetUser;
+
+    fn delete_user(name: &str) -> Users {
+        Users::Delete {
+            name: name.to_string(),
+        }
+    }
+}
+
+#[test(tokio::test)]
+async fn test_api() {
+    let t = TestServer::default();
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/users/test");
+        then.status(200).json_body(json!({ "age": 30 }));
+    });
+    let d = t.server.mock(|when, then| {
+        when.method(DELETE).path("/users/test");
+        then.status(200).body("null");
+    });
+    let api = TestApi::new(t.client.clone());
+    let r = api.get_user("test".to_string()).await;
+    let dr = api.delete_user("test").await;
+
+    m.assert();
+    d.assert();
+    assert_eq!(r.unwrap().age, 30);
+    assert!(dr.is_ok());
+    assert_eq!(api.client().base, t.client.base);
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_api_blocking() {
+    let t = TestServerBlocking::default();
+    let m = t.server.mock(|when, then| {
+        when.method(GET).path("/users/test");
+        then.status(200).json_body(json!({ "age": 30 }));
+    });
+    let r = TestApi::new(t.client).get_user_block("test".to_string());
+
+    m.assert();
+    assert_eq!(r.unwrap().age, 30);
+}
+
+#[cfg(feature = "openapi")]
+#[test]
+#[allow(dead_code)]
+fn test_openapi() {
+    use rustified::openapi::{Describe, OpenApi};
+
+    #[derive(Deserialize)]
+    struct Opaque {}
+
+    /// Updates a user.
+    ///
+    /// Only the given fields are changed.
+    #[derive(Endpoint)]
+    #[endpoint(path = "users/{self.id}", method = "PATCH", response = "Opaque")]
+    struct UpdateUser {
+        #[endpoint(skip)]
+        id: u64,
+        #[endpoint(query(style = "comma"))]
+        fields: Vec<String>,
+        #[endpoint(header = "If-Match")]
+        etag: Option<String>,
+        #[serde(rename = "displayName")]
+        name: String,
+        age: Option<u8>,
+    }
+
+    #[derive(Endpoint)]
+    #[endpoint(responses(200 = "Vec<String>", 404 = "()"))]
+    enum Users {
+        /// Lists all users.
+        #[endpoint(path = "users")]
+        List {},
+        #[endpoint(path = "users/{id}", method = "LIST")]
+        Hidden { id: u64 },
+    }
+
+    assert_eq!(Users::operations().len(), 2);
+    let doc = OpenApi::new("Test", "1.0.0")
+        .server("http://api.com")
+        .endpoint::<UpdateUser>()
+        .endpoint::<Users>()
+        .to_value();
+    assert_eq!(
+        doc,
+        json!({
+            "openapi": "3.1.0",
+            "info": { "title": "Test", "version": "1.0.0" },
+            "servers": [{ "url": "http://api.com" }],
+            "paths": {
+                "/users/{id}": {
+                    "patch": {
+                        "operationId": "UpdateUser",
+                        "summary": "Updates a user.",
+                        "description": "Only the given fields are changed.",
+                        "parameters": [
+                            {
+                                "name": "id",
+                                "in": "path",
+                                "required": true,
+                                "schema": { "type": "integer", "minimum": 0 },
+                            },
+                            {
+                                "name": "fields",
+                                "in": "query",
+                                "required": true,
+                                "schema": { "type": "array", "items": { "type": "string" } },
+                                "style": "form",
+                                "explode": false,
+                            },
+                            {
+                                "name": "if-match",
+                                "in": "header",
+                                "required": false,
+                                "schema": { "type": ["string", "null"] },
+                            },
+                        ],
+                        "requestBody": {
+                            "required": true,
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "displayName": { "type": "string" },
+                                            "age": {
+                                                "type": ["integer", "null"],
+                                                "minimum": 0,
+                                            },
+                                        },
+                                        "required": ["displayName"],
+                                    },
+                                },
+                            },
+                        },
+                        "responses": {
+                            "200": {
+                                "description": "OK",
+                                "content": { "application/json": { "schema": {} } },
+                            },
+                        },
+                    },
+                },
+                "/users": {
+                    "get": {
+                        "operationId": "UsersList",
+                        "summary": "Lists all users.",
+                        "responses": {
+                            "200": {
+                                "description": "OK",
+                                "content": {
+                                    "application/json": {
+                                        "schema": { "type": "array", "items": { "type": "string" } },
+                                    },
+                                },
+                            },
+                            "404": { "description": "Not Found" },
+                        },
+                    },
+                },
+            },
+        })
+    );
+}
+
+#[cfg(feature = "openapi")]
+#[test]
+#[allow(dead_code)]
+fn test_openapi_schema() {
+    use rustified::openapi::Schema;
+
+    #[derive(Deserialize, Schema)]
+    struct User {
+        #[serde(rename = "displayName")]
+        name: String,
+        email: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(skip)]
+        cache: u64,
+        role: Role,
+        id: UserId,
+    }
+
+    #[derive(Deserialize, Schema)]
+    struct UserId(u64);
+
+    #[derive(Deserialize, Schema)]
+    enum Role {
+        Admin,
+        #[serde(rename = "member")]
+        Member,
+        Guest {
+            until: u64,
+        },
+    }
+
+    #[derive(Deserialize, Schema)]
+    #[serde(untagged)]
+    enum Id {
+        Number(u64),
+        Name(String),
+    }
+
+    assert_eq!(
+        User::schema(),
+        json!({
+            "type": "object",
+            "properties": {
+                "displayName": { "type": "string" },
+                "email": { "type": ["string", "null"] },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "role": {
+                    "oneOf": [
+                        { "type": "string", "enum": ["Admin", "member"] },
+                        {
+                            "type": "object",
+                            "properties": {
+                                "Guest": {
+                                    "type": "object",
+                                    "properties": {
+                                        "until": { "type": "integer", "minimum": 0 },
+                                    },
+                                    "required": ["until"],
+                                },
+                            },
+                            "required": ["Guest"],
+                        },
+                    ],
+                },
+                "id": { "type": "integer", "minimum": 0 },
+            },
+            "required": ["displayName", "role", "id"],
+        })
+    );
+    assert_eq!(
+        Id::schema(),
+        json!({
+            "oneOf": [
+                { "type": "integer", "minimum": 0 },
+                { "type": "string" },
+            ],
+        })
+    );
+}
+
+#[test(tokio::test)]
+async fn test_builder() {
+    #[derive(Builder, Endpoint)]
+    #[endpoint(path = "test/path", method = "POST", builder = "true")]
+    #[builder(setter(into))]
+    struct Test {
+        name: String,
+    }
+
+    let t = TestServer::default();
+    let m = t.server.mock(|when, then| {
+        when.method(POST).path("/test/path");
+        then.status(200);
+    });
+    let r = Test::builder()
+        .name("test")
+        .build()
+        .unwrap()
+        .exec(&t.client)
+        .await;
+
+    m.assert();
+    assert!(r.is_ok());
+}
+
+#[test(tokio::test)]
+async fn test_unquoted_params() {
+    #[derive(Endpoint)]
+    #[endpoint(
+        path = "test/path",
+        method = POST,
+        response = Vec<TestResponse>,
+        request_type = JSON,
+        builder = false
+    )]
+    struct Test {
+        name: String,
+    }
+
+    #[derive(Deserialize)]
+    struct TestResponse {
+        age: u8,
+    }
+
+    let t = TestServer::default();
+    let m = t.server.mock(|when, then| {
+        when.method(POST)
+            .path("/test/path")
+            .json_body(json!({ "name": "test" }));
+        then.status(200).json_body(json!([{ "age": 30 }]));
+    });
+    let r = Test {
+        name: "test".to_string(),
+    }
+    .exec(&t.client)
+    .await;
+
+    m.assert();
+    assert_eq!(r.unwrap().parse().unwrap()[0].age, 30);
 }
 
 #[test(tokio::test)]
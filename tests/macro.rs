@@ -1,5 +1,5 @@
 #[test]
 fn test_macro() {
-    // let t = trybuild::TestCases::new();
-    // t.compile_fail("tests/macro/*.rs");
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/macro/*.rs");
 }
@@ -0,0 +1,42 @@
+use rustified_derive::{api, Endpoint};
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path")]
+struct Test {
+    name: String,
+}
+
+#[api]
+trait TestApi {
+    fn new(name: String) -> Test;
+}
+
+#[api]
+trait TestApiTwo {
+    fn missing(name: String);
+}
+
+#[api]
+trait TestApiThree {
+    async fn asynchronous(name: String) -> Test;
+}
+
+#[api]
+trait TestApiFour {
+    fn pattern((name, _): (String, u8)) -> Test;
+}
+
+#[api]
+trait TestApiFive {
+    fn tuple(name: String) -> (Test,);
+}
+
+#[api]
+trait TestApiSix {
+    const NAME: &'static str;
+}
+
+#[api(blocking)]
+trait TestApiSeven {}
+
+fn main() {}
@@ -0,0 +1,41 @@
+use rustified::endpoint::Endpoint;
+use rustified_derive::Endpoint;
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path")]
+enum Test {
+    #[endpoint(path = "test/path")]
+    Get,
+}
+
+#[derive(Endpoint)]
+enum TestTwo {
+    #[endpoint(path = "test/path", request_type = "FORM")]
+    Get,
+}
+
+#[derive(Endpoint)]
+#[endpoint(response = "String")]
+enum TestThree {
+    #[endpoint(path = "test/path", response = "String")]
+    Get,
+}
+
+#[derive(Endpoint)]
+enum TestFour {
+    Get,
+}
+
+#[derive(Endpoint)]
+enum TestFive {
+    #[endpoint(path = "test/{id}")]
+    Get(u64),
+}
+
+#[derive(Endpoint)]
+enum TestSix {
+    #[endpoint(path = "test/{name}")]
+    Get { id: u64 },
+}
+
+fn main() {}
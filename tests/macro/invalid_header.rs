@@ -0,0 +1,35 @@
+use rustified::endpoint::Endpoint;
+use rustified_derive::Endpoint;
+use serde::Serialize;
+
+#[derive(Debug, Endpoint, Serialize)]
+#[endpoint(path = "test/path")]
+struct Test {
+    #[endpoint(header = "X Name")]
+    pub name: String,
+}
+
+#[derive(Debug, Endpoint, Serialize)]
+#[endpoint(path = "test/path")]
+struct TestTwo {
+    #[endpoint(query = "name")]
+    pub name: String,
+}
+
+#[derive(Debug, Endpoint, Serialize)]
+#[endpoint(path = "test/path", headers("Accept"))]
+struct TestThree {}
+
+#[derive(Debug, Endpoint, Serialize)]
+#[endpoint(path = "test/path", headers("X Name: value"))]
+struct TestFour {}
+
+#[derive(Debug, Endpoint, Serialize)]
+#[endpoint(path = "test/path", headers = "Accept: text/plain")]
+struct TestFive {}
+
+#[derive(Debug, Endpoint, Serialize)]
+#[endpoint(path = "test/path", headers("X-Name: café"))]
+struct TestSix {}
+
+fn main() {}
@@ -0,0 +1,31 @@
+use rustified::endpoint::Endpoint;
+use rustified_derive::Endpoint;
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", response = "Vec<String>", paginate(cursor = "next"))]
+struct Test {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", response = "Vec<String>", paginate(page = "page", link = true))]
+struct TestTwo {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", response = "Vec<String>", paginate(size = "size"))]
+struct TestThree {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", response = "Vec<String>", paginate(link = true, limit = "limit"))]
+struct TestFour {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", response = "String", paginate(page = "page"))]
+struct TestFive {}
+
+#[derive(Endpoint)]
+#[endpoint(paginate(page = "page"))]
+enum TestSix {
+    #[endpoint(path = "test/path")]
+    List,
+}
+
+fn main() {}
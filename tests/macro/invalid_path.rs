@@ -0,0 +1,50 @@
+use rustified::endpoint::Endpoint;
+use rustified_derive::Endpoint;
+use serde::Serialize;
+
+#[derive(Debug, Endpoint, Serialize)]
+#[endpoint(path = "test/path/{self.nmae}")]
+struct Test {
+    pub name: String,
+}
+
+#[derive(Debug, Endpoint, Serialize)]
+#[endpoint(path = "test/path/{nmae}")]
+struct TestTwo {
+    pub name: String,
+}
+
+#[derive(Debug, Endpoint, Serialize)]
+#[endpoint(path = "test/path/{self.name")]
+struct TestThree {
+    pub name: String,
+}
+
+#[derive(Debug, Endpoint, Serialize)]
+#[endpoint(path = "test/path}/{self.name}")]
+struct TestFour {
+    pub name: String,
+}
+
+#[derive(Debug, Endpoint, Serialize)]
+#[endpoint(path = "test/path/{}")]
+struct TestFive {
+    pub name: String,
+}
+
+#[derive(Debug, Endpoint, Serialize)]
+#[endpoint(path = "test/path/{self.name.nope()}")]
+struct TestSix {
+    pub name: String,
+}
+
+#[derive(Debug, Endpoint, Serialize)]
+#[endpoint(path = "test/path/{name}")]
+struct TestSeven {
+    #[endpoint(path)]
+    pub name: String,
+    #[endpoint(path)]
+    pub version: u64,
+}
+
+fn main() {}
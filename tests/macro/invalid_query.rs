@@ -0,0 +1,33 @@
+use rustified::endpoint::Endpoint;
+use rustified_derive::Endpoint;
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path")]
+struct Test {
+    #[endpoint(query(style = "csv"))]
+    pub tag: Vec<String>,
+}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", query(style = csv))]
+struct TestTwo {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", query(format = comma))]
+struct TestThree {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path")]
+struct TestFour {
+    #[endpoint(query(explode))]
+    pub tag: Vec<String>,
+}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path")]
+struct TestFive {
+    #[endpoint(query(style = csv))]
+    pub tag: Vec<String>,
+}
+
+fn main() {}
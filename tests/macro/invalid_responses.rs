@@ -0,0 +1,20 @@
+use rustified::endpoint::Endpoint;
+use rustified_derive::Endpoint;
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", responses(200 = "String", 200 = "String"))]
+struct Test {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", responses(600 = "String"))]
+struct TestTwo {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", responses(other = "String"))]
+struct TestThree {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", response = "String", responses(200 = "String"))]
+struct TestFour {}
+
+fn main() {}
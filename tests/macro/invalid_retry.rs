@@ -0,0 +1,26 @@
+use rustified::endpoint::Endpoint;
+use rustified_derive::Endpoint;
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", retry(attempts = 3))]
+struct Test {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", retry(backoff = "linear"))]
+struct TestTwo {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", retry(on = [503, 700]))]
+struct TestThree {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", retry(max = "three"))]
+struct TestFour {}
+
+#[derive(Endpoint)]
+enum TestFive {
+    #[endpoint(path = "test/path", retry(max = 3))]
+    List,
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use rustified::endpoint::Endpoint;
+use rustified_derive::Endpoint;
+
+#[derive(Endpoint)]
+#[endpoint(path = test)]
+struct Test {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", builder = "yes")]
+struct TestTwo {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", method = 5)]
+struct TestThree {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", response = [String])]
+struct TestFour {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", response = 1.5)]
+struct TestFive {}
+
+#[derive(Endpoint)]
+#[endpoint(path = "test/path", headers(Accept))]
+struct TestSix {}
+
+fn main() {}